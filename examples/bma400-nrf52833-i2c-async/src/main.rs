@@ -1,7 +1,7 @@
 #![no_std]
 #![no_main]
 
-use bma400::{BMA400, I2CInterface, InterruptPins, OutputDataRate, PowerMode};
+use bma400::{AsyncInterface, BMA400, I2CInterface, InterruptPins, OutputDataRate, PowerMode};
 use defmt::{error, info};
 use defmt_rtt as _;
 use embassy_executor::Spawner;
@@ -32,7 +32,8 @@ async fn main(_spawner: Spawner) {
     info!("Done!");
 
     info!("Initializing the BMA400...");
-    let mut accel = Mutex::<CriticalSectionRawMutex, _>::new(BMA400::new_i2c(i2c).await.unwrap());
+    let mut accel =
+        Mutex::<CriticalSectionRawMutex, _>::new(BMA400::new_i2c_async(i2c).await.unwrap());
     info!("Done");
 
     // BMA400: Set the power mode to normal and the output data rate to 200Hz
@@ -74,7 +75,10 @@ async fn main(_spawner: Spawner) {
 }
 
 async fn handle_tap(
-    shared_accel: &Mutex<CriticalSectionRawMutex, BMA400<I2CInterface<Twim<'static, TWISPI0>>>>,
+    shared_accel: &Mutex<
+        CriticalSectionRawMutex,
+        BMA400<AsyncInterface<I2CInterface<Twim<'static, TWISPI0>>>>,
+    >,
     mut tap_pin: Input<'_>,
 ) {
     tap_pin.wait_for_high().await;
@@ -98,7 +102,10 @@ async fn handle_tap(
 }
 
 async fn sample_acceleration(
-    shared_accel: &Mutex<CriticalSectionRawMutex, BMA400<I2CInterface<Twim<'static, TWISPI0>>>>,
+    shared_accel: &Mutex<
+        CriticalSectionRawMutex,
+        BMA400<AsyncInterface<I2CInterface<Twim<'static, TWISPI0>>>>,
+    >,
 ) {
     loop {
         Timer::after_millis(2000).await;
@@ -1,7 +1,7 @@
 #![no_std]
 #![no_main]
 
-use bma400::{BMA400, InterruptPins, OutputDataRate, PowerMode, SPIInterface};
+use bma400::{AsyncInterface, BMA400, InterruptPins, OutputDataRate, PowerMode, SPIInterface};
 use defmt::{error, info};
 use defmt_rtt as _;
 use embassy_executor::Spawner;
@@ -32,7 +32,8 @@ async fn main(_spawner: Spawner) {
     info!("Done!");
 
     info!("Initializing the BMA400...");
-    let mut accel = Mutex::<CriticalSectionRawMutex, _>::new(BMA400::new_spi(spi).await.unwrap());
+    let mut accel =
+        Mutex::<CriticalSectionRawMutex, _>::new(BMA400::new_spi_async(spi).await.unwrap());
     info!("Done");
 
     // BMA400: Set the power mode to normal and the output data rate to 200Hz
@@ -76,7 +77,11 @@ async fn main(_spawner: Spawner) {
 async fn handle_tap(
     shared_accel: &Mutex<
         CriticalSectionRawMutex,
-        BMA400<SPIInterface<ExclusiveDevice<Spim<'static, TWISPI0>, Output<'_>, Delay>>>,
+        BMA400<
+            AsyncInterface<
+                SPIInterface<ExclusiveDevice<Spim<'static, TWISPI0>, Output<'_>, Delay>>,
+            >,
+        >,
     >,
     mut tap_pin: Input<'_>,
 ) {
@@ -103,7 +108,11 @@ async fn handle_tap(
 async fn sample_acceleration(
     shared_accel: &Mutex<
         CriticalSectionRawMutex,
-        BMA400<SPIInterface<ExclusiveDevice<Spim<'static, TWISPI0>, Output<'_>, Delay>>>,
+        BMA400<
+            AsyncInterface<
+                SPIInterface<ExclusiveDevice<Spim<'static, TWISPI0>, Output<'_>, Delay>>,
+            >,
+        >,
     >,
 ) {
     loop {
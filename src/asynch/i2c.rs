@@ -1,25 +1,42 @@
 use crate::{
-    BMA400, BMA400Error, Config, I2CInterface,
+    AsyncInterface, BMA400, BMA400Error, I2CInterface,
     asynch::{ReadFromRegister, WriteToRegister},
-    embedded_hal_async::i2c::{I2c, SevenBitAddress},
+    config::CONFIG_BLOCK_LEN,
     registers::{ChipId, ConfigReg, ReadReg},
 };
+use embedded_hal_async::i2c::{I2c, SevenBitAddress};
 
-impl<I2C> WriteToRegister for I2CInterface<I2C>
+impl<I2C> WriteToRegister for AsyncInterface<I2CInterface<I2C>>
 where
     I2C: I2c<SevenBitAddress>,
 {
     type Error = BMA400Error<I2C::Error>;
 
     async fn write_register<T: ConfigReg>(&mut self, register: T) -> Result<(), Self::Error> {
-        self.i2c
-            .write(self.addr, &[register.addr(), register.to_byte()])
+        self.interface
+            .i2c
+            .write(self.interface.addr, &[register.addr(), register.to_byte()])
             .await
             .map_err(BMA400Error::IOError)
     }
+
+    async fn write_registers(&mut self, start: u8, data: &[u8]) -> Result<(), Self::Error> {
+        // Start address followed by the data, the device increments the address after each byte
+        let mut buffer = [0u8; CONFIG_BLOCK_LEN + 1];
+        for (i, chunk) in data.chunks(CONFIG_BLOCK_LEN).enumerate() {
+            buffer[0] = start + (i * CONFIG_BLOCK_LEN) as u8;
+            buffer[1..=chunk.len()].copy_from_slice(chunk);
+            self.interface
+                .i2c
+                .write(self.interface.addr, &buffer[..=chunk.len()])
+                .await
+                .map_err(BMA400Error::IOError)?;
+        }
+        Ok(())
+    }
 }
 
-impl<I2C> ReadFromRegister for I2CInterface<I2C>
+impl<I2C> ReadFromRegister for AsyncInterface<I2CInterface<I2C>>
 where
     I2C: I2c<SevenBitAddress>,
 {
@@ -30,14 +47,15 @@ where
         register: T,
         buffer: &mut [u8],
     ) -> Result<(), Self::Error> {
-        self.i2c
-            .write_read(self.addr, &[register.addr()], buffer)
+        self.interface
+            .i2c
+            .write_read(self.interface.addr, &[register.addr()], buffer)
             .await
             .map_err(BMA400Error::IOError)
     }
 }
 
-impl<I2C> BMA400<I2CInterface<I2C>>
+impl<I2C> BMA400<AsyncInterface<I2CInterface<I2C>>>
 where
     I2C: I2c<SevenBitAddress>,
 {
@@ -49,22 +67,27 @@ where
     /// ```
     /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
     /// use bma400::BMA400;
+    /// # async fn example() {
     /// # let expected = vec![Transaction::write_read(0b10100, vec![0x00], vec![0x90])];
     /// # let mut i2c = Mock::new(&expected);
-    /// // i2c implements embedded-hal i2c::I2c
-    /// let mut accelerometer = BMA400::new_i2c(&mut i2c);
+    /// // i2c implements embedded-hal-async i2c::I2c
+    /// let mut accelerometer = BMA400::new_i2c_async(&mut i2c).await;
     /// assert!(accelerometer.is_ok());
     /// # i2c.done();
+    /// # }
     /// ```
-    pub async fn new_i2c(i2c: I2C) -> Result<BMA400<I2CInterface<I2C>>, BMA400Error<I2C::Error>> {
-        let mut interface = I2CInterface { addr: 0b10100, i2c };
-        let config = Config::default();
+    pub async fn new_i2c_async(
+        i2c: I2C,
+    ) -> Result<BMA400<AsyncInterface<I2CInterface<I2C>>>, BMA400Error<I2C::Error>> {
+        let mut interface = AsyncInterface {
+            interface: I2CInterface { addr: 0b10100, i2c },
+        };
         let mut chip_id = [0u8; 1];
         interface.read_register(ChipId, &mut chip_id).await?;
         if chip_id[0] != 0x90 {
             Err(BMA400Error::ChipIdReadFailed)
         } else {
-            Ok(BMA400 { interface, config })
+            Ok(BMA400::new(interface))
         }
     }
 
@@ -76,24 +99,87 @@ where
     /// ```
     /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
     /// use bma400::BMA400;
+    /// # async fn example() {
     /// # let expected = vec![Transaction::write_read(0b10101, vec![0x00], vec![0x90])];
     /// # let mut i2c = Mock::new(&expected);
-    /// // i2c implements embedded-hal i2c::I2c
-    /// let mut accelerometer = BMA400::new_i2c_alt(&mut i2c);
+    /// // i2c implements embedded-hal-async i2c::I2c
+    /// let mut accelerometer = BMA400::new_i2c_alt_async(&mut i2c).await;
     /// assert!(accelerometer.is_ok());
     /// # i2c.done();
+    /// # }
     /// ```
-    pub async fn new_i2c_alt(
+    pub async fn new_i2c_alt_async(
         i2c: I2C,
-    ) -> Result<BMA400<I2CInterface<I2C>>, BMA400Error<I2C::Error>> {
-        let mut interface = I2CInterface { addr: 0b10100, i2c };
-        let config = Config::default();
+    ) -> Result<BMA400<AsyncInterface<I2CInterface<I2C>>>, BMA400Error<I2C::Error>> {
+        let mut interface = AsyncInterface {
+            interface: I2CInterface { addr: 0b10101, i2c },
+        };
         let mut chip_id = [0u8; 1];
         interface.read_register(ChipId, &mut chip_id).await?;
         if chip_id[0] != 0x90 {
             Err(BMA400Error::ChipIdReadFailed)
         } else {
-            Ok(BMA400 { interface, config })
+            Ok(BMA400::new(interface))
         }
     }
+
+    /// Create a new instance of the BMA400 using I²C
+    /// with the default address (0b010100), adopting the configuration
+    /// already held in the device registers instead of assuming reset values
+    ///
+    /// See [`resync_config()`](BMA400::resync_config)
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// use bma400::BMA400;
+    /// # async fn example() {
+    /// # let expected = vec![
+    /// #     Transaction::write_read(0b10100, vec![0x00], vec![0x90]),
+    /// #     Transaction::write_read(0b10100, vec![0x19], vec![0u8; 64]),
+    /// # ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// // i2c implements embedded-hal-async i2c::I2c
+    /// let mut accelerometer = BMA400::new_i2c_adopt_async(&mut i2c).await;
+    /// assert!(accelerometer.is_ok());
+    /// # i2c.done();
+    /// # }
+    /// ```
+    pub async fn new_i2c_adopt_async(
+        i2c: I2C,
+    ) -> Result<BMA400<AsyncInterface<I2CInterface<I2C>>>, BMA400Error<I2C::Error>> {
+        let mut device = Self::new_i2c_async(i2c).await?;
+        device.resync_config().await?;
+        Ok(device)
+    }
+
+    /// Create a new instance of the BMA400 using I²C
+    /// with the alternate address (0b010101), adopting the configuration
+    /// already held in the device registers instead of assuming reset values
+    ///
+    /// See [`resync_config()`](BMA400::resync_config)
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// use bma400::BMA400;
+    /// # async fn example() {
+    /// # let expected = vec![
+    /// #     Transaction::write_read(0b10101, vec![0x00], vec![0x90]),
+    /// #     Transaction::write_read(0b10101, vec![0x19], vec![0u8; 64]),
+    /// # ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// // i2c implements embedded-hal-async i2c::I2c
+    /// let mut accelerometer = BMA400::new_i2c_alt_adopt_async(&mut i2c).await;
+    /// assert!(accelerometer.is_ok());
+    /// # i2c.done();
+    /// # }
+    /// ```
+    pub async fn new_i2c_alt_adopt_async(
+        i2c: I2C,
+    ) -> Result<BMA400<AsyncInterface<I2CInterface<I2C>>>, BMA400Error<I2C::Error>> {
+        let mut device = Self::new_i2c_alt_async(i2c).await?;
+        device.resync_config().await?;
+        Ok(device)
+    }
 }
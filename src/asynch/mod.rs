@@ -1,9 +1,19 @@
-use crate::{BMA400, BMA400Error, DelayNs, config::*, private, registers::*, types::*};
+use crate::{
+    AsyncInterface, BMA400, BMA400Error, CMD_READY_POLLS, CMD_READY_TIMEOUT_US, DelayNs,
+    SOFT_RESET_DELAY_MS, STATUS_POLL_US,
+    config::*,
+    private,
+    registers::*,
+    types::*,
+    typestate::{DetectsTaps, ReadsFifo},
+};
 
 #[cfg(any(feature = "i2c", test))]
 mod i2c;
 #[cfg(any(feature = "spi", test))]
 mod spi;
+mod wait;
+pub use wait::{InterruptListener, NoPin};
 
 // Suppress Lint: This will only be used inside the crate
 #[allow(async_fn_in_trait)]
@@ -14,6 +24,10 @@ pub trait ReadFromRegister: private::Sealed {
         register: T,
         buffer: &mut [u8],
     ) -> Result<(), Self::Error>;
+    /// Restores the interface mode after the device is reset
+    async fn after_reset(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
 }
 
 // Suppress Lint: This will only be used inside the crate
@@ -21,11 +35,13 @@ pub trait ReadFromRegister: private::Sealed {
 pub trait WriteToRegister: private::Sealed {
     type Error;
     async fn write_register<T: ConfigReg>(&mut self, register: T) -> Result<(), Self::Error>;
+    /// Writes `data` to consecutive registers starting at `start`
+    async fn write_registers(&mut self, start: u8, data: &[u8]) -> Result<(), Self::Error>;
 }
 
-impl<T, InterfaceError> BMA400<T>
+impl<T, S, InterfaceError> BMA400<AsyncInterface<T>, S>
 where
-    T: ReadFromRegister<Error = BMA400Error<InterfaceError>>
+    AsyncInterface<T>: ReadFromRegister<Error = BMA400Error<InterfaceError>>
         + WriteToRegister<Error = BMA400Error<InterfaceError>>,
 {
     /// Returns the chip ID (0x90)
@@ -77,7 +93,7 @@ where
     /// ```
     pub async fn get_cmd_error(&mut self) -> Result<bool, BMA400Error<InterfaceError>> {
         let mut err_byte = [0u8; 1];
-        self.interface.read_register(ErrReg, &mut err_byte).await?;
+        self.read_register(ErrReg, &mut err_byte).await?;
         Ok(err_byte[0] & 0b00000010 != 0)
     }
 
@@ -103,12 +119,111 @@ where
     /// ```
     pub async fn get_status(&mut self) -> Result<Status, BMA400Error<InterfaceError>> {
         let mut status_byte = [0u8; 1];
-        self.interface
-            .read_register(StatusReg, &mut status_byte)
-            .await?;
+        self.read_register(StatusReg, &mut status_byte).await?;
         Ok(Status::new(status_byte[0]))
     }
 
+    /// Polls the [Status] register until new data is ready to be read, waiting up to
+    /// `timeout_us` microseconds before returning [`BMA400Error::Timeout`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+    /// # use bma400::BMA400;
+    /// # let ADDR = 0b10100;
+    /// # let expected = vec![
+    /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+    /// #        Transaction::write_read(ADDR, vec![0x03], vec![0x14]),
+    /// #        Transaction::write_read(ADDR, vec![0x03], vec![0x94]),
+    /// #        Transaction::write_read(ADDR, vec![0x04], vec![0x00; 6]),
+    /// #    ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+    /// # let mut delay = NoopDelay::new();
+    /// // Wait up to 10ms for the next sample
+    /// bma400.wait_for_data_ready(&mut delay, 10_000).unwrap();
+    /// let measurement = bma400.get_data().unwrap();
+    /// # i2c.done();
+    /// ```
+    pub async fn wait_for_data_ready<Timer: DelayNs>(
+        &mut self,
+        timer: &mut Timer,
+        timeout_us: u32,
+    ) -> Result<(), BMA400Error<InterfaceError>> {
+        self.poll_status(timer, timeout_us, |status| status.drdy_stat())
+            .await
+    }
+
+    /// Polls the [Status] register until the command decoder is ready to receive a
+    /// command, waiting up to `timeout_us` microseconds before returning
+    /// [`BMA400Error::Timeout`]
+    pub async fn wait_for_cmd_ready<Timer: DelayNs>(
+        &mut self,
+        timer: &mut Timer,
+        timeout_us: u32,
+    ) -> Result<(), BMA400Error<InterfaceError>> {
+        self.poll_status(timer, timeout_us, |status| status.cmd_rdy())
+            .await
+    }
+
+    /// Polls the [Status] register until the device reports `power_mode`, waiting up to
+    /// `timeout_us` microseconds before returning [`BMA400Error::Timeout`]
+    ///
+    /// Power mode changes written using [`config_accel()`](BMA400::config_accel) wait for the
+    /// new mode automatically, see
+    /// [`AccConfigBuilder::write_and_wait()`](crate::config::AccConfigBuilder::write_and_wait)
+    /// to wait for a set time
+    pub async fn wait_for_power_mode<Timer: DelayNs>(
+        &mut self,
+        power_mode: PowerMode,
+        timer: &mut Timer,
+        timeout_us: u32,
+    ) -> Result<(), BMA400Error<InterfaceError>> {
+        self.poll_status(timer, timeout_us, |status| {
+            status.power_mode() == power_mode
+        })
+        .await
+    }
+
+    async fn poll_status<Timer: DelayNs>(
+        &mut self,
+        timer: &mut Timer,
+        timeout_us: u32,
+        ready: impl Fn(&Status) -> bool,
+    ) -> Result<(), BMA400Error<InterfaceError>> {
+        let mut waited = 0;
+        let mut status = [0u8; 1];
+        loop {
+            // Read directly, reset detection must not interfere with a soft reset
+            self.interface.read_register(StatusReg, &mut status).await?;
+            if ready(&Status::new(status[0])) {
+                return Ok(());
+            }
+            if waited >= timeout_us {
+                return Err(BMA400Error::Timeout);
+            }
+            let step = STATUS_POLL_US.min(timeout_us - waited);
+            timer.delay_us(step);
+            waited += step;
+        }
+    }
+
+    // Reads the status register back to back up to `reads` times, where there is no timer
+    pub(crate) async fn poll_status_reads(
+        &mut self,
+        reads: u32,
+        ready: impl Fn(&Status) -> bool,
+    ) -> Result<(), BMA400Error<InterfaceError>> {
+        let mut status = [0u8; 1];
+        for _ in 0..reads {
+            self.interface.read_register(StatusReg, &mut status).await?;
+            if ready(&Status::new(status[0])) {
+                return Ok(());
+            }
+        }
+        Err(BMA400Error::Timeout)
+    }
+
     /// Returns a single 3-axis reading as a [Measurement], with no adjustment for the selected [Scale]
     ///
     /// To get scaled data use [`get_data`](BMA400::get_data)
@@ -133,7 +248,7 @@ where
     /// ```
     pub async fn get_unscaled_data(&mut self) -> Result<Measurement, BMA400Error<InterfaceError>> {
         let mut bytes = [0u8; 6];
-        self.interface.read_register(AccXLSB, &mut bytes).await?;
+        self.read_register(AccXLSB, &mut bytes).await?;
         Ok(Measurement::from_bytes_unscaled(&bytes))
     }
 
@@ -161,16 +276,169 @@ where
     /// ```
     pub async fn get_data(&mut self) -> Result<Measurement, BMA400Error<InterfaceError>> {
         let mut bytes = [0u8; 6];
-        self.interface.read_register(AccXLSB, &mut bytes).await?;
+        self.read_register(AccXLSB, &mut bytes).await?;
         Ok(Measurement::from_bytes_scaled(self.config.scale(), &bytes))
     }
 
+    /// Returns a single 3-axis reading adjusted for the selected [Scale] and the sensor clock
+    /// reading taken with it, as a [TimedMeasurement]
+    ///
+    /// The data and sensor time registers are read in a single burst, so the time belongs
+    /// to the same sample as the data, unlike calling [`get_data()`](BMA400::get_data) and
+    /// [`get_sensor_clock()`](BMA400::get_sensor_clock) one after the other
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// # use bma400::BMA400;
+    /// # let ADDR = 0b10100;
+    /// # let expected = vec![
+    /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+    /// #        Transaction::write_read(ADDR, vec![0x04], vec![
+    /// #            0x0F, 0x00, 0x08, 0x00, 0xEE, 0x01, 0x00, 0x01, 0x00,
+    /// #        ]),
+    /// #    ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+    /// let data = bma400.get_data_with_time().unwrap();
+    /// assert_eq!(988, data.measurement.z);    // (988 milli-g)
+    /// assert_eq!(10_000, data.time.as_micros());
+    /// # i2c.done();
+    /// ```
+    pub async fn get_data_with_time(
+        &mut self,
+    ) -> Result<TimedMeasurement, BMA400Error<InterfaceError>> {
+        let mut bytes = [0u8; 9];
+        self.read_register(AccXLSB, &mut bytes).await?;
+        Ok(TimedMeasurement::from_bytes(self.config.scale(), &bytes))
+    }
+
+    /// Returns a [TimedMeasurement] along with the reset flag and the three interrupt status
+    /// registers, all read in a single burst
+    ///
+    /// Handles everything an interrupt service routine usually needs in one transaction. As
+    /// with [`get_reset_status()`](BMA400::get_reset_status) and the interrupt status getters,
+    /// reading clears the reset flag and latched interrupt statuses
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// # use bma400::BMA400;
+    /// # let ADDR = 0b10100;
+    /// # let expected = vec![
+    /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+    /// #        Transaction::write_read(ADDR, vec![0x04], vec![
+    /// #            0x0F, 0x00, 0x08, 0x00, 0xEE, 0x01, 0x00, 0x01, 0x00, 0x00, 0x80, 0x04, 0x00,
+    /// #        ]),
+    /// #    ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+    /// let status = bma400.get_data_with_status().unwrap();
+    /// assert_eq!(988, status.data.measurement.z);    // (988 milli-g)
+    /// assert!(!status.reset);
+    /// // Data ready and a single tap
+    /// assert!(status.int_status0.drdy_stat());
+    /// assert!(status.int_status1.s_tap_stat());
+    /// # i2c.done();
+    /// ```
+    pub async fn get_data_with_status(
+        &mut self,
+    ) -> Result<DataWithStatus, BMA400Error<InterfaceError>> {
+        let mut bytes = [0u8; 13];
+        self.interface.read_register(AccXLSB, &mut bytes).await?;
+        let data = DataWithStatus::from_bytes(self.config.scale(), &bytes);
+        // The burst includes the reset detection flag
+        if data.reset && self.reset_detection != ResetDetection::Disabled {
+            self.recover_from_reset().await?;
+            return Err(BMA400Error::DeviceWasReset);
+        }
+        Ok(data)
+    }
+
+    /// Returns a single 3-axis reading as a [Measurement] in milli-g, converted using the
+    /// selected [Scale]
+    ///
+    /// Unlike [`get_data()`](BMA400::get_data), which multiplies by a power of two,
+    /// this accounts for the exact resolution of each range (e.g. 0.9765625mg at 2g)
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// # use bma400::BMA400;
+    /// # let ADDR = 0b10100;
+    /// # let expected = vec![
+    /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+    /// #        Transaction::write_read(ADDR, vec![0x04], vec![0x0F, 0x00, 0x08, 0x00, 0xEE, 0x01]),
+    /// #    ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+    /// // Get a single measurement reading in milli-g at the default (4g) scale
+    /// let m = bma400.get_data_mg().unwrap();
+    /// assert_eq!(29, m.x);
+    /// assert_eq!(16, m.y);
+    /// assert_eq!(965, m.z);
+    /// # i2c.done();
+    /// ```
+    pub async fn get_data_mg(&mut self) -> Result<Measurement, BMA400Error<InterfaceError>> {
+        Ok(self.get_unscaled_data().await?.to_mg(self.config.scale()))
+    }
+
+    /// Returns a single 3-axis reading as an [Acceleration] in g, converted using the
+    /// selected [Scale]
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// # use bma400::BMA400;
+    /// # let ADDR = 0b10100;
+    /// # let expected = vec![
+    /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+    /// #        Transaction::write_read(ADDR, vec![0x04], vec![0x00, 0x04, 0x00, 0x08, 0x00, 0x00]),
+    /// #    ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+    /// // Get a single measurement reading in g at the default (4g) scale
+    /// let a = bma400.get_data_g().unwrap();
+    /// assert_eq!(2.0, a.x);
+    /// assert_eq!(-4.0, a.y);
+    /// assert_eq!(0.0, a.z);
+    /// # i2c.done();
+    /// ```
+    #[cfg(feature = "float")]
+    pub async fn get_data_g(&mut self) -> Result<Acceleration, BMA400Error<InterfaceError>> {
+        Ok(self.get_unscaled_data().await?.to_g(self.config.scale()))
+    }
+
+    /// Returns a single 3-axis reading as an [Acceleration] in m/s², converted using the
+    /// selected [Scale]
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// # use bma400::BMA400;
+    /// # let ADDR = 0b10100;
+    /// # let expected = vec![
+    /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+    /// #        Transaction::write_read(ADDR, vec![0x04], vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x02]),
+    /// #    ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+    /// // Get a single measurement reading in m/s² at the default (4g) scale
+    /// let a = bma400.get_data_mps2().unwrap();
+    /// assert_eq!(bma400::STANDARD_GRAVITY, a.z);
+    /// # i2c.done();
+    /// ```
+    #[cfg(feature = "float")]
+    pub async fn get_data_mps2(&mut self) -> Result<Acceleration, BMA400Error<InterfaceError>> {
+        Ok(self.get_unscaled_data().await?.to_mps2(self.config.scale()))
+    }
+
     /// Timer reading from the integrated sensor clock.
     ///
     /// The timer has a resolution of 21 bits stored across 3 bytes.
     /// The lowest 3 bits are always zero (the value is left-justified for compatibility with
     /// 25.6kHz clocks). This timer is inactive in sleep mode. The clock rolls over to zero
-    /// after `0xFFFFF8`
+    /// after `0xFFFFF8`, see [SensorTime]
     ///
     /// # Examples
     /// ```
@@ -185,16 +453,14 @@ where
     /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
     /// // Get a timer reading
     /// let time = bma400.get_sensor_clock().unwrap();
-    /// assert_eq!(524303, time);    // (524303*312.5µs)
+    /// assert_eq!(524303, time.ticks());
+    /// assert_eq!(20_480_585, time.as_micros());
     /// # i2c.done();
     /// ```
-    pub async fn get_sensor_clock(&mut self) -> Result<u32, BMA400Error<InterfaceError>> {
+    pub async fn get_sensor_clock(&mut self) -> Result<SensorTime, BMA400Error<InterfaceError>> {
         let mut buffer = [0u8; 3];
-        self.interface
-            .read_register(SensorTime0, &mut buffer)
-            .await?;
-        let bytes = [buffer[0], buffer[1], buffer[2], 0];
-        Ok(u32::from_le_bytes(bytes))
+        self.read_register(SensorTime0, &mut buffer).await?;
+        Ok(SensorTime::from_le_bytes(buffer))
     }
 
     /// Returns `true` if a power reset has been detected
@@ -263,8 +529,7 @@ where
     /// ```
     pub async fn get_int_status0(&mut self) -> Result<IntStatus0, BMA400Error<InterfaceError>> {
         let mut status_byte = [0u8; 1];
-        self.interface
-            .read_register(InterruptStatus0, &mut status_byte)
+        self.read_register(InterruptStatus0, &mut status_byte)
             .await?;
         Ok(IntStatus0::new(status_byte[0]))
     }
@@ -301,8 +566,7 @@ where
     /// ```
     pub async fn get_int_status1(&mut self) -> Result<IntStatus1, BMA400Error<InterfaceError>> {
         let mut status_byte = [0u8; 1];
-        self.interface
-            .read_register(InterruptStatus1, &mut status_byte)
+        self.read_register(InterruptStatus1, &mut status_byte)
             .await?;
         Ok(IntStatus1::new(status_byte[0]))
     }
@@ -339,12 +603,45 @@ where
     /// ```
     pub async fn get_int_status2(&mut self) -> Result<IntStatus2, BMA400Error<InterfaceError>> {
         let mut status_byte = [0u8; 1];
-        self.interface
-            .read_register(InterruptStatus2, &mut status_byte)
+        self.read_register(InterruptStatus2, &mut status_byte)
             .await?;
         Ok(IntStatus2::new(status_byte[0]))
     }
 
+    /// Reads all three interrupt status registers in a single burst and returns the triggered
+    /// [Interrupts]
+    ///
+    /// Reading clears latched interrupt statuses, as with the individual getters
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// # use bma400::{BMA400, InterruptEvent};
+    /// # let ADDR = 0b10100;
+    /// # let expected = vec![
+    /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+    /// #        Transaction::write_read(ADDR, vec![0x0E], vec![0x80, 0x06, 0x01]),
+    /// #    ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+    /// let interrupts = bma400.get_interrupts().unwrap();
+    /// for event in interrupts.events() {
+    ///     match event {
+    ///         InterruptEvent::DataReady => { /* Read the new data */ }
+    ///         InterruptEvent::SingleTap => { /* Handle the tap */ }
+    ///         InterruptEvent::Step(steps) => assert_eq!(2, steps),
+    ///         InterruptEvent::ActivityChange { x, y, z } => assert!(x && !y && !z),
+    ///         _ => unreachable!(),
+    ///     }
+    /// }
+    /// # i2c.done();
+    /// ```
+    pub async fn get_interrupts(&mut self) -> Result<Interrupts, BMA400Error<InterfaceError>> {
+        let mut bytes = [0u8; 3];
+        self.read_register(InterruptStatus0, &mut bytes).await?;
+        Ok(Interrupts::from_status_bytes(bytes))
+    }
+
     /// Returns the number of unread bytes currently in the FIFO
     ///
     /// # Examples
@@ -365,13 +662,19 @@ where
     /// ```
     pub async fn get_fifo_len(&mut self) -> Result<u16, BMA400Error<InterfaceError>> {
         let mut buffer = [0u8; 2];
-        self.interface
-            .read_register(FifoLength0, &mut buffer)
-            .await?;
+        self.read_register(FifoLength0, &mut buffer).await?;
         let bytes = [buffer[0], buffer[1] & 0b0000_0111];
         Ok(u16::from_le_bytes(bytes))
     }
+}
 
+// The FIFO can't be read in sleep mode
+impl<T, S, InterfaceError> BMA400<AsyncInterface<T>, S>
+where
+    AsyncInterface<T>: ReadFromRegister<Error = BMA400Error<InterfaceError>>
+        + WriteToRegister<Error = BMA400Error<InterfaceError>>,
+    S: ReadsFifo,
+{
     /// Reads enough bytes from the FIFO to fill `buffer` and returns a [FifoFrames] iterator
     /// over the [Frame]s in `buffer`
     ///
@@ -417,7 +720,7 @@ where
     /// // A Time Frame
     /// if let Some(frame) = frames.next() {
     ///     assert!(matches!(frame.frame_type(), FrameType::Time));
-    ///     assert_eq!(Some(0xFFFFF8), frame.time()); // about to roll over!
+    ///     assert_eq!(Some(0xFFFFF8), frame.time().map(|t| t.ticks())); // about to roll over!
     /// }
     ///
     /// // No more Frames
@@ -431,10 +734,158 @@ where
         if self.config.is_fifo_read_disabled() {
             return Err(ConfigError::FifoReadWhilePwrDisable.into());
         }
-        self.interface.read_register(FifoData, buffer).await?;
+        self.read_register(FifoData, buffer).await?;
         Ok(FifoFrames::new(buffer))
     }
 
+    /// Reads enough bytes from the FIFO to fill `buffer`, returning an iterator over the data
+    /// frames and the sensor time each was measured at, using the configured [OutputDataRate]
+    ///
+    /// Returns None if the FIFO is not configured with send_time_on_empty or `buffer` does not
+    /// reach the end of the FIFO, so no time frame was read. See
+    /// [`FifoFrames::with_timestamps()`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// # use bma400::BMA400;
+    /// # let ADDR = 0b10100;
+    /// # let expected = vec![
+    /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+    /// #        Transaction::write(ADDR, vec![0x26, 0x24]),
+    /// #        Transaction::write_read(ADDR, vec![0x14], vec![
+    /// #            0x92, 0x00, 0x20, 0x92, 0x00, 0x20, 0xA0, 0x00, 0x01, 0x00, 0x80, 0x00,
+    /// #        ]),
+    /// #    ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+    /// bma400
+    ///     .config_fifo()
+    ///     .with_axes(true, false, false)
+    ///     .with_send_time_on_empty(true)
+    ///     .write()
+    ///     .unwrap();
+    /// let mut buffer = [0u8; 12];
+    /// let mut frames = bma400.read_timestamped_fifo_frames(&mut buffer).unwrap().unwrap();
+    /// // 200Hz, the last frame was measured at the time in the time frame
+    /// let first = frames.next().unwrap();
+    /// assert_eq!(5_000, first.time.as_micros());
+    /// let last = frames.next().unwrap();
+    /// assert_eq!(10_000, last.time.as_micros());
+    /// # i2c.done();
+    /// ```
+    pub async fn read_timestamped_fifo_frames<'a>(
+        &mut self,
+        buffer: &'a mut [u8],
+    ) -> Result<Option<TimestampedFrames<'a>>, BMA400Error<InterfaceError>> {
+        let odr = self.config.acc_config().odr();
+        Ok(self.read_fifo_frames(buffer).await?.with_timestamps(odr))
+    }
+
+    /// Reads enough bytes from the FIFO to fill `buffer` and decodes them with `decoder`,
+    /// returning an iterator over the complete [Frame]s
+    ///
+    /// A frame cut off at the end of `buffer` is kept by the decoder and returned by the
+    /// next read, so the FIFO can be read in chunks smaller than its contents
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// # use bma400::{BMA400, FifoDecoder};
+    /// # let ADDR = 0b10100;
+    /// # let expected = vec![
+    /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+    /// #        Transaction::write_read(ADDR, vec![0x14], vec![0x9E, 0x01, 0x80, 0x0F]),
+    /// #        Transaction::write_read(ADDR, vec![0x14], vec![0xFF, 0x0F, 0x7F, 0x80]),
+    /// #        Transaction::write_read(ADDR, vec![0x14], vec![0x00, 0x80, 0x00, 0x80]),
+    /// #    ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+    /// let mut decoder = FifoDecoder::new();
+    /// let mut buffer = [0u8; 4];
+    /// // The first chunk only holds part of a frame
+    /// let mut frames = bma400.read_fifo_chunk(&mut decoder, &mut buffer).unwrap();
+    /// assert_eq!(None, frames.next());
+    /// // It is completed by the next
+    /// let mut frames = bma400.read_fifo_chunk(&mut decoder, &mut buffer).unwrap();
+    /// assert_eq!(Some(-2047), frames.next().unwrap().x());
+    /// assert_eq!(None, frames.next());
+    /// // Read until the FIFO is empty
+    /// bma400.read_fifo_chunk(&mut decoder, &mut buffer).unwrap();
+    /// assert!(decoder.empty_seen());
+    /// # i2c.done();
+    /// ```
+    pub async fn read_fifo_chunk<'a>(
+        &mut self,
+        decoder: &'a mut FifoDecoder,
+        buffer: &'a mut [u8],
+    ) -> Result<DecodedFrames<'a>, BMA400Error<InterfaceError>> {
+        if self.config.is_fifo_read_disabled() {
+            return Err(ConfigError::FifoReadWhilePwrDisable.into());
+        }
+        self.read_register(FifoData, buffer).await?;
+        Ok(decoder.decode(buffer))
+    }
+
+    /// Reads enough bytes from the FIFO to fill `buffer` and decodes them with `decoder`,
+    /// returning an iterator over the [FifoSample]s in milli-g
+    ///
+    /// Configuration changes made through the driver since the last read are passed on to
+    /// `decoder`, so samples written before and after a change to e.g. the [Scale] are each
+    /// converted with the settings they were measured with. Create the decoder using
+    /// [`fifo_sample_decoder()`](BMA400::fifo_sample_decoder)
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// # use bma400::{BMA400, Scale};
+    /// # let ADDR = 0b10100;
+    /// # let expected = vec![
+    /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+    /// #        Transaction::write(ADDR, vec![0x26, 0xE0]),
+    /// #        Transaction::write(ADDR, vec![0x1A, 0x89]),
+    /// #        Transaction::write_read(ADDR, vec![0x14], vec![
+    /// #            0x9E, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00,
+    /// #            0x48, 0x08,
+    /// #            0x9E, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00,
+    /// #            0x80, 0x00,
+    /// #        ]),
+    /// #    ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+    /// bma400.config_fifo().with_axes(true, true, true).write().unwrap();
+    /// let mut decoder = bma400.fifo_sample_decoder();
+    /// // Change the scale while data is waiting in the FIFO
+    /// bma400.config_accel().with_scale(Scale::Range8G).write().unwrap();
+    /// let mut buffer = [0u8; 18];
+    /// let mut samples = bma400.read_fifo_samples(&mut decoder, &mut buffer).unwrap();
+    /// // The same raw reading, before and after the change
+    /// let sample = samples.next().unwrap();
+    /// assert_eq!((Some(1000), Scale::Range4G), (sample.x, sample.settings.scale));
+    /// let sample = samples.next().unwrap();
+    /// assert_eq!((Some(2000), Scale::Range8G), (sample.x, sample.settings.scale));
+    /// assert_eq!(None, samples.next());
+    /// # i2c.done();
+    /// ```
+    pub async fn read_fifo_samples<'a>(
+        &mut self,
+        decoder: &'a mut FifoSampleDecoder,
+        buffer: &'a mut [u8],
+    ) -> Result<FifoSamples<'a>, BMA400Error<InterfaceError>> {
+        if self.config.is_fifo_read_disabled() {
+            return Err(ConfigError::FifoReadWhilePwrDisable.into());
+        }
+        self.read_register(FifoData, buffer).await?;
+        self.take_fifo_changes(decoder);
+        Ok(decoder.decode(buffer))
+    }
+}
+
+impl<T, S, InterfaceError> BMA400<AsyncInterface<T>, S>
+where
+    AsyncInterface<T>: ReadFromRegister<Error = BMA400Error<InterfaceError>>
+        + WriteToRegister<Error = BMA400Error<InterfaceError>>,
+{
     /// Flush all data from the FIFO
     ///
     /// # Examples
@@ -461,6 +912,7 @@ where
     /// ```
     pub async fn flush_fifo(&mut self) -> Result<(), BMA400Error<InterfaceError>> {
         self.interface.write_register(Command::FlushFifo).await?;
+        self.fifo_flushed();
         Ok(())
     }
 
@@ -486,9 +938,7 @@ where
     /// ```
     pub async fn get_step_count(&mut self) -> Result<u32, BMA400Error<InterfaceError>> {
         let mut buffer = [0u8; 3];
-        self.interface
-            .read_register(StepCount0, &mut buffer)
-            .await?;
+        self.read_register(StepCount0, &mut buffer).await?;
         Ok(u32::from_le_bytes([buffer[0], buffer[1], buffer[2], 0]))
     }
 
@@ -547,9 +997,7 @@ where
     /// ```
     pub async fn get_step_activity(&mut self) -> Result<Activity, BMA400Error<InterfaceError>> {
         let mut buffer = [0u8; 1];
-        self.interface
-            .read_register(StepStatus, &mut buffer)
-            .await?;
+        self.read_register(StepStatus, &mut buffer).await?;
         let activity = match buffer[0] & 0b11 {
             0x00 => Activity::Still,
             0x01 => Activity::Walk,
@@ -581,7 +1029,7 @@ where
     /// ```
     pub async fn get_raw_temp(&mut self) -> Result<i8, BMA400Error<InterfaceError>> {
         let mut temp = [0u8; 1];
-        self.interface.read_register(TempData, &mut temp).await?;
+        self.read_register(TempData, &mut temp).await?;
         let t = i8::from_le_bytes(temp);
         Ok(t)
     }
@@ -608,7 +1056,14 @@ where
     pub async fn get_temp_celsius(&mut self) -> Result<f32, BMA400Error<InterfaceError>> {
         Ok(f32::from(self.get_raw_temp().await?) * 0.5 + 23.0)
     }
+}
 
+// Changes the power mode and ODR
+impl<T, InterfaceError> BMA400<AsyncInterface<T>>
+where
+    AsyncInterface<T>: ReadFromRegister<Error = BMA400Error<InterfaceError>>
+        + WriteToRegister<Error = BMA400Error<InterfaceError>>,
+{
     /// Configure how the accelerometer samples, filters and ouputs data
     ///
     /// - [PowerMode] using [`with_power_mode()`](AccConfigBuilder::with_power_mode)
@@ -625,8 +1080,8 @@ where
     /// # let ADDR = 0b10100;
     /// # let expected = vec![
     /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
-    /// #        Transaction::write(ADDR, vec![0x19, 0x62]),
-    /// #        Transaction::write(ADDR, vec![0x1A, 0xC9]),
+    /// #        Transaction::write(ADDR, vec![0x19, 0x62, 0xC9]),
+    /// #        Transaction::write_read(ADDR, vec![0x03], vec![0x14]),
     /// #    ];
     /// # let mut i2c = Mock::new(&expected);
     /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
@@ -639,10 +1094,16 @@ where
     ///     .write().unwrap();
     /// # i2c.done();
     /// ```
-    pub fn config_accel(&'_ mut self) -> AccConfigBuilder<'_, T> {
+    pub fn config_accel(&'_ mut self) -> AccConfigBuilder<'_, AsyncInterface<T>> {
         AccConfigBuilder::new(self)
     }
+}
 
+impl<T, S, InterfaceError> BMA400<AsyncInterface<T>, S>
+where
+    AsyncInterface<T>: ReadFromRegister<Error = BMA400Error<InterfaceError>>
+        + WriteToRegister<Error = BMA400Error<InterfaceError>>,
+{
     /// Enable or disable interrupts[^except] and set interrupt latch mode
     ///
     /// [^except]: To enable the Auto-Wakeup Interrupt see [`config_autowkup()`](BMA400::config_autowkup)
@@ -654,8 +1115,7 @@ where
     /// # let ADDR = 0b10100;
     /// # let expected = vec![
     /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
-    /// #        Transaction::write(ADDR, vec![0x1F, 0x40]),
-    /// #        Transaction::write(ADDR, vec![0x20, 0x81]),
+    /// #        Transaction::write(ADDR, vec![0x1F, 0x40, 0x81]),
     /// #    ];
     /// # let mut i2c = Mock::new(&expected);
     /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
@@ -668,7 +1128,7 @@ where
     ///     .write().unwrap();
     /// # i2c.done();
     /// ```
-    pub fn config_interrupts(&'_ mut self) -> IntConfigBuilder<'_, T> {
+    pub fn config_interrupts(&'_ mut self) -> IntConfigBuilder<'_, AsyncInterface<T>, S> {
         IntConfigBuilder::new(self)
     }
 
@@ -699,7 +1159,7 @@ where
     ///     .write().unwrap();
     /// # i2c.done();
     /// ```
-    pub fn config_int_pins(&'_ mut self) -> IntPinConfigBuilder<'_, T> {
+    pub fn config_int_pins(&'_ mut self) -> IntPinConfigBuilder<'_, AsyncInterface<T>, S> {
         IntPinConfigBuilder::new(self)
     }
 
@@ -721,9 +1181,7 @@ where
     /// # let ADDR = 0b10100;
     /// # let expected = vec![
     /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
-    /// #        Transaction::write(ADDR, vec![0x26, 0xE2]),
-    /// #        Transaction::write(ADDR, vec![0x27, 0x20]),
-    /// #        Transaction::write(ADDR, vec![0x28, 0x03]),
+    /// #        Transaction::write(ADDR, vec![0x26, 0xE2, 0x20, 0x03]),
     /// #    ];
     /// # let mut i2c = Mock::new(&expected);
     /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
@@ -736,10 +1194,17 @@ where
     ///     .write().unwrap();
     /// # i2c.done();
     /// ```
-    pub fn config_fifo(&'_ mut self) -> FifoConfigBuilder<'_, T> {
+    pub fn config_fifo(&'_ mut self) -> FifoConfigBuilder<'_, AsyncInterface<T>, S> {
         FifoConfigBuilder::new(self)
     }
+}
 
+// Changes the power mode without the driver
+impl<T, InterfaceError> BMA400<AsyncInterface<T>>
+where
+    AsyncInterface<T>: ReadFromRegister<Error = BMA400Error<InterfaceError>>
+        + WriteToRegister<Error = BMA400Error<InterfaceError>>,
+{
     /// Configure Auto Low Power settings
     ///
     /// - Set the timeout counter for low power mode using [`with_timeout()`](AutoLpConfigBuilder::with_timeout)
@@ -754,8 +1219,7 @@ where
     /// # let ADDR = 0b10100;
     /// # let expected = vec![
     /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
-    /// #        Transaction::write(ADDR, vec![0x2A, 0x4E]),
-    /// #        Transaction::write(ADDR, vec![0x2B, 0x28]),
+    /// #        Transaction::write(ADDR, vec![0x2A, 0x4E, 0x28]),
     /// #    ];
     /// # let mut i2c = Mock::new(&expected);
     /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
@@ -767,7 +1231,7 @@ where
     ///     .write().unwrap();
     /// # i2c.done();
     /// ```
-    pub fn config_auto_lp(&'_ mut self) -> AutoLpConfigBuilder<'_, T> {
+    pub fn config_auto_lp(&'_ mut self) -> AutoLpConfigBuilder<'_, AsyncInterface<T>> {
         AutoLpConfigBuilder::new(self)
     }
 
@@ -784,8 +1248,7 @@ where
     /// # let ADDR = 0b10100;
     /// # let expected = vec![
     /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
-    /// #        Transaction::write(ADDR, vec![0x2C, 0x4E]),
-    /// #        Transaction::write(ADDR, vec![0x2D, 0x26]),
+    /// #        Transaction::write(ADDR, vec![0x2C, 0x4E, 0x26]),
     /// #    ];
     /// # let mut i2c = Mock::new(&expected);
     /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
@@ -799,10 +1262,16 @@ where
     ///     .write().unwrap();
     /// # i2c.done();
     /// ```
-    pub fn config_autowkup(&'_ mut self) -> AutoWakeupConfigBuilder<'_, T> {
+    pub fn config_autowkup(&'_ mut self) -> AutoWakeupConfigBuilder<'_, AsyncInterface<T>> {
         AutoWakeupConfigBuilder::new(self)
     }
+}
 
+impl<T, S, InterfaceError> BMA400<AsyncInterface<T>, S>
+where
+    AsyncInterface<T>: ReadFromRegister<Error = BMA400Error<InterfaceError>>
+        + WriteToRegister<Error = BMA400Error<InterfaceError>>,
+{
     /// Configure Wake-up Interrupt settings
     ///
     /// - [WakeupIntRefMode] using [`with_ref_mode()`](WakeupIntConfigBuilder::with_ref_mode)
@@ -834,7 +1303,7 @@ where
     ///     .write().unwrap();
     /// # i2c.done();
     /// ```
-    pub fn config_wkup_int(&'_ mut self) -> WakeupIntConfigBuilder<'_, T> {
+    pub fn config_wkup_int(&'_ mut self) -> WakeupIntConfigBuilder<'_, AsyncInterface<T>, S> {
         WakeupIntConfigBuilder::new(self)
     }
 
@@ -853,8 +1322,7 @@ where
     /// # let ADDR = 0b10100;
     /// # let expected = vec![
     /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
-    /// #        Transaction::write(ADDR, vec![0x35, 0xE4]),
-    /// #        Transaction::write(ADDR, vec![0x36, 0x20]),
+    /// #        Transaction::write(ADDR, vec![0x35, 0xE4, 0x20]),
     /// #    ];
     /// # let mut i2c = Mock::new(&expected);
     /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
@@ -869,7 +1337,7 @@ where
     ///     .write().unwrap();
     /// # i2c.done();
     /// ```
-    pub fn config_orientchg_int(&'_ mut self) -> OrientChgConfigBuilder<'_, T> {
+    pub fn config_orientchg_int(&'_ mut self) -> OrientChgConfigBuilder<'_, AsyncInterface<T>, S> {
         OrientChgConfigBuilder::new(self)
     }
 
@@ -892,11 +1360,8 @@ where
     /// # let ADDR = 0b10100;
     /// # let expected = vec![
     /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
-    /// #        Transaction::write(ADDR, vec![0x3F, 0xE0]),
-    /// #        Transaction::write(ADDR, vec![0x40, 0x01]),
-    /// #        Transaction::write(ADDR, vec![0x41, 0x20]),
-    /// #        Transaction::write(ADDR, vec![0x48, 0xD4]),
-    /// #        Transaction::write(ADDR, vec![0x49, 0x03]),
+    /// #        Transaction::write(ADDR, vec![0x3F, 0xE0, 0x01, 0x20]),
+    /// #        Transaction::write(ADDR, vec![0x48, 0xD4, 0x03]),
     /// #    ];
     /// # let mut i2c = Mock::new(&expected);
     /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
@@ -912,7 +1377,7 @@ where
     ///     .write().unwrap();
     /// # i2c.done();
     /// ```
-    pub fn config_gen1_int(&'_ mut self) -> GenIntConfigBuilder<'_, T> {
+    pub fn config_gen1_int(&'_ mut self) -> GenIntConfigBuilder<'_, AsyncInterface<T>, S> {
         GenIntConfigBuilder::new_gen1(self)
     }
 
@@ -935,11 +1400,8 @@ where
     /// # let ADDR = 0b10100;
     /// # let expected = vec![
     /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
-    /// #        Transaction::write(ADDR, vec![0x4A, 0xE0]),
-    /// #        Transaction::write(ADDR, vec![0x4B, 0x02]),
-    /// #        Transaction::write(ADDR, vec![0x4C, 0x20]),
-    /// #        Transaction::write(ADDR, vec![0x53, 0xD4]),
-    /// #        Transaction::write(ADDR, vec![0x54, 0x03]),
+    /// #        Transaction::write(ADDR, vec![0x4A, 0xE0, 0x02, 0x20]),
+    /// #        Transaction::write(ADDR, vec![0x53, 0xD4, 0x03]),
     /// #    ];
     /// # let mut i2c = Mock::new(&expected);
     /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
@@ -955,7 +1417,7 @@ where
     ///     .write().unwrap();
     /// # i2c.done();
     /// ```
-    pub fn config_gen2_int(&'_ mut self) -> GenIntConfigBuilder<'_, T> {
+    pub fn config_gen2_int(&'_ mut self) -> GenIntConfigBuilder<'_, AsyncInterface<T>, S> {
         GenIntConfigBuilder::new_gen2(self)
     }
 
@@ -973,8 +1435,7 @@ where
     /// # let ADDR = 0b10100;
     /// # let expected = vec![
     /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
-    /// #        Transaction::write(ADDR, vec![0x55, 0x20]),
-    /// #        Transaction::write(ADDR, vec![0x56, 0xF1]),
+    /// #        Transaction::write(ADDR, vec![0x55, 0x20, 0xF1]),
     /// #    ];
     /// # let mut i2c = Mock::new(&expected);
     /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
@@ -990,10 +1451,18 @@ where
     ///     .write().unwrap();
     /// # i2c.done()
     /// ```
-    pub fn config_actchg_int(&'_ mut self) -> ActChgConfigBuilder<'_, T> {
+    pub fn config_actchg_int(&'_ mut self) -> ActChgConfigBuilder<'_, AsyncInterface<T>, S> {
         ActChgConfigBuilder::new(self)
     }
+}
 
+// Tap detection requires normal mode at 200Hz
+impl<T, S, InterfaceError> BMA400<AsyncInterface<T>, S>
+where
+    AsyncInterface<T>: ReadFromRegister<Error = BMA400Error<InterfaceError>>
+        + WriteToRegister<Error = BMA400Error<InterfaceError>>,
+    S: DetectsTaps,
+{
     /// Configure Advanced Tap Interrupt Settings
     ///
     /// - Set the axis evaluated for the interrupt trigger condition using [`with_axis()`](TapConfigBuilder::with_axis)
@@ -1023,9 +1492,133 @@ where
     ///     .write().unwrap();
     /// # i2c.done();
     /// ```
-    pub fn config_tap(&'_ mut self) -> TapConfigBuilder<'_, T> {
+    pub fn config_tap(&'_ mut self) -> TapConfigBuilder<'_, AsyncInterface<T>, S> {
         TapConfigBuilder::new(self)
     }
+}
+
+// Can change the power mode and ODR
+impl<T, InterfaceError> BMA400<AsyncInterface<T>>
+where
+    AsyncInterface<T>: ReadFromRegister<Error = BMA400Error<InterfaceError>>
+        + WriteToRegister<Error = BMA400Error<InterfaceError>>,
+{
+    /// Write a complete [DeviceConfig] to the device
+    ///
+    /// The whole configuration is checked first, returning [`BMA400Error::InvalidConfig`]
+    /// with every broken rule if it is not valid (in strict mode, see
+    /// [`DeviceConfig::validate_strict()`]). Only the registers which changed are then
+    /// written, in an order that is safe whatever the current configuration: the interrupts
+    /// are disabled, the ODR is changed, the interrupt engines and pins are configured and
+    /// the interrupts are enabled again. If a write fails, the registers already written are
+    /// restored, see [`BMA400Error::RollbackFailed`]
+    pub async fn apply_config(
+        &mut self,
+        config: &DeviceConfig,
+    ) -> Result<(), BMA400Error<InterfaceError>> {
+        if self.strict_config {
+            config.validate_strict()
+        } else {
+            config.validate()
+        }
+        .map_err(BMA400Error::InvalidConfig)?;
+        let writes = StagedWrites::new(&self.config, config.config());
+        let verify = self.verify_writes;
+        let previous = self.begin_config_write();
+        let mut result = Ok(());
+        for (addr, byte) in writes.iter() {
+            result = self.write_raw_register(addr, byte, verify).await;
+            if result.is_err() {
+                break;
+            }
+        }
+        if result.is_ok() {
+            // Only keep the register values, not the record of adjusted settings
+            self.config = Config::from_registers(&config.config().to_registers());
+        }
+        self.end_config_write(previous, result).await
+    }
+
+    /// Write the sections set in a [Profile] to the device
+    ///
+    /// Each section runs the same checks and only writes the registers which changed, as if it
+    /// was written with its builder. Sections are written in the order: accelerometer, FIFO,
+    /// interrupt engines, pin mapping, then the interrupt enables, and each is checked against
+    /// the sections written before it. If any section is rejected or fails to write, the
+    /// registers already written are restored, see [`BMA400Error::RollbackFailed`]
+    ///
+    pub async fn apply(&mut self, profile: &Profile) -> Result<(), BMA400Error<InterfaceError>> {
+        let previous = self.begin_config_write();
+        let result = self.apply_changes(profile).await;
+        self.end_config_write(previous, result).await
+    }
+
+    async fn apply_changes(
+        &mut self,
+        profile: &Profile,
+    ) -> Result<(), BMA400Error<InterfaceError>> {
+        if let Some(config) = &profile.accel {
+            AccConfigBuilder::from_config(self, config.clone())
+                .write_changes()
+                .await?;
+        }
+        if let Some(config) = &profile.fifo {
+            FifoConfigBuilder::from_config(self, config.clone())
+                .write_changes()
+                .await?;
+        }
+        if let Some(config) = &profile.auto_lp {
+            AutoLpConfigBuilder::from_config(self, config.clone())
+                .write_changes()
+                .await?;
+        }
+        if let Some(config) = &profile.autowkup {
+            AutoWakeupConfigBuilder::from_config(self, config.clone())
+                .write_changes()
+                .await?;
+        }
+        if let Some(config) = &profile.wkup_int {
+            WakeupIntConfigBuilder::from_config(self, config.clone())
+                .write_changes()
+                .await?;
+        }
+        if let Some(config) = &profile.orientchg_int {
+            OrientChgConfigBuilder::from_config(self, config.clone())
+                .write_changes()
+                .await?;
+        }
+        if let Some(config) = &profile.gen1_int {
+            GenIntConfigBuilder::from_gen1_config(self, config.clone())
+                .write_changes()
+                .await?;
+        }
+        if let Some(config) = &profile.gen2_int {
+            GenIntConfigBuilder::from_gen2_config(self, config.clone())
+                .write_changes()
+                .await?;
+        }
+        if let Some(config) = &profile.actchg_int {
+            ActChgConfigBuilder::from_config(self, config.clone())
+                .write_changes()
+                .await?;
+        }
+        if let Some(config) = &profile.tap {
+            TapConfigBuilder::from_config(self, config.clone())
+                .write_changes()
+                .await?;
+        }
+        if let Some(config) = &profile.int_pins {
+            IntPinConfigBuilder::from_config(self, config.clone())
+                .write_changes()
+                .await?;
+        }
+        if let Some(config) = &profile.interrupts {
+            IntConfigBuilder::from_config(self, config.clone())
+                .write_changes()
+                .await?;
+        }
+        Ok(())
+    }
 
     async fn setup_self_test(&mut self) -> Result<(), BMA400Error<InterfaceError>> {
         let interface = &mut self.interface;
@@ -1158,19 +1751,364 @@ where
             Err(BMA400Error::SelfTestFailedError)
         }
     }
+}
+
+impl<T, S, InterfaceError> BMA400<AsyncInterface<T>, S>
+where
+    AsyncInterface<T>: ReadFromRegister<Error = BMA400Error<InterfaceError>>
+        + WriteToRegister<Error = BMA400Error<InterfaceError>>,
+{
+    /// Reads back the device configuration registers and replaces the driver's
+    /// copy of the configuration with their current values
+    ///
+    /// Use this when the device may have been configured outside of this driver instance,
+    /// e.g. after a reset of the host MCU that left the BMA400 powered
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// # use bma400::{BMA400, PowerMode};
+    /// # let ADDR = 0b10100;
+    /// # let mut registers = vec![0u8; 64];
+    /// # registers[0x00] = 0x02;
+    /// # registers[0x01] = 0x49;
+    /// # registers[0x0B] = 0x22;
+    /// # registers[0x3F] = 0x06;
+    /// # let expected = vec![
+    /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+    /// #        Transaction::write_read(ADDR, vec![0x19], registers),
+    /// #    ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+    /// // The device was left in normal mode by a previous session
+    /// bma400.resync_config().unwrap();
+    /// // Already in normal mode, nothing is written
+    /// bma400.config_accel().with_power_mode(PowerMode::Normal).write().unwrap();
+    /// # i2c.done();
+    /// ```
+    pub async fn resync_config(&mut self) -> Result<(), BMA400Error<InterfaceError>> {
+        let mut buffer = [0u8; CONFIG_BLOCK_LEN];
+        self.interface
+            .read_register(AccConfig0::default(), &mut buffer)
+            .await?;
+        self.config = Config::from_registers(&ConfigRegisters::new(buffer));
+        self.fifo_flushed();
+        Ok(())
+    }
+
+    /// Reads every register into a [RegisterSnapshot] for diagnostics
+    ///
+    /// Reading clears the error, reset detection and interrupt status registers, as reading
+    /// them individually does. The FIFO data register is not read
+    ///
+    /// With [ResetDetection] enabled, a reset reported in the snapshot restores the
+    /// configuration and returns [`BMA400Error::DeviceWasReset`], as other reads do
+    ///
+    /// # Examples
+    /// ```
+    /// # use core::cell::RefCell;
+    /// # use bma400::{BMA400, PowerMode, sim::{EmulatedDevice, SharedDevice}};
+    /// # let sim = RefCell::new(EmulatedDevice::new());
+    /// # let mut bma400 = BMA400::new_i2c(SharedDevice::new(&sim)).unwrap();
+    /// bma400.config_accel().with_power_mode(PowerMode::Normal).write().unwrap();
+    /// let snapshot = bma400.dump_registers().unwrap();
+    /// assert_eq!(Some(0x90), snapshot.get(0x00));
+    /// // The device holds the configuration written by the driver
+    /// assert_eq!(0, bma400.diff_config(&snapshot).count());
+    /// ```
+    pub async fn dump_registers(
+        &mut self,
+    ) -> Result<RegisterSnapshot, BMA400Error<InterfaceError>> {
+        let mut bytes = [0u8; REGISTER_SPACE_LEN];
+        // The address does not advance past the FIFO data register, read either side of it
+        self.interface
+            .read_register(ChipId, &mut bytes[..FifoData::ADDR as usize])
+            .await?;
+        self.interface
+            .read_register(StepCount0, &mut bytes[StepCount0::ADDR as usize..])
+            .await?;
+        // The burst cleared the reset detection flag, so act on it here
+        if bytes[Event::ADDR as usize] & 0x01 != 0
+            && self.reset_detection != ResetDetection::Disabled
+        {
+            self.recover_from_reset().await?;
+            return Err(BMA400Error::DeviceWasReset);
+        }
+        Ok(RegisterSnapshot::new(bytes))
+    }
+}
 
+// Puts the device back in sleep mode
+impl<T, InterfaceError> BMA400<AsyncInterface<T>>
+where
+    AsyncInterface<T>: ReadFromRegister<Error = BMA400Error<InterfaceError>>
+        + WriteToRegister<Error = BMA400Error<InterfaceError>>,
+{
     /// Returns all settings to default values
+    ///
+    /// Checks that the command decoder is ready first, reading the status up to 10 times and
+    /// returning [`BMA400Error::Timeout`] if it stays busy. Does not wait for the device to
+    /// start up again, see [`soft_reset_with_delay()`](BMA400::soft_reset_with_delay)
     pub async fn soft_reset(&mut self) -> Result<(), BMA400Error<InterfaceError>> {
+        self.poll_status_reads(CMD_READY_POLLS, |status| status.cmd_rdy())
+            .await?;
         self.interface.write_register(Command::SoftReset).await?;
         self.config = Config::default();
+        self.fifo_flushed();
         let mut buffer = [0u8; 1];
         // Clear reset detection bit
         self.interface.read_register(Event, &mut buffer).await?;
         Ok(())
     }
 
+    /// Soft resets the device, waits for it to start up and checks that it came back: the
+    /// chip ID must read correctly and the device must report the reset
+    ///
+    /// With `restore_config` the previous configuration is written back to the device in
+    /// dependency order: accelerometer settings first, then the FIFO and interrupt engines,
+    /// the interrupt enables and finally the pin mapping. Otherwise all settings return to
+    /// their default values
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+    /// # use bma400::{BMA400, PowerMode};
+    /// # let ADDR = 0b10100;
+    /// # let expected = vec![
+    /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+    /// #        Transaction::write(ADDR, vec![0x19, 0x02]),
+    /// #        Transaction::write_read(ADDR, vec![0x03], vec![0x14]),
+    /// #        Transaction::write_read(ADDR, vec![0x03], vec![0x10]),
+    /// #        Transaction::write(ADDR, vec![0x7E, 0xB6]),
+    /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+    /// #        Transaction::write_read(ADDR, vec![0x0D], vec![0x01]),
+    /// #        Transaction::write(ADDR, vec![0x19, 0x02]),
+    /// #    ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+    /// # let mut delay = NoopDelay::new();
+    /// bma400.config_accel().with_power_mode(PowerMode::Normal).write().unwrap();
+    /// // Reset the device, then put it back in normal mode
+    /// bma400.soft_reset_with_delay(&mut delay, true).unwrap();
+    /// # i2c.done();
+    /// ```
+    pub async fn soft_reset_with_delay<Timer: DelayNs>(
+        &mut self,
+        timer: &mut Timer,
+        restore_config: bool,
+    ) -> Result<(), BMA400Error<InterfaceError>> {
+        self.poll_status(timer, CMD_READY_TIMEOUT_US, |status| status.cmd_rdy())
+            .await?;
+        self.interface.write_register(Command::SoftReset).await?;
+        let previous = core::mem::take(&mut self.config);
+        self.fifo_flushed();
+        timer.delay_ms(SOFT_RESET_DELAY_MS);
+        self.interface.after_reset().await?;
+        let mut chip_id = [0u8; 1];
+        self.interface.read_register(ChipId, &mut chip_id).await?;
+        if chip_id[0] != 0x90 {
+            return Err(BMA400Error::ChipIdReadFailed);
+        }
+        // Reading also clears the reset detection bit
+        let mut event = [0u8; 1];
+        self.interface.read_register(Event, &mut event).await?;
+        if event[0] & 0x01 == 0 {
+            return Err(BMA400Error::ResetFailed);
+        }
+        if restore_config {
+            self.replay_config(&previous).await?;
+            self.config = previous;
+            self.fifo_flushed();
+        }
+        Ok(())
+    }
+}
+
+impl<T, S, InterfaceError> BMA400<AsyncInterface<T>, S>
+where
+    AsyncInterface<T>: ReadFromRegister<Error = BMA400Error<InterfaceError>>
+        + WriteToRegister<Error = BMA400Error<InterfaceError>>,
+{
+    /// Writes the registers of `config` that differ from their reset values, in
+    /// [REPLAY_ORDER]
+    async fn replay_config(&mut self, config: &Config) -> Result<(), BMA400Error<InterfaceError>> {
+        let registers = config.to_registers();
+        let defaults = Config::default().to_registers();
+        for (first, last) in REPLAY_ORDER {
+            let mut from = first;
+            while let Some((start, end)) = registers.changed_run(&defaults, from, last) {
+                self.interface
+                    .write_registers(start, registers.slice(start, end))
+                    .await?;
+                if end == last {
+                    break;
+                }
+                from = end + 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads `register`, first checking whether the device was reset if a check is due, see
+    /// [ResetDetection]
+    async fn read_register<R: ReadReg>(
+        &mut self,
+        register: R,
+        buffer: &mut [u8],
+    ) -> Result<(), BMA400Error<InterfaceError>> {
+        let int_status = (InterruptStatus0::ADDR..=InterruptStatus2::ADDR).contains(&R::ADDR);
+        if self.reset_check_due(int_status) {
+            let mut event = [0u8; 1];
+            self.interface.read_register(Event, &mut event).await?;
+            if event[0] & 0x01 != 0 {
+                self.recover_from_reset().await?;
+                return Err(BMA400Error::DeviceWasReset);
+            }
+        }
+        self.interface.read_register(register, buffer).await
+    }
+
+    /// Writes the stored configuration back to a device that was reset unexpectedly
+    async fn recover_from_reset(&mut self) -> Result<(), BMA400Error<InterfaceError>> {
+        let config = self.config.clone();
+        self.replay_config(&config).await?;
+        self.fifo_flushed();
+        Ok(())
+    }
+
+    /// Queues a configuration register write. Adjacent registers are combined and written
+    /// in a single burst once a register that does not follow them is written, or when
+    /// the configuration write completes
+    pub(crate) async fn write_register<R: ConfigReg + FromByte + Copy>(
+        &mut self,
+        register: R,
+        verify: bool,
+    ) -> Result<(), BMA400Error<InterfaceError>> {
+        self.written.insert(register.addr());
+        if !self.pending.push(register, verify) {
+            self.flush_writes().await?;
+            self.pending.push(register, verify);
+        }
+        Ok(())
+    }
+
+    /// Queues the value `byte` of the configuration register at `addr` to be written, see
+    /// [`write_register()`](BMA400::write_register)
+    async fn write_raw_register(
+        &mut self,
+        addr: u8,
+        byte: u8,
+        verify: bool,
+    ) -> Result<(), BMA400Error<InterfaceError>> {
+        let mask = register_info(addr).map_or(0xFF, |info| info.mask);
+        self.written.insert(addr);
+        if !self.pending.push_raw(addr, byte, mask, verify) {
+            self.flush_writes().await?;
+            self.pending.push_raw(addr, byte, mask, verify);
+        }
+        Ok(())
+    }
+
+    /// Writes any queued configuration registers. If verification was requested, reads the
+    /// registers back and checks the command error register
+    async fn flush_writes(&mut self) -> Result<(), BMA400Error<InterfaceError>> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let pending = core::mem::replace(&mut self.pending, PendingWrites::new());
+        let start = pending.start();
+        self.interface
+            .write_registers(start, pending.bytes())
+            .await?;
+        if pending.verify() {
+            let mut buffer = [0u8; CONFIG_BLOCK_LEN];
+            let actual = &mut buffer[..pending.bytes().len()];
+            self.interface
+                .read_register(RawConfigReg::new(start, 0), actual)
+                .await?;
+            let registers = pending
+                .bytes()
+                .iter()
+                .zip(pending.masks())
+                .zip(actual.iter());
+            for (offset, ((expected, mask), actual)) in registers.enumerate() {
+                if actual & mask != *expected {
+                    return Err(BMA400Error::VerifyFailed {
+                        register: start + offset as u8,
+                        expected: *expected,
+                        actual: *actual,
+                    });
+                }
+            }
+            let mut err_byte = [0u8; 1];
+            self.interface.read_register(ErrReg, &mut err_byte).await?;
+            if err_byte[0] & 0b00000010 != 0 {
+                return Err(BMA400Error::VerifyFailed {
+                    register: ErrReg::ADDR,
+                    expected: 0x00,
+                    actual: err_byte[0],
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts recording the registers written by a configuration builder, returning the
+    /// configuration to restore if the write fails
+    pub(crate) fn begin_config_write(&mut self) -> Config {
+        self.written = RegisterSet::default();
+        self.config.clone()
+    }
+
+    /// Completes a configuration write. If it failed, restores the registers written since
+    /// [`begin_config_write()`](BMA400::begin_config_write) and the driver configuration
+    pub(crate) async fn end_config_write(
+        &mut self,
+        previous: Config,
+        result: Result<(), BMA400Error<InterfaceError>>,
+    ) -> Result<(), BMA400Error<InterfaceError>> {
+        let result = match result {
+            Ok(()) => self.flush_writes().await,
+            Err(error) => {
+                // Discard the queued writes, they are restored below
+                self.pending = PendingWrites::new();
+                Err(error)
+            }
+        };
+        let written = core::mem::take(&mut self.written);
+        let error = match result {
+            Ok(()) => {
+                self.record_fifo_changes(&previous);
+                return Ok(());
+            }
+            Err(error) => error,
+        };
+        let registers = previous.to_registers();
+        self.config = previous;
+        // Restore the interrupt enable registers last so that no interrupt is enabled
+        // while its settings are being restored
+        let is_int_enable = |addr: &u8| *addr == IntConfig0::ADDR || *addr == IntConfig1::ADDR;
+        let restore_order = written
+            .iter()
+            .filter(|addr| !is_int_enable(addr))
+            .chain(written.iter().filter(is_int_enable));
+        let mut unknown = RegisterSet::default();
+        let mut rollback_error = None;
+        for addr in restore_order {
+            let register = RawConfigReg::new(addr, registers.get(addr));
+            if let Err(BMA400Error::IOError(e)) = self.interface.write_register(register).await {
+                unknown.insert(addr);
+                rollback_error = Some(e);
+            }
+        }
+        match rollback_error {
+            Some(error) => Err(BMA400Error::RollbackFailed { error, unknown }),
+            None => Err(error),
+        }
+    }
+
     /// Consumes the device instance returning the I²C / SPI Interface
     pub fn destroy(self) -> T {
-        self.interface
+        self.interface.interface
     }
 }
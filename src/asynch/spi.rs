@@ -1,26 +1,43 @@
 use crate::{
-    BMA400, BMA400Error, Config, SPIInterface,
+    AsyncInterface, BMA400, BMA400Error, SPIInterface,
     asynch::{ReadFromRegister, WriteToRegister},
-    embedded_hal_async::spi::{Operation, SpiDevice},
+    config::CONFIG_BLOCK_LEN,
     registers::{ChipId, ConfigReg, InterfaceConfig, ReadReg},
 };
+use embedded_hal_async::spi::{Operation, SpiDevice};
 
-impl<SPI> WriteToRegister for SPIInterface<SPI>
+impl<SPI> WriteToRegister for AsyncInterface<SPIInterface<SPI>>
 where
     SPI: SpiDevice,
 {
     type Error = BMA400Error<SPI::Error>;
 
     async fn write_register<T: ConfigReg>(&mut self, register: T) -> Result<(), Self::Error> {
-        self.spi
+        self.interface
+            .spi
             .write(&[register.addr(), register.to_byte()])
             .await
             .map_err(BMA400Error::IOError)?;
         Ok(())
     }
+
+    async fn write_registers(&mut self, start: u8, data: &[u8]) -> Result<(), Self::Error> {
+        // Start address followed by the data, the device increments the address after each byte
+        let mut buffer = [0u8; CONFIG_BLOCK_LEN + 1];
+        for (i, chunk) in data.chunks(CONFIG_BLOCK_LEN).enumerate() {
+            buffer[0] = start + (i * CONFIG_BLOCK_LEN) as u8;
+            buffer[1..=chunk.len()].copy_from_slice(chunk);
+            self.interface
+                .spi
+                .write(&buffer[..=chunk.len()])
+                .await
+                .map_err(BMA400Error::IOError)?;
+        }
+        Ok(())
+    }
 }
 
-impl<SPI> ReadFromRegister for SPIInterface<SPI>
+impl<SPI> ReadFromRegister for AsyncInterface<SPIInterface<SPI>>
 where
     SPI: SpiDevice,
 {
@@ -31,7 +48,8 @@ where
         register: T,
         buffer: &mut [u8],
     ) -> Result<(), Self::Error> {
-        self.spi
+        self.interface
+            .spi
             .transaction(&mut [
                 Operation::Write(&[register.addr() | 1 << 7, 0]),
                 Operation::Read(buffer),
@@ -40,9 +58,14 @@ where
             .map_err(BMA400Error::IOError)?;
         Ok(())
     }
+
+    async fn after_reset(&mut self) -> Result<(), Self::Error> {
+        // The device returns to I²C mode, a dummy read switches it back to SPI
+        self.read_register(ChipId, &mut [0u8; 1]).await
+    }
 }
 
-impl<SPI> BMA400<SPIInterface<SPI>>
+impl<SPI> BMA400<AsyncInterface<SPIInterface<SPI>>>
 where
     SPI: SpiDevice,
 {
@@ -52,6 +75,7 @@ where
     /// ```
     /// # use embedded_hal_mock::eh1::spi::{Mock, Transaction};
     /// use bma400::BMA400;
+    /// # async fn example() {
     /// # let expected_io = vec![
     /// #   Transaction::transaction_start(),
     /// #   Transaction::write_vec(vec![0x80, 0x00]),
@@ -63,14 +87,18 @@ where
     /// #   Transaction::transaction_end(),
     /// # ];
     /// # let mut spi = Mock::new(&expected_io);
-    /// // spi implements embedded-hal spi::SpiDevice
-    /// let mut accelerometer = BMA400::new_spi(&mut spi);
+    /// // spi implements embedded-hal-async spi::SpiDevice
+    /// let mut accelerometer = BMA400::new_spi_async(&mut spi).await;
     /// assert!(accelerometer.is_ok());
     /// # spi.done();
+    /// # }
     /// ```
-    pub async fn new_spi(spi: SPI) -> Result<BMA400<SPIInterface<SPI>>, BMA400Error<SPI::Error>> {
-        let mut interface = SPIInterface { spi };
-        let config = Config::default();
+    pub async fn new_spi_async(
+        spi: SPI,
+    ) -> Result<BMA400<AsyncInterface<SPIInterface<SPI>>>, BMA400Error<SPI::Error>> {
+        let mut interface = AsyncInterface {
+            interface: SPIInterface { spi },
+        };
         // Initialize SPI Mode by doing a dummy read
         interface.read_register(ChipId, &mut [0u8; 1]).await?;
         // Validate Chip ID
@@ -79,7 +107,7 @@ where
         if chip_id[0] != 0x90 {
             Err(BMA400Error::ChipIdReadFailed)
         } else {
-            Ok(BMA400 { interface, config })
+            Ok(BMA400::new(interface))
         }
     }
     /// Create a new instance of the BMA400 using 3-wire SPI
@@ -88,6 +116,7 @@ where
     /// ```
     /// # use embedded_hal_mock::eh1::spi::{Mock, Transaction};
     /// use bma400::BMA400;
+    /// # async fn example() {
     /// # let expected_io = vec![
     /// #   Transaction::transaction_start(),
     /// #   Transaction::write_vec(vec![0x80, 0x00]),
@@ -102,16 +131,18 @@ where
     /// #   Transaction::transaction_end(),
     /// # ];
     /// # let mut spi = Mock::new(&expected_io);
-    /// // spi implements embedded-hal spi::SpiDevice
-    /// let mut accelerometer = BMA400::new_spi_3wire(&mut spi);
+    /// // spi implements embedded-hal-async spi::SpiDevice
+    /// let mut accelerometer = BMA400::new_spi_3wire_async(&mut spi).await;
     /// assert!(accelerometer.is_ok());
     /// # spi.done();
+    /// # }
     /// ```
-    pub async fn new_spi_3wire(
+    pub async fn new_spi_3wire_async(
         spi: SPI,
-    ) -> Result<BMA400<SPIInterface<SPI>>, BMA400Error<SPI::Error>> {
-        let mut interface = SPIInterface { spi };
-        let config = Config::default();
+    ) -> Result<BMA400<AsyncInterface<SPIInterface<SPI>>>, BMA400Error<SPI::Error>> {
+        let mut interface = AsyncInterface {
+            interface: SPIInterface { spi },
+        };
         // Initialize SPI Mode by doing a dummy read
         interface.read_register(ChipId, &mut [0u8; 1]).await?;
         let mut chip_id = [0u8; 1];
@@ -121,7 +152,88 @@ where
         if chip_id[0] != 0x90 {
             Err(BMA400Error::ChipIdReadFailed)
         } else {
-            Ok(BMA400 { interface, config })
+            Ok(BMA400::new(interface))
         }
     }
+
+    /// Create a new instance of the BMA400 using 4-wire SPI, adopting the configuration
+    /// already held in the device registers instead of assuming reset values
+    ///
+    /// See [`resync_config()`](BMA400::resync_config)
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::spi::{Mock, Transaction};
+    /// use bma400::BMA400;
+    /// # async fn example() {
+    /// # let expected_io = vec![
+    /// #   Transaction::transaction_start(),
+    /// #   Transaction::write_vec(vec![0x80, 0x00]),
+    /// #   Transaction::read_vec(vec![0x00]),
+    /// #   Transaction::transaction_end(),
+    /// #   Transaction::transaction_start(),
+    /// #   Transaction::write_vec(vec![0x80, 0x00]),
+    /// #   Transaction::read_vec(vec![0x90]),
+    /// #   Transaction::transaction_end(),
+    /// #   Transaction::transaction_start(),
+    /// #   Transaction::write_vec(vec![0x99, 0x00]),
+    /// #   Transaction::read_vec(vec![0u8; 64]),
+    /// #   Transaction::transaction_end(),
+    /// # ];
+    /// # let mut spi = Mock::new(&expected_io);
+    /// // spi implements embedded-hal-async spi::SpiDevice
+    /// let mut accelerometer = BMA400::new_spi_adopt_async(&mut spi).await;
+    /// assert!(accelerometer.is_ok());
+    /// # spi.done();
+    /// # }
+    /// ```
+    pub async fn new_spi_adopt_async(
+        spi: SPI,
+    ) -> Result<BMA400<AsyncInterface<SPIInterface<SPI>>>, BMA400Error<SPI::Error>> {
+        let mut device = Self::new_spi_async(spi).await?;
+        device.resync_config().await?;
+        Ok(device)
+    }
+
+    /// Create a new instance of the BMA400 using 3-wire SPI, adopting the configuration
+    /// already held in the device registers instead of assuming reset values
+    ///
+    /// See [`resync_config()`](BMA400::resync_config)
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::spi::{Mock, Transaction};
+    /// use bma400::BMA400;
+    /// # async fn example() {
+    /// # let expected_io = vec![
+    /// #   Transaction::transaction_start(),
+    /// #   Transaction::write_vec(vec![0x80, 0x00]),
+    /// #   Transaction::read_vec(vec![0x00]),
+    /// #   Transaction::transaction_end(),
+    /// #   Transaction::transaction_start(),
+    /// #   Transaction::write_vec(vec![0x80, 0x00]),
+    /// #   Transaction::read_vec(vec![0x90]),
+    /// #   Transaction::transaction_end(),
+    /// #   Transaction::transaction_start(),
+    /// #   Transaction::write_vec(vec![0x7C, 0x01]),
+    /// #   Transaction::transaction_end(),
+    /// #   Transaction::transaction_start(),
+    /// #   Transaction::write_vec(vec![0x99, 0x00]),
+    /// #   Transaction::read_vec(vec![0u8; 64]),
+    /// #   Transaction::transaction_end(),
+    /// # ];
+    /// # let mut spi = Mock::new(&expected_io);
+    /// // spi implements embedded-hal-async spi::SpiDevice
+    /// let mut accelerometer = BMA400::new_spi_3wire_adopt_async(&mut spi).await;
+    /// assert!(accelerometer.is_ok());
+    /// # spi.done();
+    /// # }
+    /// ```
+    pub async fn new_spi_3wire_adopt_async(
+        spi: SPI,
+    ) -> Result<BMA400<AsyncInterface<SPIInterface<SPI>>>, BMA400Error<SPI::Error>> {
+        let mut device = Self::new_spi_3wire_async(spi).await?;
+        device.resync_config().await?;
+        Ok(device)
+    }
 }
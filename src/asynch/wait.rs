@@ -0,0 +1,171 @@
+use core::{
+    convert::Infallible,
+    future::{pending, poll_fn},
+    pin::pin,
+    task::Poll,
+};
+
+use embedded_hal::digital::{Error, ErrorKind, ErrorType};
+use embedded_hal_async::digital::Wait;
+
+use super::{ReadFromRegister, WriteToRegister};
+use crate::{AsyncInterface, BMA400, BMA400Error, Interrupts};
+
+/// Placeholder for an interrupt pin that is not connected
+#[derive(Debug)]
+pub struct NoPin;
+
+impl ErrorType for NoPin {
+    type Error = Infallible;
+}
+
+impl Wait for NoPin {
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        pending().await
+    }
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        pending().await
+    }
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        pending().await
+    }
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        pending().await
+    }
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        pending().await
+    }
+}
+
+/// A BMA400 together with the MCU inputs its INT1 / INT2 pins are wired to
+///
+/// Create one using [`with_int1_pin()`](BMA400::with_int1_pin),
+/// [`with_int2_pin()`](BMA400::with_int2_pin) or [`with_int_pins()`](BMA400::with_int_pins)
+///
+/// The pins to wait on and their active level are taken from the current interrupt pin
+/// configuration (see [`config_int_pins()`](BMA400::config_int_pins)). In latched mode
+/// the listener waits for a pin to be at its active level, so an interrupt raised before
+/// the wait started is not missed. Otherwise it waits for the edge into the active level
+pub struct InterruptListener<T, INT1, INT2> {
+    device: BMA400<AsyncInterface<T>>,
+    int1: Option<INT1>,
+    int2: Option<INT2>,
+}
+
+impl<T> BMA400<AsyncInterface<T>> {
+    /// Wait for interrupts on the MCU input connected to the INT1 pin
+    pub fn with_int1_pin<INT1: Wait>(self, int1: INT1) -> InterruptListener<T, INT1, NoPin> {
+        InterruptListener {
+            device: self,
+            int1: Some(int1),
+            int2: None,
+        }
+    }
+
+    /// Wait for interrupts on the MCU input connected to the INT2 pin
+    pub fn with_int2_pin<INT2: Wait>(self, int2: INT2) -> InterruptListener<T, NoPin, INT2> {
+        InterruptListener {
+            device: self,
+            int1: None,
+            int2: Some(int2),
+        }
+    }
+
+    /// Wait for interrupts on the MCU inputs connected to the INT1 and INT2 pins
+    pub fn with_int_pins<INT1: Wait, INT2: Wait>(
+        self,
+        int1: INT1,
+        int2: INT2,
+    ) -> InterruptListener<T, INT1, INT2> {
+        InterruptListener {
+            device: self,
+            int1: Some(int1),
+            int2: Some(int2),
+        }
+    }
+}
+
+impl<T, INT1, INT2> InterruptListener<T, INT1, INT2> {
+    /// The underlying device, e.g. to change its configuration between waits
+    pub fn device(&mut self) -> &mut BMA400<AsyncInterface<T>> {
+        &mut self.device
+    }
+
+    /// Consumes the listener, returning the device and the pins
+    pub fn destroy(self) -> (BMA400<AsyncInterface<T>>, Option<INT1>, Option<INT2>) {
+        (self.device, self.int1, self.int2)
+    }
+}
+
+impl<T, INT1, INT2, InterfaceError> InterruptListener<T, INT1, INT2>
+where
+    AsyncInterface<T>: ReadFromRegister<Error = BMA400Error<InterfaceError>>
+        + WriteToRegister<Error = BMA400Error<InterfaceError>>,
+    INT1: Wait,
+    INT2: Wait,
+{
+    /// Waits for an interrupt on either pin, then reads the interrupt status registers,
+    /// clearing any latched interrupts
+    ///
+    /// Returns [`BMA400Error::NoInterruptPin`] if no interrupt is mapped to a connected pin
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::{digital, i2c::Mock};
+    /// # use bma400::{BMA400Error, I2CInterface, InterruptEvent, InterruptListener, NoPin};
+    /// async fn taps(
+    ///     listener: &mut InterruptListener<I2CInterface<Mock>, digital::Mock, NoPin>,
+    /// ) -> Result<(), BMA400Error<embedded_hal::i2c::ErrorKind>> {
+    ///     loop {
+    ///         for event in listener.wait_for_event().await? {
+    ///             match event {
+    ///                 InterruptEvent::SingleTap => { /* ... */ }
+    ///                 InterruptEvent::DoubleTap => { /* ... */ }
+    ///                 _ => {}
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn wait_for_event(&mut self) -> Result<Interrupts, BMA400Error<InterfaceError>> {
+        let latched = self.device.config.int_config().get_config1().latch_int();
+        let pins = self.device.config.int_pin_config();
+        let int1 = self
+            .int1
+            .as_mut()
+            .filter(|_| pins.int1_mapped())
+            .map(|pin| (pin, pins.int1_active_high()));
+        let int2 = self
+            .int2
+            .as_mut()
+            .filter(|_| pins.int2_mapped())
+            .map(|pin| (pin, pins.int2_active_high()));
+        if int1.is_none() && int2.is_none() {
+            return Err(BMA400Error::NoInterruptPin);
+        }
+        let mut int1 = pin!(wait_active(int1, latched));
+        let mut int2 = pin!(wait_active(int2, latched));
+        poll_fn(|cx| match int1.as_mut().poll(cx) {
+            Poll::Ready(result) => Poll::Ready(result),
+            Poll::Pending => int2.as_mut().poll(cx),
+        })
+        .await
+        .map_err(BMA400Error::PinError)?;
+        self.device.get_interrupts().await
+    }
+}
+
+// Completes once the pin reaches its active level (latched) or on the edge into it.
+// Never completes if the pin is not waited on
+async fn wait_active<P: Wait>(pin: Option<(&mut P, bool)>, latched: bool) -> Result<(), ErrorKind> {
+    let Some((pin, active_high)) = pin else {
+        return pending().await;
+    };
+    match (active_high, latched) {
+        (true, true) => pin.wait_for_high().await,
+        (false, true) => pin.wait_for_low().await,
+        (true, false) => pin.wait_for_rising_edge().await,
+        (false, false) => pin.wait_for_falling_edge().await,
+    }
+    .map_err(|e| e.kind())
+}
@@ -0,0 +1,93 @@
+use crate::{
+    BMA400, BMA400Error, CustomInterface, RegisterInterface,
+    blocking::{ReadFromRegister, WriteToRegister},
+    registers::{ChipId, ConfigReg, ReadReg},
+};
+
+impl<T> WriteToRegister for CustomInterface<T>
+where
+    T: RegisterInterface,
+{
+    type Error = BMA400Error<T::Error>;
+
+    fn write_register<R: ConfigReg>(&mut self, register: R) -> Result<(), Self::Error> {
+        self.interface
+            .write_registers(register.addr(), &[register.to_byte()])
+            .map_err(BMA400Error::IOError)
+    }
+
+    fn write_registers(&mut self, start: u8, data: &[u8]) -> Result<(), Self::Error> {
+        self.interface
+            .write_registers(start, data)
+            .map_err(BMA400Error::IOError)
+    }
+}
+
+impl<T> ReadFromRegister for CustomInterface<T>
+where
+    T: RegisterInterface,
+{
+    type Error = BMA400Error<T::Error>;
+
+    fn read_register<R: ReadReg>(
+        &mut self,
+        register: R,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.interface
+            .read_registers(register.addr(), buffer)
+            .map_err(BMA400Error::IOError)
+    }
+}
+
+impl<T> BMA400<CustomInterface<T>>
+where
+    T: RegisterInterface,
+{
+    /// Create a new instance of the BMA400 using a custom [`RegisterInterface`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// # use embedded_hal::i2c::I2c;
+    /// use bma400::{BMA400, MAX_WRITE_LEN, RegisterInterface};
+    ///
+    /// // I²C with a 16-bit register address prefix, e.g. through a bridge
+    /// struct Bridge<I2C> {
+    ///     i2c: I2C,
+    /// }
+    ///
+    /// impl<I2C: I2c> RegisterInterface for Bridge<I2C> {
+    ///     type Error = I2C::Error;
+    ///
+    ///     fn read_registers(&mut self, start: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+    ///         self.i2c.write_read(0x42, &[0x00, start], buffer)
+    ///     }
+    ///
+    ///     fn write_registers(&mut self, start: u8, data: &[u8]) -> Result<(), Self::Error> {
+    ///         let mut buffer = [0u8; MAX_WRITE_LEN + 2];
+    ///         buffer[1] = start;
+    ///         buffer[2..data.len() + 2].copy_from_slice(data);
+    ///         self.i2c.write(0x42, &buffer[..data.len() + 2])
+    ///     }
+    /// }
+    ///
+    /// # let expected = vec![Transaction::write_read(0x42, vec![0x00, 0x00], vec![0x90])];
+    /// # let mut i2c = Mock::new(&expected);
+    /// let mut accelerometer = BMA400::new_with_interface(Bridge { i2c: &mut i2c });
+    /// assert!(accelerometer.is_ok());
+    /// # i2c.done();
+    /// ```
+    pub fn new_with_interface(
+        interface: T,
+    ) -> Result<BMA400<CustomInterface<T>>, BMA400Error<T::Error>> {
+        let mut interface = CustomInterface { interface };
+        let mut chip_id = [0u8; 1];
+        interface.read_register(ChipId, &mut chip_id)?;
+        if chip_id[0] != 0x90 {
+            Err(BMA400Error::ChipIdReadFailed)
+        } else {
+            Ok(BMA400::new(interface))
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use crate::{
-    BMA400, BMA400Error, Config, I2CInterface,
+    BMA400, BMA400Error, I2CInterface,
     blocking::{ReadFromRegister, WriteToRegister},
-    embedded_hal::i2c::{I2c, SevenBitAddress},
+    config::CONFIG_BLOCK_LEN,
     registers::{ChipId, ConfigReg, ReadReg},
 };
+use embedded_hal::i2c::{I2c, SevenBitAddress};
 
 impl<I2C> WriteToRegister for I2CInterface<I2C>
 where
@@ -16,6 +17,19 @@ where
             .write(self.addr, &[register.addr(), register.to_byte()])
             .map_err(BMA400Error::IOError)
     }
+
+    fn write_registers(&mut self, start: u8, data: &[u8]) -> Result<(), Self::Error> {
+        // Start address followed by the data, the device increments the address after each byte
+        let mut buffer = [0u8; CONFIG_BLOCK_LEN + 1];
+        for (i, chunk) in data.chunks(CONFIG_BLOCK_LEN).enumerate() {
+            buffer[0] = start + (i * CONFIG_BLOCK_LEN) as u8;
+            buffer[1..=chunk.len()].copy_from_slice(chunk);
+            self.i2c
+                .write(self.addr, &buffer[..=chunk.len()])
+                .map_err(BMA400Error::IOError)?;
+        }
+        Ok(())
+    }
 }
 
 impl<I2C> ReadFromRegister for I2CInterface<I2C>
@@ -56,13 +70,12 @@ where
     /// ```
     pub fn new_i2c(i2c: I2C) -> Result<BMA400<I2CInterface<I2C>>, BMA400Error<I2C::Error>> {
         let mut interface = I2CInterface { addr: 0b10100, i2c };
-        let config = Config::default();
         let mut chip_id = [0u8; 1];
         interface.read_register(ChipId, &mut chip_id)?;
         if chip_id[0] != 0x90 {
             Err(BMA400Error::ChipIdReadFailed)
         } else {
-            Ok(BMA400 { interface, config })
+            Ok(BMA400::new(interface))
         }
     }
 
@@ -83,13 +96,66 @@ where
     /// ```
     pub fn new_i2c_alt(i2c: I2C) -> Result<BMA400<I2CInterface<I2C>>, BMA400Error<I2C::Error>> {
         let mut interface = I2CInterface { addr: 0b10101, i2c };
-        let config = Config::default();
         let mut chip_id = [0u8; 1];
         interface.read_register(ChipId, &mut chip_id)?;
         if chip_id[0] != 0x90 {
             Err(BMA400Error::ChipIdReadFailed)
         } else {
-            Ok(BMA400 { interface, config })
+            Ok(BMA400::new(interface))
         }
     }
+
+    /// Create a new instance of the BMA400 using I²C
+    /// with the default address (0b010100), adopting the configuration
+    /// already held in the device registers instead of assuming reset values
+    ///
+    /// See [`resync_config()`](BMA400::resync_config)
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// use bma400::BMA400;
+    /// # let expected = vec![
+    /// #     Transaction::write_read(0b10100, vec![0x00], vec![0x90]),
+    /// #     Transaction::write_read(0b10100, vec![0x19], vec![0u8; 64]),
+    /// # ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// // i2c implements embedded-hal i2c::I2c
+    /// let mut accelerometer = BMA400::new_i2c_adopt(&mut i2c);
+    /// assert!(accelerometer.is_ok());
+    /// # i2c.done();
+    /// ```
+    pub fn new_i2c_adopt(i2c: I2C) -> Result<BMA400<I2CInterface<I2C>>, BMA400Error<I2C::Error>> {
+        let mut device = Self::new_i2c(i2c)?;
+        device.resync_config()?;
+        Ok(device)
+    }
+
+    /// Create a new instance of the BMA400 using I²C
+    /// with the alternate address (0b010101), adopting the configuration
+    /// already held in the device registers instead of assuming reset values
+    ///
+    /// See [`resync_config()`](BMA400::resync_config)
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// use bma400::BMA400;
+    /// # let expected = vec![
+    /// #     Transaction::write_read(0b10101, vec![0x00], vec![0x90]),
+    /// #     Transaction::write_read(0b10101, vec![0x19], vec![0u8; 64]),
+    /// # ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// // i2c implements embedded-hal i2c::I2c
+    /// let mut accelerometer = BMA400::new_i2c_alt_adopt(&mut i2c);
+    /// assert!(accelerometer.is_ok());
+    /// # i2c.done();
+    /// ```
+    pub fn new_i2c_alt_adopt(
+        i2c: I2C,
+    ) -> Result<BMA400<I2CInterface<I2C>>, BMA400Error<I2C::Error>> {
+        let mut device = Self::new_i2c_alt(i2c)?;
+        device.resync_config()?;
+        Ok(device)
+    }
 }
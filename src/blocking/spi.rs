@@ -1,9 +1,10 @@
 use crate::{
-    BMA400, BMA400Error, Config, SPIInterface,
+    BMA400, BMA400Error, SPIInterface,
     blocking::{ReadFromRegister, WriteToRegister},
-    embedded_hal::spi::{Operation, SpiDevice},
+    config::CONFIG_BLOCK_LEN,
     registers::{ChipId, ConfigReg, InterfaceConfig, ReadReg},
 };
+use embedded_hal::spi::{Operation, SpiDevice};
 
 impl<SPI> WriteToRegister for SPIInterface<SPI>
 where
@@ -17,6 +18,19 @@ where
             .map_err(BMA400Error::IOError)?;
         Ok(())
     }
+
+    fn write_registers(&mut self, start: u8, data: &[u8]) -> Result<(), Self::Error> {
+        // Start address followed by the data, the device increments the address after each byte
+        let mut buffer = [0u8; CONFIG_BLOCK_LEN + 1];
+        for (i, chunk) in data.chunks(CONFIG_BLOCK_LEN).enumerate() {
+            buffer[0] = start + (i * CONFIG_BLOCK_LEN) as u8;
+            buffer[1..=chunk.len()].copy_from_slice(chunk);
+            self.spi
+                .write(&buffer[..=chunk.len()])
+                .map_err(BMA400Error::IOError)?;
+        }
+        Ok(())
+    }
 }
 
 impl<SPI> ReadFromRegister for SPIInterface<SPI>
@@ -38,6 +52,11 @@ where
             .map_err(BMA400Error::IOError)?;
         Ok(())
     }
+
+    fn after_reset(&mut self) -> Result<(), Self::Error> {
+        // The device returns to I²C mode, a dummy read switches it back to SPI
+        self.read_register(ChipId, &mut [0u8; 1])
+    }
 }
 
 impl<SPI> BMA400<SPIInterface<SPI>>
@@ -68,7 +87,6 @@ where
     /// ```
     pub fn new_spi(spi: SPI) -> Result<BMA400<SPIInterface<SPI>>, BMA400Error<SPI::Error>> {
         let mut interface = SPIInterface { spi };
-        let config = Config::default();
         // Initialize SPI Mode by doing a dummy read
         interface.read_register(ChipId, &mut [0u8; 1])?;
         // Validate Chip ID
@@ -77,7 +95,7 @@ where
         if chip_id[0] != 0x90 {
             Err(BMA400Error::ChipIdReadFailed)
         } else {
-            Ok(BMA400 { interface, config })
+            Ok(BMA400::new(interface))
         }
     }
     /// Create a new instance of the BMA400 using 3-wire SPI
@@ -107,7 +125,6 @@ where
     /// ```
     pub fn new_spi_3wire(spi: SPI) -> Result<BMA400<SPIInterface<SPI>>, BMA400Error<SPI::Error>> {
         let mut interface = SPIInterface { spi };
-        let config = Config::default();
         // Initialize SPI Mode by doing a dummy read
         interface.read_register(ChipId, &mut [0u8; 1])?;
         let mut chip_id = [0u8; 1];
@@ -117,7 +134,82 @@ where
         if chip_id[0] != 0x90 {
             Err(BMA400Error::ChipIdReadFailed)
         } else {
-            Ok(BMA400 { interface, config })
+            Ok(BMA400::new(interface))
         }
     }
+
+    /// Create a new instance of the BMA400 using 4-wire SPI, adopting the configuration
+    /// already held in the device registers instead of assuming reset values
+    ///
+    /// See [`resync_config()`](BMA400::resync_config)
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::spi::{Mock, Transaction};
+    /// use bma400::BMA400;
+    /// # let expected_io = vec![
+    /// #   Transaction::transaction_start(),
+    /// #   Transaction::write_vec(vec![0x80, 0x00]),
+    /// #   Transaction::read_vec(vec![0x00]),
+    /// #   Transaction::transaction_end(),
+    /// #   Transaction::transaction_start(),
+    /// #   Transaction::write_vec(vec![0x80, 0x00]),
+    /// #   Transaction::read_vec(vec![0x90]),
+    /// #   Transaction::transaction_end(),
+    /// #   Transaction::transaction_start(),
+    /// #   Transaction::write_vec(vec![0x99, 0x00]),
+    /// #   Transaction::read_vec(vec![0u8; 64]),
+    /// #   Transaction::transaction_end(),
+    /// # ];
+    /// # let mut spi = Mock::new(&expected_io);
+    /// // spi implements embedded-hal spi::SpiDevice
+    /// let mut accelerometer = BMA400::new_spi_adopt(&mut spi);
+    /// assert!(accelerometer.is_ok());
+    /// # spi.done();
+    /// ```
+    pub fn new_spi_adopt(spi: SPI) -> Result<BMA400<SPIInterface<SPI>>, BMA400Error<SPI::Error>> {
+        let mut device = Self::new_spi(spi)?;
+        device.resync_config()?;
+        Ok(device)
+    }
+
+    /// Create a new instance of the BMA400 using 3-wire SPI, adopting the configuration
+    /// already held in the device registers instead of assuming reset values
+    ///
+    /// See [`resync_config()`](BMA400::resync_config)
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::spi::{Mock, Transaction};
+    /// use bma400::BMA400;
+    /// # let expected_io = vec![
+    /// #   Transaction::transaction_start(),
+    /// #   Transaction::write_vec(vec![0x80, 0x00]),
+    /// #   Transaction::read_vec(vec![0x00]),
+    /// #   Transaction::transaction_end(),
+    /// #   Transaction::transaction_start(),
+    /// #   Transaction::write_vec(vec![0x80, 0x00]),
+    /// #   Transaction::read_vec(vec![0x90]),
+    /// #   Transaction::transaction_end(),
+    /// #   Transaction::transaction_start(),
+    /// #   Transaction::write_vec(vec![0x7C, 0x01]),
+    /// #   Transaction::transaction_end(),
+    /// #   Transaction::transaction_start(),
+    /// #   Transaction::write_vec(vec![0x99, 0x00]),
+    /// #   Transaction::read_vec(vec![0u8; 64]),
+    /// #   Transaction::transaction_end(),
+    /// # ];
+    /// # let mut spi = Mock::new(&expected_io);
+    /// // spi implements embedded-hal spi::SpiDevice
+    /// let mut accelerometer = BMA400::new_spi_3wire_adopt(&mut spi);
+    /// assert!(accelerometer.is_ok());
+    /// # spi.done();
+    /// ```
+    pub fn new_spi_3wire_adopt(
+        spi: SPI,
+    ) -> Result<BMA400<SPIInterface<SPI>>, BMA400Error<SPI::Error>> {
+        let mut device = Self::new_spi_3wire(spi)?;
+        device.resync_config()?;
+        Ok(device)
+    }
 }
@@ -1,24 +1,32 @@
 //! Accelerometer configuration options
 mod accel_config;
-use accel_config::AccConfig;
-mod int_config;
-use int_config::IntConfig;
-mod int_pin_config;
-use int_pin_config::IntPinConfig;
-mod fifo_config;
-use fifo_config::FifoConfig;
+mod actchg_config;
 mod auto_lp_config;
-use auto_lp_config::AutoLpConfig;
 mod auto_wkup_config;
-use auto_wkup_config::AutoWakeupConfig;
-mod wkup_int_config;
-use wkup_int_config::WakeupIntConfig;
-mod actchg_config;
-use actchg_config::ActChgConfig;
-mod tap_config;
-use tap_config::TapConfig;
+mod device_config;
+mod fifo_config;
+mod gen_int_config;
+mod int_config;
+mod int_pin_config;
 mod orientch_config;
-use orientch_config::OrientChgConfig;
+mod profile;
+mod tap_config;
+mod wkup_int_config;
+
+// Re-export configuration values
+pub use accel_config::AccConfig;
+pub use actchg_config::ActChgConfig;
+pub use auto_lp_config::AutoLpConfig;
+pub use auto_wkup_config::AutoWakeupConfig;
+pub use device_config::DeviceConfig;
+pub use fifo_config::FifoConfig;
+pub use gen_int_config::{Gen1IntConfig, Gen2IntConfig};
+pub use int_config::IntConfig;
+pub use int_pin_config::IntPinConfig;
+pub use orientch_config::OrientChgConfig;
+pub use profile::Profile;
+pub use tap_config::TapConfig;
+pub use wkup_int_config::WakeupIntConfig;
 
 // Re-export builders
 pub use accel_config::AccConfigBuilder;
@@ -33,10 +41,262 @@ pub use orientch_config::OrientChgConfigBuilder;
 pub use tap_config::TapConfigBuilder;
 pub use wkup_int_config::WakeupIntConfigBuilder;
 
-mod gen_int_config;
-use gen_int_config::{Gen1IntConfig, Gen2IntConfig};
+use crate::{
+    ConfigError, ConfigErrors, DataSource, FifoSettings, OutputDataRate, Scale,
+    registers::{
+        AccConfig0, AccConfig2, AutoLowPow1, AutoWakeup1, ConfigReg, FifoConfig0, FromByte,
+        Int1Map, Int12IOCtrl, IntConfig0, IntConfig1, ReadReg, TapConfig1, WakeupIntConfig0,
+    },
+};
+
+/// Number of bytes in the configuration register block (AccConfig0 0x19 - TapConfig1 0x58)
+pub(crate) const CONFIG_BLOCK_LEN: usize = (TapConfig1::ADDR - AccConfig0::ADDR) as usize + 1;
+
+/// Register ranges in the order a configuration is replayed after a reset: the accelerometer
+/// first, then the FIFO and interrupt engines, interrupt enables and finally the pin mapping
+pub(crate) const REPLAY_ORDER: [(u8, u8); 4] = [
+    (AccConfig0::ADDR, AccConfig2::ADDR),
+    (FifoConfig0::ADDR, TapConfig1::ADDR),
+    (IntConfig0::ADDR, IntConfig1::ADDR),
+    (Int1Map::ADDR, Int12IOCtrl::ADDR),
+];
+
+// `Ord::clamp()` can't be called from the const configuration setters
+macro_rules! const_clamp {
+    ($($name:ident: $ty:ty),+) => {
+        $(pub(crate) const fn $name(value: $ty, min: $ty, max: $ty) -> $ty {
+            if value < min {
+                min
+            } else if value > max {
+                max
+            } else {
+                value
+            }
+        })+
+    };
+}
+const_clamp!(clamp_u8: u8, clamp_u16: u16, clamp_i16: i16);
+
+// The sections of a complete configuration, passed to `$callback` so that [Profile] and
+// [DeviceConfig] are built from the same list. Each entry is the getter / [Profile] field, the
+// setter, the [Config] field and its type, the builder method and a description
+macro_rules! config_sections {
+    ($callback:ident) => {
+        $callback! {
+            accel, with_accel, acc_config: AccConfig, config_accel,
+                "Accelerometer settings";
+            interrupts, with_interrupts, int_config: IntConfig, config_interrupts,
+                "Interrupt settings";
+            int_pins, with_int_pins, int_pin_config: IntPinConfig, config_int_pins,
+                "Interrupt pin settings";
+            fifo, with_fifo, fifo_config: FifoConfig, config_fifo,
+                "FIFO settings";
+            auto_lp, with_auto_lp, auto_lp_config: AutoLpConfig, config_auto_lp,
+                "Auto low power settings";
+            autowkup, with_autowkup, auto_wkup_config: AutoWakeupConfig, config_autowkup,
+                "Auto wake-up settings";
+            wkup_int, with_wkup_int, wkup_int_config: WakeupIntConfig, config_wkup_int,
+                "Wake-up interrupt settings";
+            orientchg_int, with_orientchg_int, orientch_config: OrientChgConfig,
+                config_orientchg_int, "Orientation change interrupt settings";
+            gen1_int, with_gen1_int, gen1int_config: Gen1IntConfig, config_gen1_int,
+                "Generic interrupt 1 settings";
+            gen2_int, with_gen2_int, gen2int_config: Gen2IntConfig, config_gen2_int,
+                "Generic interrupt 2 settings";
+            actchg_int, with_actchg_int, actchg_config: ActChgConfig, config_actchg_int,
+                "Activity change interrupt settings";
+            tap, with_tap, tap_config: TapConfig, config_tap,
+                "Advanced tap settings";
+        }
+    };
+}
+pub(crate) use config_sections;
+
+/// Converts a value in physical units to the nearest whole number of register steps of
+/// `step_num / step_den` units, returning `error` if it is above `max` steps
+pub(crate) const fn to_steps(
+    value: u32,
+    step_num: u32,
+    step_den: u32,
+    max: u32,
+    error: ConfigError,
+) -> Result<u32, ConfigError> {
+    let (step_num, step_den) = (step_num as u64, step_den as u64);
+    let steps = (value as u64 * step_den + step_num / 2) / step_num;
+    if steps > max as u64 {
+        Err(error)
+    } else {
+        Ok(steps as u32)
+    }
+}
+
+/// Whether a reference acceleration doesn't fit the 12-bit signed register fields
+pub(crate) const fn out_of_range(ref_accel: i16) -> bool {
+    ref_accel < -2048 || ref_accel > 2047
+}
+
+/// Contents of the configuration register block as read in a single burst
+#[derive(Clone)]
+pub(crate) struct ConfigRegisters([u8; CONFIG_BLOCK_LEN]);
+
+impl ConfigRegisters {
+    pub fn new(bytes: [u8; CONFIG_BLOCK_LEN]) -> Self {
+        Self(bytes)
+    }
+    /// Decode the register `R` from the block
+    pub fn decode<R: ReadReg + FromByte>(&self) -> R {
+        R::from_byte(self.0[(R::ADDR - AccConfig0::ADDR) as usize])
+    }
+    /// Store the value of `register` in the block
+    pub fn encode<R: ConfigReg>(&mut self, register: R) {
+        self.0[(R::ADDR - AccConfig0::ADDR) as usize] = register.to_byte();
+    }
+    /// Returns the value held for the register at `addr`
+    pub fn get(&self, addr: u8) -> u8 {
+        self.0[(addr - AccConfig0::ADDR) as usize]
+    }
+    /// Sets the value held for the register at `addr`
+    pub fn set(&mut self, addr: u8, byte: u8) {
+        self.0[(addr - AccConfig0::ADDR) as usize] = byte;
+    }
+    /// Returns the values held for the registers `start..=end`
+    pub fn slice(&self, start: u8, end: u8) -> &[u8] {
+        &self.0[(start - AccConfig0::ADDR) as usize..=(end - AccConfig0::ADDR) as usize]
+    }
+    /// Finds the next run of registers in `from..=last` holding a different value than in
+    /// `other`, returning its first and last address
+    pub fn changed_run(&self, other: &Self, from: u8, last: u8) -> Option<(u8, u8)> {
+        let start = (from..=last).find(|&addr| self.get(addr) != other.get(addr))?;
+        let end = (start..=last)
+            .take_while(|&addr| self.get(addr) != other.get(addr))
+            .last()?;
+        Some((start, end))
+    }
+}
+
+/// Configuration register values waiting to be written in a single burst. Registers are
+/// only combined if each directly follows the previous one
+pub(crate) struct PendingWrites {
+    start: u8,
+    len: usize,
+    bytes: [u8; CONFIG_BLOCK_LEN],
+    // Bits in use for each register, reserved bits may read back differently
+    masks: [u8; CONFIG_BLOCK_LEN],
+    verify: bool,
+}
 
-use crate::Scale;
+impl PendingWrites {
+    pub const fn new() -> Self {
+        Self {
+            start: 0,
+            len: 0,
+            bytes: [0u8; CONFIG_BLOCK_LEN],
+            masks: [0u8; CONFIG_BLOCK_LEN],
+            verify: false,
+        }
+    }
+    /// Adds `register` to the burst. Returns `false` if the register can't be combined with
+    /// the pending ones and they need to be written first
+    pub fn push<R: ConfigReg + FromByte>(&mut self, register: R, verify: bool) -> bool {
+        let mask = R::from_byte(0xFF).to_byte();
+        self.push_raw(register.addr(), register.to_byte(), mask, verify)
+    }
+    /// Adds the value `byte` of the register at `addr`, with `mask` the bits in use
+    pub fn push_raw(&mut self, addr: u8, byte: u8, mask: u8, verify: bool) -> bool {
+        if self.len == 0 {
+            self.start = addr;
+        } else if addr as usize != self.start as usize + self.len || self.len == CONFIG_BLOCK_LEN {
+            return false;
+        }
+        self.bytes[self.len] = byte;
+        self.masks[self.len] = mask;
+        self.verify |= verify;
+        self.len += 1;
+        true
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Address of the first register in the burst
+    pub fn start(&self) -> u8 {
+        self.start
+    }
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+    pub fn masks(&self) -> &[u8] {
+        &self.masks[..self.len]
+    }
+    pub fn verify(&self) -> bool {
+        self.verify
+    }
+}
+
+// Interrupt enable registers, cleared while the settings of the interrupts are changed
+const INT_ENABLES: [u8; 3] = [IntConfig0::ADDR, IntConfig1::ADDR, WakeupIntConfig0::ADDR];
+
+/// Register writes taking a device from one configuration to another: the interrupts are
+/// disabled, the ODR is changed, the interrupt engines are configured and finally the
+/// interrupts are enabled again. Registers already holding their value are skipped
+pub(crate) struct StagedWrites {
+    writes: [(u8, u8); CONFIG_BLOCK_LEN + INT_ENABLES.len()],
+    len: usize,
+}
+
+impl StagedWrites {
+    pub fn new(current: &Config, target: &Config) -> Self {
+        let mut state = current.to_registers();
+        let target = target.to_registers();
+        let mut staged = Self {
+            writes: [(0, 0); CONFIG_BLOCK_LEN + INT_ENABLES.len()],
+            len: 0,
+        };
+        let settings_change = (AccConfig0::ADDR..=TapConfig1::ADDR)
+            .any(|addr| disabled(&state, addr) != disabled(&target, addr));
+        if settings_change {
+            for addr in INT_ENABLES {
+                let byte = disabled(&state, addr);
+                staged.push(&mut state, addr, byte);
+            }
+        }
+        // The ODR first, the interrupt engines depend on it
+        for addr in AccConfig0::ADDR..=TapConfig1::ADDR {
+            if !INT_ENABLES.contains(&addr) {
+                staged.push(&mut state, addr, target.get(addr));
+            } else if settings_change {
+                staged.push(&mut state, addr, disabled(&target, addr));
+            }
+        }
+        for addr in INT_ENABLES {
+            staged.push(&mut state, addr, target.get(addr));
+        }
+        staged
+    }
+    fn push(&mut self, state: &mut ConfigRegisters, addr: u8, byte: u8) {
+        if state.get(addr) != byte {
+            state.set(addr, byte);
+            self.writes[self.len] = (addr, byte);
+            self.len += 1;
+        }
+    }
+    /// Returns the address and value of each register to write, in order
+    pub fn iter(&self) -> impl Iterator<Item = (u8, u8)> + '_ {
+        self.writes[..self.len].iter().copied()
+    }
+}
+
+// Value of the register at `addr` with its interrupt enable bits cleared
+fn disabled(registers: &ConfigRegisters, addr: u8) -> u8 {
+    match addr {
+        IntConfig0::ADDR | IntConfig1::ADDR => 0x00,
+        WakeupIntConfig0::ADDR => WakeupIntConfig0::from_byte(registers.get(addr))
+            .with_x_axis(false)
+            .with_y_axis(false)
+            .with_z_axis(false)
+            .to_byte(),
+        _ => registers.get(addr),
+    }
+}
 
 #[derive(Default, Clone)]
 pub(crate) struct Config {
@@ -55,9 +315,140 @@ pub(crate) struct Config {
 }
 
 impl Config {
+    pub const fn new() -> Self {
+        Config {
+            acc_config: AccConfig::new(),
+            int_config: IntConfig::new(),
+            int_pin_config: IntPinConfig::new(),
+            fifo_config: FifoConfig::new(),
+            auto_lp_config: AutoLpConfig::new(),
+            auto_wkup_config: AutoWakeupConfig::new(),
+            wkup_int_config: WakeupIntConfig::new(),
+            orientch_config: OrientChgConfig::new(),
+            gen1int_config: Gen1IntConfig::new(),
+            gen2int_config: Gen2IntConfig::new(),
+            actchg_config: ActChgConfig::new(),
+            tap_config: TapConfig::new(),
+        }
+    }
+    /// Checks the rules between the settings of different sections, returning every rule
+    /// which is broken
+    pub fn validate(&self) -> ConfigErrors {
+        let mut errors = ConfigErrors::default();
+        let int_config0 = self.int_config.get_config0();
+        let int_config1 = self.int_config.get_config1();
+        let odr = self.acc_config.odr();
+        // If Gen Int 1 / 2 or Activity Change use AccFilt1 and are enabled, ODR must be 100Hz
+        let filt1_used_for_ints = (int_config1.actch_int()
+            && matches!(self.actchg_config.src(), DataSource::AccFilt1))
+            || (int_config0.gen1_int()
+                && matches!(self.gen1int_config.src(), DataSource::AccFilt1))
+            || (int_config0.gen2_int()
+                && matches!(self.gen2int_config.src(), DataSource::AccFilt1));
+        if filt1_used_for_ints && !matches!(odr, OutputDataRate::Hz100) {
+            errors.insert(ConfigError::Filt1InterruptInvalidODR);
+        }
+        // If either Tap Interrupt is enabled, filt1 ODR must be set to 200Hz
+        if (int_config1.d_tap_int() || int_config1.s_tap_int())
+            && !matches!(odr, OutputDataRate::Hz200)
+        {
+            errors.insert(ConfigError::TapIntEnabledInvalidODR);
+        }
+        errors
+    }
+    /// Checks the settings which are only rejected in strict mode: values which were adjusted
+    /// to a supported one, and automatic power mode changes relying on a disabled interrupt
+    ///
+    /// The setters of each section record the values they adjusted in its `adjusted()` set
+    pub fn strict_errors(&self) -> ConfigErrors {
+        let mut errors = ConfigErrors::default();
+        errors.union(self.fifo_config.adjusted());
+        errors.union(self.auto_lp_config.adjusted());
+        errors.union(self.auto_wkup_config.adjusted());
+        errors.union(self.wkup_int_config.adjusted());
+        errors.union(self.orientch_config.adjusted());
+        errors.union(self.gen1int_config.adjusted());
+        errors.union(self.gen2int_config.adjusted());
+        errors.union(self.actchg_config.adjusted());
+        let int_config0 = self.int_config.get_config0();
+        let auto_low_pow1 = self.auto_lp_config.get_config1();
+        if auto_low_pow1.contains(AutoLowPow1::GEN1_INT_TRIG) && !int_config0.gen1_int() {
+            errors.insert(ConfigError::AutoLpGen1IntDisabled);
+        }
+        if auto_low_pow1.contains(AutoLowPow1::AUT_LP_TIMEOUT1) && !int_config0.gen2_int() {
+            errors.insert(ConfigError::AutoLpGen2IntDisabled);
+        }
+        if self
+            .auto_wkup_config
+            .get_config1()
+            .contains(AutoWakeup1::WKUP_INT)
+            && !self.wkup_int_config.is_int_en()
+        {
+            errors.insert(ConfigError::AutoWakeupIntDisabled);
+        }
+        errors
+    }
+    /// Whether auto low power or auto wake-up can change the power mode without the driver
+    pub(crate) fn auto_power_enabled(&self) -> bool {
+        let auto_lp_triggers =
+            AutoLowPow1::AUTO_LP_TIMEOUT | AutoLowPow1::GEN1_INT_TRIG | AutoLowPow1::DRDY_TRIG;
+        let auto_wkup_triggers = AutoWakeup1::WKUP_TIMEOUT | AutoWakeup1::WKUP_INT;
+        self.auto_lp_config
+            .get_config1()
+            .intersects(auto_lp_triggers)
+            || self
+                .auto_wkup_config
+                .get_config1()
+                .intersects(auto_wkup_triggers)
+    }
+    /// Rebuild the configuration from the values currently held in device registers
+    pub fn from_registers(registers: &ConfigRegisters) -> Self {
+        Config {
+            acc_config: AccConfig::from_registers(registers),
+            int_config: IntConfig::from_registers(registers),
+            int_pin_config: IntPinConfig::from_registers(registers),
+            fifo_config: FifoConfig::from_registers(registers),
+            auto_lp_config: AutoLpConfig::from_registers(registers),
+            auto_wkup_config: AutoWakeupConfig::from_registers(registers),
+            wkup_int_config: WakeupIntConfig::from_registers(registers),
+            orientch_config: OrientChgConfig::from_registers(registers),
+            gen1int_config: Gen1IntConfig::from_registers(registers),
+            gen2int_config: Gen2IntConfig::from_registers(registers),
+            actchg_config: ActChgConfig::from_registers(registers),
+            tap_config: TapConfig::from_registers(registers),
+        }
+    }
+    /// Returns the register values making up this configuration
+    pub fn to_registers(&self) -> ConfigRegisters {
+        let mut registers = ConfigRegisters::new([0u8; CONFIG_BLOCK_LEN]);
+        self.acc_config.to_registers(&mut registers);
+        self.int_config.to_registers(&mut registers);
+        self.int_pin_config.to_registers(&mut registers);
+        self.fifo_config.to_registers(&mut registers);
+        self.auto_lp_config.to_registers(&mut registers);
+        self.auto_wkup_config.to_registers(&mut registers);
+        self.wkup_int_config.to_registers(&mut registers);
+        self.orientch_config.to_registers(&mut registers);
+        self.gen1int_config.to_registers(&mut registers);
+        self.gen2int_config.to_registers(&mut registers);
+        self.actchg_config.to_registers(&mut registers);
+        self.tap_config.to_registers(&mut registers);
+        registers
+    }
     pub fn scale(&self) -> Scale {
         self.acc_config.scale()
     }
+    /// Returns the settings FIFO data is currently measured with
+    pub fn fifo_settings(&self) -> FifoSettings {
+        let acc_config1 = self.acc_config.get_config1();
+        FifoSettings {
+            scale: acc_config1.scale(),
+            odr: acc_config1.odr(),
+            osr: acc_config1.osr(),
+            filt1_bw: self.acc_config.get_config0().filt1_bw(),
+            src: self.fifo_config.get_config0().fifo_src(),
+        }
+    }
     pub fn is_fifo_read_disabled(&self) -> bool {
         self.fifo_config.is_read_disabled()
     }
@@ -73,4 +464,8 @@ impl Config {
     pub fn int_config(&self) -> &IntConfig {
         &self.int_config
     }
+    #[cfg(feature = "embedded-hal-async")]
+    pub fn int_pin_config(&self) -> &IntPinConfig {
+        &self.int_pin_config
+    }
 }
@@ -1,9 +1,16 @@
+#[cfg(feature = "embedded-hal-async")]
+use crate::AsyncInterface;
 use crate::{
-    BMA400, ConfigError, DataSource, Filter1Bandwidth, OutputDataRate, OversampleRate, PowerMode,
-    Scale,
+    BMA400, BMA400Error, ConfigError, DataSource, DelayNs, Filter1Bandwidth, OutputDataRate,
+    OversampleRate, POWER_MODE_POLLS, POWER_MODE_TIMEOUT_US, PowerMode, Scale,
+    config::ConfigRegisters,
     registers::{AccConfig0, AccConfig1, AccConfig2},
+    typestate::Unchecked,
 };
 
+/// Accelerometer sampling, filtering and output settings
+///
+/// Set with the same methods as [AccConfigBuilder], without a device
 #[derive(Clone, Default)]
 pub struct AccConfig {
     acc_config0: AccConfig0,
@@ -12,20 +19,83 @@ pub struct AccConfig {
 }
 
 impl AccConfig {
-    pub fn odr(&self) -> OutputDataRate {
+    pub(crate) fn from_registers(registers: &ConfigRegisters) -> Self {
+        Self {
+            acc_config0: registers.decode(),
+            acc_config1: registers.decode(),
+            acc_config2: registers.decode(),
+        }
+    }
+    pub(crate) fn to_registers(&self, registers: &mut ConfigRegisters) {
+        registers.encode(self.acc_config0);
+        registers.encode(self.acc_config1);
+        registers.encode(self.acc_config2);
+    }
+
+    pub(crate) fn odr(&self) -> OutputDataRate {
         self.acc_config1.odr()
     }
-    pub fn scale(&self) -> Scale {
+    pub(crate) fn scale(&self) -> Scale {
         self.acc_config1.scale()
     }
-    pub fn get_config0(&self) -> AccConfig0 {
+    pub(crate) fn get_config0(&self) -> AccConfig0 {
         self.acc_config0
     }
-    pub fn get_config1(&self) -> AccConfig1 {
+    pub(crate) fn get_config1(&self) -> AccConfig1 {
         self.acc_config1
     }
 }
 
+impl AccConfig {
+    /// Returns the configuration the device has after a reset
+    pub const fn new() -> Self {
+        Self {
+            acc_config0: AccConfig0::RESET,
+            acc_config1: AccConfig1::RESET,
+            acc_config2: AccConfig2::RESET,
+        }
+    }
+    /// Set [PowerMode]
+    ///
+    /// Other settings can result in the power changing automatically,
+    /// for example auto wakeup and auto low-power mode.
+    /// To read the current power mode from the sensor use [`get_status()`](BMA400::get_status)
+    pub const fn with_power_mode(mut self, power_mode: PowerMode) -> Self {
+        self.acc_config0 = self.acc_config0.with_power_mode(power_mode);
+        self
+    }
+    /// Set the [OversampleRate] used in [`PowerMode::LowPower`] mode
+    pub const fn with_osr_lp(mut self, osr: OversampleRate) -> Self {
+        self.acc_config0 = self.acc_config0.with_osr_lp(osr);
+        self
+    }
+    /// Set the [Filter1Bandwidth] for [`DataSource::AccFilt1`]
+    pub const fn with_filt1_bw(mut self, bandwidth: Filter1Bandwidth) -> Self {
+        self.acc_config0 = self.acc_config0.with_filt1_bw(bandwidth);
+        self
+    }
+    /// Output Data Rate for [`DataSource::AccFilt1`]
+    pub const fn with_odr(mut self, odr: OutputDataRate) -> Self {
+        self.acc_config1 = self.acc_config1.with_odr(odr);
+        self
+    }
+    /// Set the [OversampleRate] used in [PowerMode::Normal] power mode
+    pub const fn with_osr(mut self, osr: OversampleRate) -> Self {
+        self.acc_config1 = self.acc_config1.with_osr(osr);
+        self
+    }
+    /// Set the [Scale] (resolution) of the data being output
+    pub const fn with_scale(mut self, scale: Scale) -> Self {
+        self.acc_config1 = self.acc_config1.with_scale(scale);
+        self
+    }
+    /// Set the [DataSource] feeding the single read registers
+    pub const fn with_reg_dta_src(mut self, src: DataSource) -> Self {
+        self.acc_config2 = self.acc_config2.with_dta_reg_src(src);
+        self
+    }
+}
+
 /// Configure how the accelerometer samples, filters and ouputs data
 ///
 /// - [PowerMode] using [`with_power_mode()`](AccConfigBuilder::with_power_mode)
@@ -34,19 +104,80 @@ impl AccConfig {
 /// - [Filter1Bandwidth] using [`with_filt1_bw()`](AccConfigBuilder::with_filt1_bw)
 /// - [OutputDataRate] using [`with_odr()`](AccConfigBuilder::with_odr)
 /// - [Scale] using [`with_scale()`](AccConfigBuilder::with_scale)
-pub struct AccConfigBuilder<'a, Interface> {
+pub struct AccConfigBuilder<'a, Interface, S = Unchecked> {
     config: AccConfig,
-    device: &'a mut BMA400<Interface>,
+    verify: bool,
+    device: &'a mut BMA400<Interface, S>,
 }
 
-#[cfg(not(feature = "embedded-hal-async"))]
-impl<'a, Interface, E> AccConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> AccConfigBuilder<'a, Interface, S>
 where
-    Interface: crate::blocking::WriteToRegister<Error = E>,
-    E: From<ConfigError>,
+    Interface: crate::blocking::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::blocking::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
-    pub fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    ///
+    /// A power mode change is waited for by reading the status until the device reports the
+    /// new mode, returning [`BMA400Error::Timeout`] after 100 reads. To wait for a set time
+    /// instead use [`write_and_wait()`](AccConfigBuilder::write_and_wait)
+    pub fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let power_mode = self.config.acc_config0.power_mode();
+        let mode_changed = self.device.config.acc_config.acc_config0.power_mode() != power_mode;
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes();
+        self.device.end_config_write(previous, result)?;
+        if mode_changed {
+            self.device
+                .poll_status_reads(POWER_MODE_POLLS, |status| status.power_mode() == power_mode)?;
+        }
+        Ok(())
+    }
+
+    /// Write this configuration to device registers, then wait up to 5ms for a power mode
+    /// change to take effect, returning [`BMA400Error::Timeout`] if it does not
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+    /// # use bma400::{BMA400, PowerMode};
+    /// # let ADDR = 0b10100;
+    /// # let expected = vec![
+    /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+    /// #        Transaction::write(ADDR, vec![0x19, 0x02]),
+    /// #        Transaction::write_read(ADDR, vec![0x03], vec![0x10]),
+    /// #        Transaction::write_read(ADDR, vec![0x03], vec![0x14]),
+    /// #    ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+    /// # let mut delay = NoopDelay::new();
+    /// bma400
+    ///     .config_accel()
+    ///     .with_power_mode(PowerMode::Normal)
+    ///     .write_and_wait(&mut delay)
+    ///     .unwrap();
+    /// // The device is now in normal mode
+    /// # i2c.done();
+    /// ```
+    pub fn write_and_wait<Timer: DelayNs>(
+        mut self,
+        timer: &mut Timer,
+    ) -> Result<(), BMA400Error<E>> {
+        let power_mode = self.config.acc_config0.power_mode();
+        let mode_changed = self.device.config.acc_config.acc_config0.power_mode() != power_mode;
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes();
+        self.device.end_config_write(previous, result)?;
+        if mode_changed {
+            self.device
+                .wait_for_power_mode(power_mode, timer, POWER_MODE_TIMEOUT_US)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
         let int_config0 = self.device.config.int_config.get_config0();
         let int_config1 = self.device.config.int_config.get_config1();
 
@@ -84,20 +215,17 @@ where
         }
         if self.device.config.acc_config.acc_config0.bits() != self.config.acc_config0.bits() {
             self.device
-                .interface
-                .write_register(self.config.acc_config0)?;
+                .write_register(self.config.acc_config0, self.verify)?;
             self.device.config.acc_config.acc_config0 = self.config.acc_config0;
         }
         if self.device.config.acc_config.acc_config1.bits() != self.config.acc_config1.bits() {
             self.device
-                .interface
-                .write_register(self.config.acc_config1)?;
+                .write_register(self.config.acc_config1, self.verify)?;
             self.device.config.acc_config.acc_config1 = self.config.acc_config1;
         }
         if self.device.config.acc_config.acc_config2.bits() != self.config.acc_config2.bits() {
             self.device
-                .interface
-                .write_register(self.config.acc_config2)?;
+                .write_register(self.config.acc_config2, self.verify)?;
             self.device.config.acc_config.acc_config2 = self.config.acc_config2;
         }
         Ok(())
@@ -105,13 +233,53 @@ where
 }
 
 #[cfg(feature = "embedded-hal-async")]
-impl<'a, Interface, E> AccConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> AccConfigBuilder<'a, AsyncInterface<Interface>, S>
 where
-    Interface: crate::asynch::WriteToRegister<Error = E>,
-    E: From<ConfigError>,
+    AsyncInterface<Interface>: crate::asynch::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::asynch::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
-    pub async fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    ///
+    /// A power mode change is waited for by reading the status until the device reports the
+    /// new mode, returning [`BMA400Error::Timeout`] after 100 reads. To wait for a set time
+    /// instead use [`write_and_wait()`](AccConfigBuilder::write_and_wait)
+    pub async fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let power_mode = self.config.acc_config0.power_mode();
+        let mode_changed = self.device.config.acc_config.acc_config0.power_mode() != power_mode;
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes().await;
+        self.device.end_config_write(previous, result).await?;
+        if mode_changed {
+            self.device
+                .poll_status_reads(POWER_MODE_POLLS, |status| status.power_mode() == power_mode)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Write this configuration to device registers, then wait up to 5ms for a power mode
+    /// change to take effect, returning [`BMA400Error::Timeout`] if it does not
+    pub async fn write_and_wait<Timer: DelayNs>(
+        mut self,
+        timer: &mut Timer,
+    ) -> Result<(), BMA400Error<E>> {
+        let power_mode = self.config.acc_config0.power_mode();
+        let mode_changed = self.device.config.acc_config.acc_config0.power_mode() != power_mode;
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes().await;
+        self.device.end_config_write(previous, result).await?;
+        if mode_changed {
+            self.device
+                .wait_for_power_mode(power_mode, timer, POWER_MODE_TIMEOUT_US)
+                .await?;
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
         let int_config0 = self.device.config.int_config.get_config0();
         let int_config1 = self.device.config.int_config.get_config1();
 
@@ -149,22 +317,19 @@ where
         }
         if self.device.config.acc_config.acc_config0.bits() != self.config.acc_config0.bits() {
             self.device
-                .interface
-                .write_register(self.config.acc_config0)
+                .write_register(self.config.acc_config0, self.verify)
                 .await?;
             self.device.config.acc_config.acc_config0 = self.config.acc_config0;
         }
         if self.device.config.acc_config.acc_config1.bits() != self.config.acc_config1.bits() {
             self.device
-                .interface
-                .write_register(self.config.acc_config1)
+                .write_register(self.config.acc_config1, self.verify)
                 .await?;
             self.device.config.acc_config.acc_config1 = self.config.acc_config1;
         }
         if self.device.config.acc_config.acc_config2.bits() != self.config.acc_config2.bits() {
             self.device
-                .interface
-                .write_register(self.config.acc_config2)
+                .write_register(self.config.acc_config2, self.verify)
                 .await?;
             self.device.config.acc_config.acc_config2 = self.config.acc_config2;
         }
@@ -172,13 +337,30 @@ where
     }
 }
 
-impl<'a, Interface> AccConfigBuilder<'a, Interface> {
-    pub(crate) fn new(device: &'a mut BMA400<Interface>) -> AccConfigBuilder<'a, Interface> {
+impl<'a, Interface, S> AccConfigBuilder<'a, Interface, S> {
+    pub(crate) fn new(device: &'a mut BMA400<Interface, S>) -> AccConfigBuilder<'a, Interface, S> {
         AccConfigBuilder {
             config: device.config.acc_config.clone(),
+            verify: device.verify_writes,
+            device,
+        }
+    }
+    /// Start from `config` instead of the current device configuration
+    pub(crate) fn from_config(device: &'a mut BMA400<Interface, S>, config: AccConfig) -> Self {
+        AccConfigBuilder {
+            config,
+            verify: device.verify_writes,
             device,
         }
     }
+    /// Read back each register written by [`write()`](AccConfigBuilder::write) and return
+    /// [`BMA400Error::VerifyFailed`] if the device did not accept the value
+    ///
+    /// Defaults to the device setting, see [`set_verify_writes()`](BMA400::set_verify_writes)
+    pub fn with_verify_writes(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
     // AccConfig0
     /// Set [PowerMode]
     ///
@@ -186,39 +368,39 @@ impl<'a, Interface> AccConfigBuilder<'a, Interface> {
     /// for example auto wakeup and auto low-power mode.
     /// To read the current power mode from the sensor use [`get_status()`](BMA400::get_status)
     pub fn with_power_mode(mut self, power_mode: PowerMode) -> Self {
-        self.config.acc_config0 = self.config.acc_config0.with_power_mode(power_mode);
+        self.config = self.config.with_power_mode(power_mode);
         self
     }
     /// Set the [OversampleRate] used in [`PowerMode::LowPower`] mode
     pub fn with_osr_lp(mut self, osr: OversampleRate) -> Self {
-        self.config.acc_config0 = self.config.acc_config0.with_osr_lp(osr);
+        self.config = self.config.with_osr_lp(osr);
         self
     }
     /// Set the [Filter1Bandwidth] for [`DataSource::AccFilt1`]
     pub fn with_filt1_bw(mut self, bandwidth: Filter1Bandwidth) -> Self {
-        self.config.acc_config0 = self.config.acc_config0.with_filt1_bw(bandwidth);
+        self.config = self.config.with_filt1_bw(bandwidth);
         self
     }
     // AccConfig1
     /// Output Data Rate for [`DataSource::AccFilt1`]
     pub fn with_odr(mut self, odr: OutputDataRate) -> Self {
-        self.config.acc_config1 = self.config.acc_config1.with_odr(odr);
+        self.config = self.config.with_odr(odr);
         self
     }
     /// Set the [OversampleRate] used in [PowerMode::Normal] power mode
     pub fn with_osr(mut self, osr: OversampleRate) -> Self {
-        self.config.acc_config1 = self.config.acc_config1.with_osr(osr);
+        self.config = self.config.with_osr(osr);
         self
     }
     /// Set the [Scale] (resolution) of the data being output
     pub fn with_scale(mut self, scale: Scale) -> Self {
-        self.config.acc_config1 = self.config.acc_config1.with_scale(scale);
+        self.config = self.config.with_scale(scale);
         self
     }
     // AccConfig2
     /// Set the [DataSource] feeding the single read registers
     pub fn with_reg_dta_src(mut self, src: DataSource) -> Self {
-        self.config.acc_config2 = self.config.acc_config2.with_dta_reg_src(src);
+        self.config = self.config.with_reg_dta_src(src);
         self
     }
 }
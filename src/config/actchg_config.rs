@@ -1,39 +1,132 @@
+#[cfg(feature = "embedded-hal-async")]
+use crate::AsyncInterface;
 use crate::{
-    ActChgObsPeriod, BMA400, ConfigError, DataSource, OutputDataRate,
+    ActChgObsPeriod, BMA400, BMA400Error, ConfigError, ConfigErrors, DataSource, OutputDataRate,
+    config::{ConfigRegisters, to_steps},
     registers::{ActChgConfig0, ActChgConfig1},
+    typestate::Unchecked,
 };
 
+/// Activity Change Interrupt settings
+///
+/// Set with the same methods as [ActChgConfigBuilder], without a device
 #[derive(Clone, Default)]
 pub struct ActChgConfig {
     actchg_config0: ActChgConfig0,
     actchg_config1: ActChgConfig1,
+    adjusted: ConfigErrors,
 }
 
 impl ActChgConfig {
-    pub fn src(&self) -> DataSource {
+    pub(crate) fn from_registers(registers: &ConfigRegisters) -> Self {
+        Self {
+            actchg_config0: registers.decode(),
+            actchg_config1: registers.decode(),
+            adjusted: ConfigErrors::new(),
+        }
+    }
+    pub(crate) fn to_registers(&self, registers: &mut ConfigRegisters) {
+        registers.encode(self.actchg_config0);
+        registers.encode(self.actchg_config1);
+    }
+    pub(crate) fn adjusted(&self) -> ConfigErrors {
+        self.adjusted
+    }
+
+    pub(crate) fn src(&self) -> DataSource {
         self.actchg_config1.src()
     }
 }
 
+impl ActChgConfig {
+    /// Returns the configuration the device has after a reset
+    pub const fn new() -> Self {
+        Self {
+            actchg_config0: ActChgConfig0::RESET,
+            actchg_config1: ActChgConfig1::RESET,
+            adjusted: ConfigErrors::new(),
+        }
+    }
+    /// Set the threshold used when evaluating the activity changed interrupt condition
+    pub const fn with_threshold(mut self, threshold: u8) -> Self {
+        self.actchg_config0 = self.actchg_config0.with_actch_thres(threshold);
+        self
+    }
+    /// Set the threshold in milli-g, rounded to the nearest 8 milli-g
+    ///
+    /// Returns [`ConfigError::ThresholdOutOfRange`] above 2043 milli-g
+    pub const fn with_threshold_mg(self, threshold: u16) -> Result<Self, ConfigError> {
+        match to_steps(
+            threshold as u32,
+            8,
+            1,
+            255,
+            ConfigError::ThresholdOutOfRange,
+        ) {
+            Ok(threshold) => Ok(self.with_threshold(threshold as u8)),
+            Err(e) => Err(e),
+        }
+    }
+    /// Select the axes to be used when evaluating the activity changed interrupt condition
+    pub const fn with_axes(mut self, x_en: bool, y_en: bool, z_en: bool) -> Self {
+        self.actchg_config1 = self
+            .actchg_config1
+            .with_x_axis(x_en)
+            .with_y_axis(y_en)
+            .with_z_axis(z_en);
+        self
+    }
+    /// Select the data source used for evaluating the activity changed interrupt condition
+    ///
+    /// Cannot use [DataSource::AccFilt2Lp]. If passed, this will default to AccFilt2, or in
+    /// strict mode [`ConfigError::ActChgInvalidSrc`] is returned when writing
+    pub const fn with_src(mut self, src: DataSource) -> Self {
+        let invalid = matches!(src, DataSource::AccFilt2Lp);
+        self.adjusted = self.adjusted.with(ConfigError::ActChgInvalidSrc, invalid);
+        self.actchg_config1 = match src {
+            DataSource::AccFilt2Lp => self.actchg_config1.with_dta_src(DataSource::AccFilt2),
+            _ => self.actchg_config1.with_dta_src(src),
+        };
+        self
+    }
+    /// Select the number of samples to observe (observation period) when evaluating the activity
+    /// type
+    pub const fn with_obs_period(mut self, obs_period: ActChgObsPeriod) -> Self {
+        self.actchg_config1 = self.actchg_config1.with_observation_period(obs_period);
+        self
+    }
+}
+
 /// Configure Activity Change Interrupt settings
 ///
 /// - Set the interrupt trigger threshold using [`with_threshold()`](ActChgConfigBuilder::with_threshold)
 /// - Enable / Disable the axes evaluated for the interrupt trigger condition using [`with_axes()`](ActChgConfigBuilder::with_axes)
 /// - [DataSource] used for evaluating the trigger condition using [`with_src()`](ActChgConfigBuilder::with_src)
 /// - [ActChgObsPeriod] (number of samples) using [`with_obs_period()`](ActChgConfigBuilder::with_obs_period)
-pub struct ActChgConfigBuilder<'a, Interface> {
+pub struct ActChgConfigBuilder<'a, Interface, S = Unchecked> {
     config: ActChgConfig,
-    device: &'a mut BMA400<Interface>,
+    verify: bool,
+    device: &'a mut BMA400<Interface, S>,
 }
 
-#[cfg(not(feature = "embedded-hal-async"))]
-impl<'a, Interface, E> ActChgConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> ActChgConfigBuilder<'a, Interface, S>
 where
-    Interface: crate::blocking::WriteToRegister<Error = E>,
-    E: From<ConfigError>,
+    Interface: crate::blocking::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::blocking::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
-    pub fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes();
+        self.device.end_config_write(previous, result)
+    }
+
+    pub(crate) fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
+        self.device
+            .check_strict(|config| config.actchg_config = self.config.clone())?;
         let has_config0_changes = self.device.config.actchg_config.actchg_config0.bits()
             != self.config.actchg_config0.bits();
         let has_config1_changes = self.device.config.actchg_config.actchg_config1.bits()
@@ -59,41 +152,49 @@ where
         // Temporarily disable the interrupt, if active
         if int_enabled {
             tmp_int_config1 = tmp_int_config1.with_actch_int(false);
-            self.device.interface.write_register(tmp_int_config1)?;
+            self.device.write_register(tmp_int_config1, self.verify)?;
         }
 
         // Write the changes
         if has_config0_changes {
             self.device
-                .interface
-                .write_register(self.config.actchg_config0)?;
+                .write_register(self.config.actchg_config0, self.verify)?;
             self.device.config.actchg_config.actchg_config0 = self.config.actchg_config0;
         }
         if has_config1_changes {
             self.device
-                .interface
-                .write_register(self.config.actchg_config1)?;
+                .write_register(self.config.actchg_config1, self.verify)?;
             self.device.config.actchg_config.actchg_config1 = self.config.actchg_config1;
         }
 
         // Re-enable the interrupt, if it was disabled
         if self.device.config.int_config.get_config1().bits() != tmp_int_config1.bits() {
             self.device
-                .interface
-                .write_register(self.device.config.int_config.get_config0())?;
+                .write_register(self.device.config.int_config.get_config0(), self.verify)?;
         }
         Ok(())
     }
 }
 
 #[cfg(feature = "embedded-hal-async")]
-impl<'a, Interface, E> ActChgConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> ActChgConfigBuilder<'a, AsyncInterface<Interface>, S>
 where
-    Interface: crate::asynch::WriteToRegister<Error = E>,
-    E: From<ConfigError>,
+    AsyncInterface<Interface>: crate::asynch::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::asynch::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
-    pub async fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub async fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes().await;
+        self.device.end_config_write(previous, result).await
+    }
+
+    pub(crate) async fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
+        self.device
+            .check_strict(|config| config.actchg_config = self.config.clone())?;
         let has_config0_changes = self.device.config.actchg_config.actchg_config0.bits()
             != self.config.actchg_config0.bits();
         let has_config1_changes = self.device.config.actchg_config.actchg_config1.bits()
@@ -120,23 +221,20 @@ where
         if int_enabled {
             tmp_int_config1 = tmp_int_config1.with_actch_int(false);
             self.device
-                .interface
-                .write_register(tmp_int_config1)
+                .write_register(tmp_int_config1, self.verify)
                 .await?;
         }
 
         // Write the changes
         if has_config0_changes {
             self.device
-                .interface
-                .write_register(self.config.actchg_config0)
+                .write_register(self.config.actchg_config0, self.verify)
                 .await?;
             self.device.config.actchg_config.actchg_config0 = self.config.actchg_config0;
         }
         if has_config1_changes {
             self.device
-                .interface
-                .write_register(self.config.actchg_config1)
+                .write_register(self.config.actchg_config1, self.verify)
                 .await?;
             self.device.config.actchg_config.actchg_config1 = self.config.actchg_config1;
         }
@@ -144,59 +242,71 @@ where
         // Re-enable the interrupt, if it was disabled
         if self.device.config.int_config.get_config1().bits() != tmp_int_config1.bits() {
             self.device
-                .interface
-                .write_register(self.device.config.int_config.get_config0())
+                .write_register(self.device.config.int_config.get_config0(), self.verify)
                 .await?;
         }
         Ok(())
     }
 }
 
-impl<'a, Interface> ActChgConfigBuilder<'a, Interface> {
-    pub(crate) fn new(device: &'a mut BMA400<Interface>) -> ActChgConfigBuilder<'a, Interface> {
+impl<'a, Interface, S> ActChgConfigBuilder<'a, Interface, S> {
+    pub(crate) fn new(
+        device: &'a mut BMA400<Interface, S>,
+    ) -> ActChgConfigBuilder<'a, Interface, S> {
         ActChgConfigBuilder {
             config: device.config.actchg_config.clone(),
+            verify: device.verify_writes,
+            device,
+        }
+    }
+    /// Start from `config` instead of the current device configuration
+    pub(crate) fn from_config(device: &'a mut BMA400<Interface, S>, config: ActChgConfig) -> Self {
+        ActChgConfigBuilder {
+            config,
+            verify: device.verify_writes,
             device,
         }
     }
+    /// Read back each register written by [`write()`](ActChgConfigBuilder::write) and return
+    /// [`BMA400Error::VerifyFailed`] if the device did not accept the value
+    ///
+    /// Defaults to the device setting, see [`set_verify_writes()`](BMA400::set_verify_writes)
+    pub fn with_verify_writes(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
     // ActChgConfig0
     /// Set the threshold used when evaluating the activity changed interrupt condition
     pub fn with_threshold(mut self, threshold: u8) -> Self {
-        self.config.actchg_config0 = self.config.actchg_config0.with_actch_thres(threshold);
+        self.config = self.config.with_threshold(threshold);
         self
     }
+    /// Set the threshold in milli-g, rounded to the nearest 8 milli-g
+    ///
+    /// Returns [`ConfigError::ThresholdOutOfRange`] above 2043 milli-g
+    pub fn with_threshold_mg(mut self, threshold: u16) -> Result<Self, ConfigError> {
+        self.config = self.config.with_threshold_mg(threshold)?;
+        Ok(self)
+    }
 
     // ActChgConfig1
     /// Select the axes to be used when evaluating the activity changed interrupt condition
     pub fn with_axes(mut self, x_en: bool, y_en: bool, z_en: bool) -> Self {
-        self.config.actchg_config1 = self
-            .config
-            .actchg_config1
-            .with_x_axis(x_en)
-            .with_y_axis(y_en)
-            .with_z_axis(z_en);
+        self.config = self.config.with_axes(x_en, y_en, z_en);
         self
     }
     /// Select the data source used for evaluating the activity changed interrupt condition
     ///
-    /// Cannot use [DataSource::AccFilt2Lp]. If passed, this will default to AccFilt2
+    /// Cannot use [DataSource::AccFilt2Lp]. If passed, this will default to AccFilt2, or in
+    /// strict mode [`ConfigError::ActChgInvalidSrc`] is returned when writing
     pub fn with_src(mut self, src: DataSource) -> Self {
-        self.config.actchg_config1 = match src {
-            DataSource::AccFilt2Lp => self
-                .config
-                .actchg_config1
-                .with_dta_src(DataSource::AccFilt2),
-            _ => self.config.actchg_config1.with_dta_src(src),
-        };
+        self.config = self.config.with_src(src);
         self
     }
     /// Select the number of samples to observe (observation period) when evaluating the activity
     /// type
     pub fn with_obs_period(mut self, obs_period: ActChgObsPeriod) -> Self {
-        self.config.actchg_config1 = self
-            .config
-            .actchg_config1
-            .with_observation_period(obs_period);
+        self.config = self.config.with_obs_period(obs_period);
         self
     }
 }
@@ -215,6 +325,18 @@ mod tests {
         assert_eq!(builder.config.actchg_config0.bits(), 0x00);
     }
     #[test]
+    fn test_threshold_mg() {
+        let mut device = get_test_device();
+        let builder = device.config_actchg_int().with_threshold_mg(100).unwrap();
+        assert_eq!(builder.config.actchg_config0.bits(), 13);
+        let builder = builder.with_threshold_mg(2043).unwrap();
+        assert_eq!(builder.config.actchg_config0.bits(), 0xFF);
+        assert!(matches!(
+            builder.with_threshold_mg(2044),
+            Err(ConfigError::ThresholdOutOfRange)
+        ));
+    }
+    #[test]
     fn test_axes() {
         let mut device = get_test_device();
         let builder = device.config_actchg_int();
@@ -279,4 +401,28 @@ mod tests {
             ))
         ));
     }
+    #[test]
+    fn test_strict_src() {
+        let mut device = get_test_device();
+        // Outside strict mode the source is replaced with AccFilt2
+        let builder = device.config_actchg_int().with_src(DataSource::AccFilt2Lp);
+        assert_eq!(builder.config.actchg_config1.bits(), 0x10);
+        assert!(matches!(builder.write(), Ok(())));
+        device.set_strict_config(true);
+        let result = device
+            .config_actchg_int()
+            .with_src(DataSource::AccFilt2Lp)
+            .write();
+        assert!(matches!(
+            result,
+            Err(BMA400Error::ConfigBuildError(ConfigError::ActChgInvalidSrc))
+        ));
+        // A later valid source clears the error
+        let result = device
+            .config_actchg_int()
+            .with_src(DataSource::AccFilt2Lp)
+            .with_src(DataSource::AccFilt2)
+            .write();
+        assert!(matches!(result, Ok(())));
+    }
 }
@@ -1,12 +1,90 @@
+#[cfg(feature = "embedded-hal-async")]
+use crate::AsyncInterface;
 use crate::{
-    AutoLPTimeoutTrigger, BMA400,
+    AutoLPTimeoutTrigger, BMA400, BMA400Error, ConfigError, ConfigErrors,
+    config::{ConfigRegisters, clamp_u16, to_steps},
     registers::{AutoLowPow0, AutoLowPow1},
+    typestate::Unchecked,
 };
 
+/// Auto Low Power settings
+///
+/// Set with the same methods as [AutoLpConfigBuilder], without a device
 #[derive(Clone, Default)]
 pub struct AutoLpConfig {
     auto_low_pow0: AutoLowPow0,
     auto_low_pow1: AutoLowPow1,
+    adjusted: ConfigErrors,
+}
+
+impl AutoLpConfig {
+    pub(crate) fn from_registers(registers: &ConfigRegisters) -> Self {
+        Self {
+            auto_low_pow0: registers.decode(),
+            auto_low_pow1: registers.decode(),
+            adjusted: ConfigErrors::new(),
+        }
+    }
+    pub(crate) fn to_registers(&self, registers: &mut ConfigRegisters) {
+        registers.encode(self.auto_low_pow0);
+        registers.encode(self.auto_low_pow1);
+    }
+    pub(crate) fn adjusted(&self) -> ConfigErrors {
+        self.adjusted
+    }
+    pub(crate) fn get_config1(&self) -> AutoLowPow1 {
+        self.auto_low_pow1
+    }
+}
+
+impl AutoLpConfig {
+    /// Returns the configuration the device has after a reset
+    pub const fn new() -> Self {
+        Self {
+            auto_low_pow0: AutoLowPow0::RESET,
+            auto_low_pow1: AutoLowPow1::RESET,
+            adjusted: ConfigErrors::new(),
+        }
+    }
+    /// Set the timeout counter for auto low power mode. This value is 12-bits, and is incremented
+    /// every 2.5ms
+    ///
+    /// This value is clamped to \[0, 4095\], or in strict mode
+    /// [`ConfigError::AutoLpTimeoutOutOfRange`] is returned when writing
+    pub const fn with_timeout(mut self, count: u16) -> Self {
+        let adjusted = count > 4095;
+        self.adjusted = self
+            .adjusted
+            .with(ConfigError::AutoLpTimeoutOutOfRange, adjusted);
+        let timeout = clamp_u16(count, 0, 4095);
+        self.auto_low_pow0 = self.auto_low_pow0.with_auto_lp_timeout_msb(timeout);
+        self.auto_low_pow1 = self.auto_low_pow1.with_auto_lp_timeout_lsb(timeout);
+        self
+    }
+    /// Set the timeout in milliseconds, rounded to the nearest 2.5ms
+    ///
+    /// Returns [`ConfigError::DurationOutOfRange`] above 10238ms
+    pub const fn with_timeout_ms(self, timeout: u32) -> Result<Self, ConfigError> {
+        match to_steps(timeout, 5, 2, 4095, ConfigError::DurationOutOfRange) {
+            Ok(count) => Ok(self.with_timeout(count as u16)),
+            Err(e) => Err(e),
+        }
+    }
+    /// Set the auto low power trigger condition
+    pub const fn with_auto_lp_trigger(mut self, trigger: AutoLPTimeoutTrigger) -> Self {
+        self.auto_low_pow1 = self.auto_low_pow1.with_auto_lp_timeout_mode(trigger);
+        self
+    }
+    /// Set Generic Interrupt 1 as a trigger condition for auto low power
+    pub const fn with_gen1_int_trigger(mut self, enabled: bool) -> Self {
+        self.auto_low_pow1 = self.auto_low_pow1.with_gen1_int_trigger(enabled);
+        self
+    }
+    /// Set new data ready as a trigger condition for auto low power
+    pub const fn with_drdy_trigger(mut self, enabled: bool) -> Self {
+        self.auto_low_pow1 = self.auto_low_pow1.with_drdy_trigger(enabled);
+        self
+    }
 }
 
 /// Configure Auto Low Power settings
@@ -15,32 +93,42 @@ pub struct AutoLpConfig {
 /// - [AutoLPTimeoutTrigger] (trigger and timer reset condition) using [`with_auto_lp_trigger()`](AutoLpConfigBuilder::with_auto_lp_trigger)
 /// - Set Generic Interrupt 1 as a trigger condition for auto low power using [`with_gen1_int_trigger()`](AutoLpConfigBuilder::with_gen1_int_trigger)
 /// - Set Data Ready as a trigger condition for auto low power using [`with_drdy_trigger()`](AutoLpConfigBuilder::with_drdy_trigger)
-pub struct AutoLpConfigBuilder<'a, Interface> {
+pub struct AutoLpConfigBuilder<'a, Interface, S = Unchecked> {
     config: AutoLpConfig,
-    device: &'a mut BMA400<Interface>,
+    verify: bool,
+    device: &'a mut BMA400<Interface, S>,
 }
 
-#[cfg(not(feature = "embedded-hal-async"))]
-impl<'a, Interface, E> AutoLpConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> AutoLpConfigBuilder<'a, Interface, S>
 where
-    Interface: crate::blocking::WriteToRegister<Error = E>,
+    Interface: crate::blocking::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::blocking::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write the configuration to device registers
-    pub fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes();
+        self.device.end_config_write(previous, result)
+    }
+
+    pub(crate) fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
+        self.device
+            .check_strict(|config| config.auto_lp_config = self.config.clone())?;
         if self.device.config.auto_lp_config.auto_low_pow0.bits()
             != self.config.auto_low_pow0.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.auto_low_pow0)?;
+                .write_register(self.config.auto_low_pow0, self.verify)?;
             self.device.config.auto_lp_config.auto_low_pow0 = self.config.auto_low_pow0;
         }
         if self.device.config.auto_lp_config.auto_low_pow1.bits()
             != self.config.auto_low_pow1.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.auto_low_pow1)?;
+                .write_register(self.config.auto_low_pow1, self.verify)?;
             self.device.config.auto_lp_config.auto_low_pow1 = self.config.auto_low_pow1;
         }
         Ok(())
@@ -48,18 +136,29 @@ where
 }
 
 #[cfg(feature = "embedded-hal-async")]
-impl<'a, Interface, E> AutoLpConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> AutoLpConfigBuilder<'a, AsyncInterface<Interface>, S>
 where
-    Interface: crate::asynch::WriteToRegister<Error = E>,
+    AsyncInterface<Interface>: crate::asynch::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::asynch::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write the configuration to device registers
-    pub async fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub async fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes().await;
+        self.device.end_config_write(previous, result).await
+    }
+
+    pub(crate) async fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
+        self.device
+            .check_strict(|config| config.auto_lp_config = self.config.clone())?;
         if self.device.config.auto_lp_config.auto_low_pow0.bits()
             != self.config.auto_low_pow0.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.auto_low_pow0)
+                .write_register(self.config.auto_low_pow0, self.verify)
                 .await?;
             self.device.config.auto_lp_config.auto_low_pow0 = self.config.auto_low_pow0;
         }
@@ -67,8 +166,7 @@ where
             != self.config.auto_low_pow1.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.auto_low_pow1)
+                .write_register(self.config.auto_low_pow1, self.verify)
                 .await?;
             self.device.config.auto_lp_config.auto_low_pow1 = self.config.auto_low_pow1;
         }
@@ -76,40 +174,65 @@ where
     }
 }
 
-impl<'a, Interface> AutoLpConfigBuilder<'a, Interface> {
-    pub(crate) fn new(device: &'a mut BMA400<Interface>) -> AutoLpConfigBuilder<'a, Interface> {
+impl<'a, Interface, S> AutoLpConfigBuilder<'a, Interface, S> {
+    pub(crate) fn new(
+        device: &'a mut BMA400<Interface, S>,
+    ) -> AutoLpConfigBuilder<'a, Interface, S> {
         AutoLpConfigBuilder {
             config: device.config.auto_lp_config.clone(),
+            verify: device.verify_writes,
             device,
         }
     }
+    /// Start from `config` instead of the current device configuration
+    pub(crate) fn from_config(device: &'a mut BMA400<Interface, S>, config: AutoLpConfig) -> Self {
+        AutoLpConfigBuilder {
+            config,
+            verify: device.verify_writes,
+            device,
+        }
+    }
+    /// Read back each register written by [`write()`](AutoLpConfigBuilder::write) and return
+    /// [`BMA400Error::VerifyFailed`] if the device did not accept the value
+    ///
+    /// Defaults to the device setting, see [`set_verify_writes()`](BMA400::set_verify_writes)
+    pub fn with_verify_writes(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
     // AutoLowPow0 + AutoLowPow1
 
     /// Set the timeout counter for auto low power mode. This value is 12-bits, and is incremented
     /// every 2.5ms
     ///
-    /// This value is clamped to \[0, 4095\]
+    /// This value is clamped to \[0, 4095\], or in strict mode
+    /// [`ConfigError::AutoLpTimeoutOutOfRange`] is returned when writing
     pub fn with_timeout(mut self, count: u16) -> Self {
-        let timeout = count.clamp(0, 4095);
-        self.config.auto_low_pow0 = self.config.auto_low_pow0.with_auto_lp_timeout_msb(timeout);
-        self.config.auto_low_pow1 = self.config.auto_low_pow1.with_auto_lp_timeout_lsb(timeout);
+        self.config = self.config.with_timeout(count);
         self
     }
+    /// Set the timeout in milliseconds, rounded to the nearest 2.5ms
+    ///
+    /// Returns [`ConfigError::DurationOutOfRange`] above 10238ms
+    pub fn with_timeout_ms(mut self, timeout: u32) -> Result<Self, ConfigError> {
+        self.config = self.config.with_timeout_ms(timeout)?;
+        Ok(self)
+    }
     // AutoLowPow1
 
     /// Set the auto low power trigger condition
     pub fn with_auto_lp_trigger(mut self, trigger: AutoLPTimeoutTrigger) -> Self {
-        self.config.auto_low_pow1 = self.config.auto_low_pow1.with_auto_lp_timeout_mode(trigger);
+        self.config = self.config.with_auto_lp_trigger(trigger);
         self
     }
     /// Set Generic Interrupt 1 as a trigger condition for auto low power
     pub fn with_gen1_int_trigger(mut self, enabled: bool) -> Self {
-        self.config.auto_low_pow1 = self.config.auto_low_pow1.with_gen1_int_trigger(enabled);
+        self.config = self.config.with_gen1_int_trigger(enabled);
         self
     }
     /// Set new data ready as a trigger condition for auto low power
     pub fn with_drdy_trigger(mut self, enabled: bool) -> Self {
-        self.config.auto_low_pow1 = self.config.auto_low_pow1.with_drdy_trigger(enabled);
+        self.config = self.config.with_drdy_trigger(enabled);
         self
     }
 }
@@ -130,6 +253,21 @@ mod tests {
         assert_eq!(builder.config.auto_low_pow1.bits(), 0x00);
     }
     #[test]
+    fn test_timeout_ms() {
+        let mut device = get_test_device();
+        let builder = device.config_auto_lp().with_timeout_ms(1000).unwrap();
+        // 400 * 2.5ms
+        assert_eq!(builder.config.auto_low_pow0.bits(), 0x19);
+        assert_eq!(builder.config.auto_low_pow1.bits(), 0x00);
+        let builder = builder.with_timeout_ms(10_238).unwrap();
+        assert_eq!(builder.config.auto_low_pow0.bits(), 0xFF);
+        assert_eq!(builder.config.auto_low_pow1.bits(), 0xF0);
+        assert!(matches!(
+            builder.with_timeout_ms(10_239),
+            Err(ConfigError::DurationOutOfRange)
+        ));
+    }
+    #[test]
     fn test_auto_lp_trigger() {
         let mut device = get_test_device();
         let builder = device.config_auto_lp();
@@ -159,4 +297,74 @@ mod tests {
         let builder = builder.with_drdy_trigger(false);
         assert_eq!(builder.config.auto_low_pow1.bits(), 0x00);
     }
+    #[test]
+    fn test_strict() {
+        let mut device = get_test_device();
+        device.set_strict_config(true);
+        let result = device.config_auto_lp().with_timeout(4096).write();
+        assert!(matches!(
+            result,
+            Err(BMA400Error::ConfigBuildError(
+                ConfigError::AutoLpTimeoutOutOfRange
+            ))
+        ));
+        let result = device.config_auto_lp().with_gen1_int_trigger(true).write();
+        assert!(matches!(
+            result,
+            Err(BMA400Error::ConfigBuildError(
+                ConfigError::AutoLpGen1IntDisabled
+            ))
+        ));
+        let result = device
+            .config_auto_lp()
+            .with_auto_lp_trigger(AutoLPTimeoutTrigger::TimeoutEnabledGen2IntReset)
+            .write();
+        assert!(matches!(
+            result,
+            Err(BMA400Error::ConfigBuildError(
+                ConfigError::AutoLpGen2IntDisabled
+            ))
+        ));
+        // Enable the interrupt first
+        assert!(matches!(
+            device
+                .config_gen1_int()
+                .with_src(crate::DataSource::AccFilt2)
+                .write(),
+            Ok(())
+        ));
+        assert!(matches!(
+            device.config_interrupts().with_gen1_int(true).write(),
+            Ok(())
+        ));
+        let result = device.config_auto_lp().with_gen1_int_trigger(true).write();
+        assert!(matches!(result, Ok(())));
+        // The interrupt can't be disabled while it triggers auto low power
+        let result = device.config_interrupts().with_gen1_int(false).write();
+        assert!(matches!(
+            result,
+            Err(BMA400Error::ConfigBuildError(
+                ConfigError::AutoLpGen1IntDisabled
+            ))
+        ));
+    }
+    #[test]
+    fn test_strict_unrelated() {
+        let mut device = get_test_device();
+        // Left over from before strict mode was enabled
+        assert!(matches!(
+            device.config_auto_lp().with_gen1_int_trigger(true).write(),
+            Ok(())
+        ));
+        device.set_strict_config(true);
+        // Writes which don't introduce the conflict are not rejected
+        assert!(matches!(
+            device.config_fifo().with_watermark_thresh(512).write(),
+            Ok(())
+        ));
+        assert!(matches!(
+            device.config_auto_lp().with_timeout(100).write(),
+            Ok(())
+        ));
+    }
 }
@@ -1,51 +1,127 @@
+#[cfg(feature = "embedded-hal-async")]
+use crate::AsyncInterface;
 use crate::{
-    BMA400,
+    BMA400, BMA400Error, ConfigError, ConfigErrors,
+    config::{ConfigRegisters, clamp_u16, to_steps},
     registers::{AutoWakeup0, AutoWakeup1},
+    typestate::Unchecked,
 };
 
+/// Auto Wake-up settings
+///
+/// Set with the same methods as [AutoWakeupConfigBuilder], without a device
 #[derive(Clone, Default)]
 pub struct AutoWakeupConfig {
     auto_wakeup0: AutoWakeup0,
     auto_wakeup1: AutoWakeup1,
+    adjusted: ConfigErrors,
 }
 
 impl AutoWakeupConfig {
-    pub fn get_config1(&self) -> AutoWakeup1 {
+    pub(crate) fn from_registers(registers: &ConfigRegisters) -> Self {
+        Self {
+            auto_wakeup0: registers.decode(),
+            auto_wakeup1: registers.decode(),
+            adjusted: ConfigErrors::new(),
+        }
+    }
+    pub(crate) fn to_registers(&self, registers: &mut ConfigRegisters) {
+        registers.encode(self.auto_wakeup0);
+        registers.encode(self.auto_wakeup1);
+    }
+    pub(crate) fn adjusted(&self) -> ConfigErrors {
+        self.adjusted
+    }
+
+    pub(crate) fn get_config1(&self) -> AutoWakeup1 {
         self.auto_wakeup1
     }
 }
 
+impl AutoWakeupConfig {
+    /// Returns the configuration the device has after a reset
+    pub const fn new() -> Self {
+        Self {
+            auto_wakeup0: AutoWakeup0::RESET,
+            auto_wakeup1: AutoWakeup1::RESET,
+            adjusted: ConfigErrors::new(),
+        }
+    }
+    /// Set the timer counter threshold for periodic auto wake-up. The counter is 12-bits and is
+    /// incremented every 2.5ms, so this value is clamped to \[0, 4095\], or in strict mode
+    /// [`ConfigError::AutoWakeupPeriodOutOfRange`] is returned when writing
+    pub const fn with_wakeup_period(mut self, count: u16) -> Self {
+        let adjusted = count > 4095;
+        self.adjusted = self
+            .adjusted
+            .with(ConfigError::AutoWakeupPeriodOutOfRange, adjusted);
+        let timeout = clamp_u16(count, 0, 4095);
+        self.auto_wakeup0 = self.auto_wakeup0.with_wakeup_timeout_msb(timeout);
+        self.auto_wakeup1 = self.auto_wakeup1.with_wakeup_timeout_lsb(timeout);
+        self
+    }
+    /// Set the wake-up period in milliseconds, rounded to the nearest 2.5ms
+    ///
+    /// Returns [`ConfigError::DurationOutOfRange`] above 10238ms
+    pub const fn with_period_ms(self, period: u32) -> Result<Self, ConfigError> {
+        match to_steps(period, 5, 2, 4095, ConfigError::DurationOutOfRange) {
+            Ok(count) => Ok(self.with_wakeup_period(count as u16)),
+            Err(e) => Err(e),
+        }
+    }
+    /// Enable/Disable periodic auto wake-up
+    pub const fn with_periodic_wakeup(mut self, enabled: bool) -> Self {
+        self.auto_wakeup1 = self.auto_wakeup1.with_wakeup_timeout(enabled);
+        self
+    }
+    /// Enable/Disable activity wake-up interrupt
+    pub const fn with_activity_int(mut self, enabled: bool) -> Self {
+        self.auto_wakeup1 = self.auto_wakeup1.with_wakeup_int(enabled);
+        self
+    }
+}
+
 /// Configure Auto Wake-up settings
 ///
 /// - Set the length of time between each wake-up using [`with_wakeup_period()`](AutoWakeupConfigBuilder::with_wakeup_period)
 /// - Enable / Disable periodic wakeup using [`with_periodic_wakeup()`](AutoWakeupConfigBuilder::with_periodic_wakeup)
 /// - Enable / Disable wake-up interrupt using [`with_activity_int()`](AutoWakeupConfigBuilder::with_activity_int)
-pub struct AutoWakeupConfigBuilder<'a, Interface> {
+pub struct AutoWakeupConfigBuilder<'a, Interface, S = Unchecked> {
     config: AutoWakeupConfig,
-    device: &'a mut BMA400<Interface>,
+    verify: bool,
+    device: &'a mut BMA400<Interface, S>,
 }
 
-#[cfg(not(feature = "embedded-hal-async"))]
-impl<'a, Interface, E> AutoWakeupConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> AutoWakeupConfigBuilder<'a, Interface, S>
 where
-    Interface: crate::blocking::WriteToRegister<Error = E>,
+    Interface: crate::blocking::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::blocking::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
-    pub fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes();
+        self.device.end_config_write(previous, result)
+    }
+
+    pub(crate) fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
+        self.device
+            .check_strict(|config| config.auto_wkup_config = self.config.clone())?;
         if self.device.config.auto_wkup_config.auto_wakeup0.bits()
             != self.config.auto_wakeup0.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.auto_wakeup0)?;
+                .write_register(self.config.auto_wakeup0, self.verify)?;
             self.device.config.auto_wkup_config.auto_wakeup0 = self.config.auto_wakeup0;
         }
         if self.device.config.auto_wkup_config.auto_wakeup1.bits()
             != self.config.auto_wakeup1.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.auto_wakeup1)?;
+                .write_register(self.config.auto_wakeup1, self.verify)?;
             self.device.config.auto_wkup_config.auto_wakeup1 = self.config.auto_wakeup1;
         }
         Ok(())
@@ -53,18 +129,29 @@ where
 }
 
 #[cfg(feature = "embedded-hal-async")]
-impl<'a, Interface, E> AutoWakeupConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> AutoWakeupConfigBuilder<'a, AsyncInterface<Interface>, S>
 where
-    Interface: crate::asynch::WriteToRegister<Error = E>,
+    AsyncInterface<Interface>: crate::asynch::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::asynch::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
-    pub async fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub async fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes().await;
+        self.device.end_config_write(previous, result).await
+    }
+
+    pub(crate) async fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
+        self.device
+            .check_strict(|config| config.auto_wkup_config = self.config.clone())?;
         if self.device.config.auto_wkup_config.auto_wakeup0.bits()
             != self.config.auto_wakeup0.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.auto_wakeup0)
+                .write_register(self.config.auto_wakeup0, self.verify)
                 .await?;
             self.device.config.auto_wkup_config.auto_wakeup0 = self.config.auto_wakeup0;
         }
@@ -72,8 +159,7 @@ where
             != self.config.auto_wakeup1.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.auto_wakeup1)
+                .write_register(self.config.auto_wakeup1, self.verify)
                 .await?;
             self.device.config.auto_wkup_config.auto_wakeup1 = self.config.auto_wakeup1;
         }
@@ -81,30 +167,58 @@ where
     }
 }
 
-impl<'a, Interface> AutoWakeupConfigBuilder<'a, Interface> {
-    pub(crate) fn new(device: &'a mut BMA400<Interface>) -> AutoWakeupConfigBuilder<'a, Interface> {
+impl<'a, Interface, S> AutoWakeupConfigBuilder<'a, Interface, S> {
+    pub(crate) fn new(
+        device: &'a mut BMA400<Interface, S>,
+    ) -> AutoWakeupConfigBuilder<'a, Interface, S> {
         AutoWakeupConfigBuilder {
             config: device.config.auto_wkup_config.clone(),
+            verify: device.verify_writes,
+            device,
+        }
+    }
+    /// Start from `config` instead of the current device configuration
+    pub(crate) fn from_config(
+        device: &'a mut BMA400<Interface, S>,
+        config: AutoWakeupConfig,
+    ) -> Self {
+        AutoWakeupConfigBuilder {
+            config,
+            verify: device.verify_writes,
             device,
         }
     }
+    /// Read back each register written by [`write()`](AutoWakeupConfigBuilder::write) and return
+    /// [`BMA400Error::VerifyFailed`] if the device did not accept the value
+    ///
+    /// Defaults to the device setting, see [`set_verify_writes()`](BMA400::set_verify_writes)
+    pub fn with_verify_writes(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
 
     /// Set the timer counter threshold for periodic auto wake-up. The counter is 12-bits and is
-    /// incremented every 2.5ms, so this value is clamped to \[0, 4095\]
+    /// incremented every 2.5ms, so this value is clamped to \[0, 4095\], or in strict mode
+    /// [`ConfigError::AutoWakeupPeriodOutOfRange`] is returned when writing
     pub fn with_wakeup_period(mut self, count: u16) -> Self {
-        let timeout = count.clamp(0, 4095);
-        self.config.auto_wakeup0 = self.config.auto_wakeup0.with_wakeup_timeout_msb(timeout);
-        self.config.auto_wakeup1 = self.config.auto_wakeup1.with_wakeup_timeout_lsb(timeout);
+        self.config = self.config.with_wakeup_period(count);
         self
     }
+    /// Set the wake-up period in milliseconds, rounded to the nearest 2.5ms
+    ///
+    /// Returns [`ConfigError::DurationOutOfRange`] above 10238ms
+    pub fn with_period_ms(mut self, period: u32) -> Result<Self, ConfigError> {
+        self.config = self.config.with_period_ms(period)?;
+        Ok(self)
+    }
     /// Enable/Disable periodic auto wake-up
     pub fn with_periodic_wakeup(mut self, enabled: bool) -> Self {
-        self.config.auto_wakeup1 = self.config.auto_wakeup1.with_wakeup_timeout(enabled);
+        self.config = self.config.with_periodic_wakeup(enabled);
         self
     }
     /// Enable/Disable activity wake-up interrupt
     pub fn with_activity_int(mut self, enabled: bool) -> Self {
-        self.config.auto_wakeup1 = self.config.auto_wakeup1.with_wakeup_int(enabled);
+        self.config = self.config.with_activity_int(enabled);
         self
     }
 }
@@ -0,0 +1,111 @@
+use crate::{
+    ConfigErrors,
+    config::{
+        AccConfig, ActChgConfig, AutoLpConfig, AutoWakeupConfig, Config, FifoConfig, Gen1IntConfig,
+        Gen2IntConfig, IntConfig, IntPinConfig, OrientChgConfig, TapConfig, WakeupIntConfig,
+        config_sections,
+    },
+};
+
+/// The complete configuration of a device, written at once using
+/// [`apply_config()`](crate::BMA400::apply_config)
+///
+/// Unlike the builders, which each check their changes against the rest of the current
+/// configuration, the whole target state is checked before anything is written, so the
+/// sections can be set in any order. Start from the reset values using
+/// [`new()`](DeviceConfig::new) or from the current configuration using
+/// [`device_config()`](crate::BMA400::device_config)
+///
+/// # Examples
+/// ```
+/// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+/// use bma400::{
+///     BMA400, BMA400Error, ConfigError, DataSource, OutputDataRate,
+///     config::{AccConfig, DeviceConfig, Gen1IntConfig, IntConfig},
+/// };
+///
+/// const TAP_AND_MOTION: DeviceConfig = DeviceConfig::new()
+///     .with_accel(AccConfig::new().with_odr(OutputDataRate::Hz100))
+///     .with_gen1_int(Gen1IntConfig::new().with_src(DataSource::AccFilt1))
+///     .with_interrupts(IntConfig::new().with_gen1_int(true).with_s_tap_int(true));
+///
+/// // Taps need a 200Hz ODR, generic interrupts using AccFilt1 need 100Hz
+/// let errors = TAP_AND_MOTION.validate().unwrap_err();
+/// assert!(errors.contains(ConfigError::TapIntEnabledInvalidODR));
+/// # let ADDR = 0b10100;
+/// # let expected = vec![
+/// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+/// #    ];
+/// # let mut i2c = Mock::new(&expected);
+/// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+/// assert!(matches!(
+///     bma400.apply_config(&TAP_AND_MOTION),
+///     Err(BMA400Error::InvalidConfig(_))
+/// ));
+/// # i2c.done();
+/// ```
+#[derive(Clone, Default)]
+pub struct DeviceConfig {
+    config: Config,
+}
+
+impl DeviceConfig {
+    /// The configuration the device has after a reset
+    pub const fn new() -> Self {
+        Self {
+            config: Config::new(),
+        }
+    }
+    pub(crate) fn from_config(config: Config) -> Self {
+        Self { config }
+    }
+    pub(crate) fn config(&self) -> &Config {
+        &self.config
+    }
+    /// Checks the complete configuration, returning every rule it breaks
+    pub fn validate(&self) -> Result<(), ConfigErrors> {
+        let errors = self.config.validate();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+    /// Checks the complete configuration as it is checked in strict mode (see
+    /// [`set_strict_config()`](crate::BMA400::set_strict_config)), also returning the settings
+    /// which were adjusted to a supported value and the broken auto power mode triggers
+    pub fn validate_strict(&self) -> Result<(), ConfigErrors> {
+        let mut errors = self.config.validate();
+        errors.union(self.config.strict_errors());
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+macro_rules! sections {
+    ($($name:ident, $setter:ident, $field:ident: $ty:ident, $builder:ident, $desc:literal;)+) => {
+        impl DeviceConfig {
+            $(
+                #[doc = concat!(
+                    $desc, ", see [`", stringify!($builder), "()`](crate::BMA400::",
+                    stringify!($builder), ")"
+                )]
+                pub const fn $setter(mut self, config: $ty) -> Self {
+                    self.config.$field = config;
+                    self
+                }
+            )+
+            $(
+                #[doc = $desc]
+                pub fn $name(&self) -> &$ty {
+                    &self.config.$field
+                }
+            )+
+        }
+    };
+}
+
+config_sections!(sections);
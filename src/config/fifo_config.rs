@@ -1,25 +1,138 @@
+#[cfg(feature = "embedded-hal-async")]
+use crate::AsyncInterface;
 use crate::{
-    BMA400, DataSource,
+    BMA400, BMA400Error, ConfigError, ConfigErrors, DataSource,
+    config::{ConfigRegisters, clamp_u16},
     registers::{FifoConfig0, FifoConfig1, FifoConfig2, FifoPwrConfig},
+    typestate::Unchecked,
 };
 
+/// FIFO Buffer settings
+///
+/// Set with the same methods as [FifoConfigBuilder], without a device
 #[derive(Clone, Default)]
 pub struct FifoConfig {
     fifo_config0: FifoConfig0,
     fifo_config1: FifoConfig1,
     fifo_config2: FifoConfig2,
     fifo_pwr_config: FifoPwrConfig,
+    adjusted: ConfigErrors,
 }
 
 impl FifoConfig {
-    pub fn is_read_disabled(&self) -> bool {
+    pub(crate) fn from_registers(registers: &ConfigRegisters) -> Self {
+        Self {
+            fifo_config0: registers.decode(),
+            fifo_config1: registers.decode(),
+            fifo_config2: registers.decode(),
+            fifo_pwr_config: registers.decode(),
+            adjusted: ConfigErrors::new(),
+        }
+    }
+    pub(crate) fn to_registers(&self, registers: &mut ConfigRegisters) {
+        registers.encode(self.fifo_config0);
+        registers.encode(self.fifo_config1);
+        registers.encode(self.fifo_config2);
+        registers.encode(self.fifo_pwr_config);
+    }
+    pub(crate) fn adjusted(&self) -> ConfigErrors {
+        self.adjusted
+    }
+
+    pub(crate) fn is_read_disabled(&self) -> bool {
         self.fifo_pwr_config.fifo_pwr_disable()
     }
-    pub fn get_config0(&self) -> FifoConfig0 {
+    pub(crate) fn get_config0(&self) -> FifoConfig0 {
         self.fifo_config0
     }
 }
 
+impl FifoConfig {
+    /// Returns the configuration the device has after a reset
+    pub const fn new() -> Self {
+        Self {
+            fifo_config0: FifoConfig0::RESET,
+            fifo_config1: FifoConfig1::RESET,
+            fifo_config2: FifoConfig2::RESET,
+            fifo_pwr_config: FifoPwrConfig::RESET,
+            adjusted: ConfigErrors::new(),
+        }
+    }
+    /// Manually Disable power to the FIFO Read circuit. This can save 100nA but you must wait 50µs
+    /// after re-enabling before attempting to read
+    ///
+    /// See Datasheet p.30
+    pub const fn with_read_disabled(mut self, disabled: bool) -> Self {
+        self.fifo_pwr_config = self.fifo_pwr_config.with_fifo_pwr_disable(disabled);
+        self
+    }
+    /// Enable writing measurements to the FIFO Buffer for x, y, z axis
+    pub const fn with_axes(mut self, x_en: bool, y_en: bool, z_en: bool) -> Self {
+        self.fifo_config0 = self
+            .fifo_config0
+            .with_fifo_x(x_en)
+            .with_fifo_y(y_en)
+            .with_fifo_z(z_en);
+        self
+    }
+    /// Truncates the 4 least significant bits of the reading to store the measurement of each axis
+    /// in a single byte
+    pub const fn with_8bit_mode(mut self, enabled: bool) -> Self {
+        self.fifo_config0 = self.fifo_config0.with_fifo_8bit(enabled);
+        self
+    }
+    /// Configure FIFO Data Source
+    ///
+    /// Cannot use [DataSource::AccFilt2Lp]. If passed, this will default to AccFilt2, or in
+    /// strict mode [`ConfigError::FifoInvalidSrc`] is returned when writing
+    pub const fn with_src(mut self, src: DataSource) -> Self {
+        let invalid = matches!(src, DataSource::AccFilt2Lp);
+        self.adjusted = self.adjusted.with(ConfigError::FifoInvalidSrc, invalid);
+        self.fifo_config0 = match src {
+            DataSource::AccFilt2Lp => self.fifo_config0.with_fifo_src(DataSource::AccFilt2),
+            _ => self.fifo_config0.with_fifo_src(src),
+        };
+        self
+    }
+    /// Enable sending a clock reading if more frames are requested than the buffer contains (>
+    /// `get_fifo_len()`)
+    pub const fn with_send_time_on_empty(mut self, enabled: bool) -> Self {
+        self.fifo_config0 = self.fifo_config0.with_send_time_on_empty(enabled);
+        self
+    }
+    /// Define the overflow behavior
+    ///
+    /// Enabled = newest frames are dropped (not written)
+    ///
+    /// Disabled = oldest frames are overwritten first
+    pub const fn with_stop_on_full(mut self, enabled: bool) -> Self {
+        self.fifo_config0 = self.fifo_config0.with_stop_on_full(enabled);
+        self
+    }
+    /// Automatically flush FIFO Buffer when changing power mode
+    pub const fn with_auto_flush(mut self, enabled: bool) -> Self {
+        self.fifo_config0 = self.fifo_config0.with_flush_on_pwr_mode_change(enabled);
+        self
+    }
+    /// Set the fill threshold for the FIFO watermark interrupt
+    ///
+    /// Interupt will be active if FIFO length is > this value
+    ///
+    /// Clamped to \[0, 1024\], or in strict mode [`ConfigError::FifoWatermarkOutOfRange`] is
+    /// returned when writing. See also [`with_ffull_int`](crate::config::IntConfigBuilder::with_ffull_int)
+    pub const fn with_watermark_thresh(mut self, threshold: u16) -> Self {
+        let adjusted = threshold > 1024;
+        self.adjusted = self
+            .adjusted
+            .with(ConfigError::FifoWatermarkOutOfRange, adjusted);
+        let thresh = clamp_u16(threshold, 0, 1024);
+        let bytes = thresh.to_le_bytes();
+        self.fifo_config1 = self.fifo_config1.with_fifo_wtrmk_threshold(bytes[0]);
+        self.fifo_config2 = self.fifo_config2.with_fifo_wtrmk_threshold(bytes[1]);
+        self
+    }
+}
+
 /// Configure the 1024 byte FIFO Buffer Behavior
 ///
 /// - Enable / Disable writing data for axes using [`with_axes()`](FifoConfigBuilder::with_axes)
@@ -30,22 +143,33 @@ impl FifoConfig {
 /// - Enable / Disable automatic flush on power mode change using [`with_auto_flush()`](FifoConfigBuilder::with_auto_flush)
 /// - Set the fill threshold for the FIFO watermark interrupt using [`with_watermark_thresh()`](FifoConfigBuilder::with_watermark_thresh)
 /// - Manually Enable / Disable the FIFO read circuit using [`with_read_disabled()`](FifoConfigBuilder::with_read_disabled)
-pub struct FifoConfigBuilder<'a, Interface> {
+pub struct FifoConfigBuilder<'a, Interface, S = Unchecked> {
     config: FifoConfig,
-    device: &'a mut BMA400<Interface>,
+    verify: bool,
+    device: &'a mut BMA400<Interface, S>,
 }
 
-#[cfg(not(feature = "embedded-hal-async"))]
-impl<'a, Interface, E> FifoConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> FifoConfigBuilder<'a, Interface, S>
 where
-    Interface: crate::blocking::WriteToRegister<Error = E>,
+    Interface: crate::blocking::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::blocking::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
-    pub fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes();
+        self.device.end_config_write(previous, result)
+    }
+
+    pub(crate) fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
+        self.device
+            .check_strict(|config| config.fifo_config = self.config.clone())?;
         if self.device.config.fifo_config.fifo_config0.bits() != self.config.fifo_config0.bits() {
             self.device
-                .interface
-                .write_register(self.config.fifo_config0)?;
+                .write_register(self.config.fifo_config0, self.verify)?;
             self.device.config.fifo_config.fifo_config0 = self.config.fifo_config0;
         }
         let wm1_changes =
@@ -58,32 +182,28 @@ where
         // If enabled, temporarily disable the FIFO Watermark Interrupt to change the config
         if self.device.config.int_config.get_config0().fwm_int() && fifo_wm_changes {
             tmp_int_config = tmp_int_config.with_fwm_int(false);
-            self.device.interface.write_register(tmp_int_config)?;
+            self.device.write_register(tmp_int_config, self.verify)?;
         }
         if wm1_changes {
             self.device
-                .interface
-                .write_register(self.config.fifo_config1)?;
+                .write_register(self.config.fifo_config1, self.verify)?;
             self.device.config.fifo_config.fifo_config1 = self.config.fifo_config1;
         }
         if wm2_changes {
             self.device
-                .interface
-                .write_register(self.config.fifo_config2)?;
+                .write_register(self.config.fifo_config2, self.verify)?;
             self.device.config.fifo_config.fifo_config2 = self.config.fifo_config2;
         }
         // Re-enable the interrupt if it was changed
         if self.device.config.int_config.get_config0().bits() != tmp_int_config.bits() {
             self.device
-                .interface
-                .write_register(self.device.config.int_config.get_config0())?;
+                .write_register(self.device.config.int_config.get_config0(), self.verify)?;
         }
         if self.device.config.fifo_config.fifo_pwr_config.bits()
             != self.config.fifo_pwr_config.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.fifo_pwr_config)?;
+                .write_register(self.config.fifo_pwr_config, self.verify)?;
             self.device.config.fifo_config.fifo_pwr_config = self.config.fifo_pwr_config
         }
         Ok(())
@@ -91,16 +211,27 @@ where
 }
 
 #[cfg(feature = "embedded-hal-async")]
-impl<'a, Interface, E> FifoConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> FifoConfigBuilder<'a, AsyncInterface<Interface>, S>
 where
-    Interface: crate::asynch::WriteToRegister<Error = E>,
+    AsyncInterface<Interface>: crate::asynch::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::asynch::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
-    pub async fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub async fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes().await;
+        self.device.end_config_write(previous, result).await
+    }
+
+    pub(crate) async fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
+        self.device
+            .check_strict(|config| config.fifo_config = self.config.clone())?;
         if self.device.config.fifo_config.fifo_config0.bits() != self.config.fifo_config0.bits() {
             self.device
-                .interface
-                .write_register(self.config.fifo_config0)
+                .write_register(self.config.fifo_config0, self.verify)
                 .await?;
             self.device.config.fifo_config.fifo_config0 = self.config.fifo_config0;
         }
@@ -114,35 +245,33 @@ where
         // If enabled, temporarily disable the FIFO Watermark Interrupt to change the config
         if self.device.config.int_config.get_config0().fwm_int() && fifo_wm_changes {
             tmp_int_config = tmp_int_config.with_fwm_int(false);
-            self.device.interface.write_register(tmp_int_config).await?;
+            self.device
+                .write_register(tmp_int_config, self.verify)
+                .await?;
         }
         if wm1_changes {
             self.device
-                .interface
-                .write_register(self.config.fifo_config1)
+                .write_register(self.config.fifo_config1, self.verify)
                 .await?;
             self.device.config.fifo_config.fifo_config1 = self.config.fifo_config1;
         }
         if wm2_changes {
             self.device
-                .interface
-                .write_register(self.config.fifo_config2)
+                .write_register(self.config.fifo_config2, self.verify)
                 .await?;
             self.device.config.fifo_config.fifo_config2 = self.config.fifo_config2;
         }
         // Re-enable the interrupt if it was changed
         if self.device.config.int_config.get_config0().bits() != tmp_int_config.bits() {
             self.device
-                .interface
-                .write_register(self.device.config.int_config.get_config0())
+                .write_register(self.device.config.int_config.get_config0(), self.verify)
                 .await?;
         }
         if self.device.config.fifo_config.fifo_pwr_config.bits()
             != self.config.fifo_pwr_config.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.fifo_pwr_config)
+                .write_register(self.config.fifo_pwr_config, self.verify)
                 .await?;
             self.device.config.fifo_config.fifo_pwr_config = self.config.fifo_pwr_config
         }
@@ -150,13 +279,30 @@ where
     }
 }
 
-impl<'a, Interface> FifoConfigBuilder<'a, Interface> {
-    pub(crate) fn new(device: &'a mut BMA400<Interface>) -> FifoConfigBuilder<'a, Interface> {
+impl<'a, Interface, S> FifoConfigBuilder<'a, Interface, S> {
+    pub(crate) fn new(device: &'a mut BMA400<Interface, S>) -> FifoConfigBuilder<'a, Interface, S> {
         FifoConfigBuilder {
             config: device.config.fifo_config.clone(),
+            verify: device.verify_writes,
+            device,
+        }
+    }
+    /// Start from `config` instead of the current device configuration
+    pub(crate) fn from_config(device: &'a mut BMA400<Interface, S>, config: FifoConfig) -> Self {
+        FifoConfigBuilder {
+            config,
+            verify: device.verify_writes,
             device,
         }
     }
+    /// Read back each register written by [`write()`](FifoConfigBuilder::write) and return
+    /// [`BMA400Error::VerifyFailed`] if the device did not accept the value
+    ///
+    /// Defaults to the device setting, see [`set_verify_writes()`](BMA400::set_verify_writes)
+    pub fn with_verify_writes(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
     // FifoConfig0
 
     /// Manually Disable power to the FIFO Read circuit. This can save 100nA but you must wait 50µs
@@ -164,39 +310,32 @@ impl<'a, Interface> FifoConfigBuilder<'a, Interface> {
     ///
     /// See Datasheet p.30
     pub fn with_read_disabled(mut self, disabled: bool) -> Self {
-        self.config.fifo_pwr_config = self.config.fifo_pwr_config.with_fifo_pwr_disable(disabled);
+        self.config = self.config.with_read_disabled(disabled);
         self
     }
     /// Enable writing measurements to the FIFO Buffer for x, y, z axis
     pub fn with_axes(mut self, x_en: bool, y_en: bool, z_en: bool) -> Self {
-        self.config.fifo_config0 = self
-            .config
-            .fifo_config0
-            .with_fifo_x(x_en)
-            .with_fifo_y(y_en)
-            .with_fifo_z(z_en);
+        self.config = self.config.with_axes(x_en, y_en, z_en);
         self
     }
     /// Truncates the 4 least significant bits of the reading to store the measurement of each axis
     /// in a single byte
     pub fn with_8bit_mode(mut self, enabled: bool) -> Self {
-        self.config.fifo_config0 = self.config.fifo_config0.with_fifo_8bit(enabled);
+        self.config = self.config.with_8bit_mode(enabled);
         self
     }
     /// Configure FIFO Data Source
     ///
-    /// Cannot use [DataSource::AccFilt2Lp]. If passed, this will default to AccFilt2
+    /// Cannot use [DataSource::AccFilt2Lp]. If passed, this will default to AccFilt2, or in
+    /// strict mode [`ConfigError::FifoInvalidSrc`] is returned when writing
     pub fn with_src(mut self, src: DataSource) -> Self {
-        self.config.fifo_config0 = match src {
-            DataSource::AccFilt2Lp => self.config.fifo_config0.with_fifo_src(DataSource::AccFilt2),
-            _ => self.config.fifo_config0.with_fifo_src(src),
-        };
+        self.config = self.config.with_src(src);
         self
     }
     /// Enable sending a clock reading if more frames are requested than the buffer contains (>
     /// `get_fifo_len()`)
     pub fn with_send_time_on_empty(mut self, enabled: bool) -> Self {
-        self.config.fifo_config0 = self.config.fifo_config0.with_send_time_on_empty(enabled);
+        self.config = self.config.with_send_time_on_empty(enabled);
         self
     }
     /// Define the overflow behavior
@@ -205,15 +344,12 @@ impl<'a, Interface> FifoConfigBuilder<'a, Interface> {
     ///
     /// Disabled = oldest frames are overwritten first
     pub fn with_stop_on_full(mut self, enabled: bool) -> Self {
-        self.config.fifo_config0 = self.config.fifo_config0.with_stop_on_full(enabled);
+        self.config = self.config.with_stop_on_full(enabled);
         self
     }
     /// Automatically flush FIFO Buffer when changing power mode
     pub fn with_auto_flush(mut self, enabled: bool) -> Self {
-        self.config.fifo_config0 = self
-            .config
-            .fifo_config0
-            .with_flush_on_pwr_mode_change(enabled);
+        self.config = self.config.with_auto_flush(enabled);
         self
     }
 
@@ -223,12 +359,10 @@ impl<'a, Interface> FifoConfigBuilder<'a, Interface> {
     ///
     /// Interupt will be active if FIFO length is > this value
     ///
-    /// Clamped to \[0, 1024\] See also [`with_ffull_int`](crate::config::IntConfigBuilder::with_ffull_int)
+    /// Clamped to \[0, 1024\], or in strict mode [`ConfigError::FifoWatermarkOutOfRange`] is
+    /// returned when writing. See also [`with_ffull_int`](crate::config::IntConfigBuilder::with_ffull_int)
     pub fn with_watermark_thresh(mut self, threshold: u16) -> Self {
-        let thresh = threshold.clamp(0, 1024);
-        let bytes = thresh.to_le_bytes();
-        self.config.fifo_config1 = self.config.fifo_config1.with_fifo_wtrmk_threshold(bytes[0]);
-        self.config.fifo_config2 = self.config.fifo_config2.with_fifo_wtrmk_threshold(bytes[1]);
+        self.config = self.config.with_watermark_thresh(threshold);
         self
     }
 }
@@ -315,4 +449,26 @@ mod tests {
         assert_eq!(builder.config.fifo_config1.bits(), 0xFF);
         assert_eq!(builder.config.fifo_config2.bits(), 0x03);
     }
+    #[test]
+    fn test_strict() {
+        let mut device = get_test_device();
+        device.set_strict_config(true);
+        let result = device.config_fifo().with_watermark_thresh(1025).write();
+        assert!(matches!(
+            result,
+            Err(BMA400Error::ConfigBuildError(
+                ConfigError::FifoWatermarkOutOfRange
+            ))
+        ));
+        let result = device
+            .config_fifo()
+            .with_src(DataSource::AccFilt2Lp)
+            .write();
+        assert!(matches!(
+            result,
+            Err(BMA400Error::ConfigBuildError(ConfigError::FifoInvalidSrc))
+        ));
+        let result = device.config_fifo().with_watermark_thresh(1024).write();
+        assert!(matches!(result, Ok(())));
+    }
 }
@@ -1,7 +1,9 @@
+#[cfg(feature = "embedded-hal-async")]
+use crate::AsyncInterface;
 pub(crate) use crate::{
-    BMA400, ConfigError, DataSource, GenIntCriterionMode, GenIntLogicMode, GenIntRefMode,
-    Hysteresis, OutputDataRate,
-    config::Config,
+    BMA400, BMA400Error, ConfigError, ConfigErrors, DataSource, GenIntCriterionMode,
+    GenIntLogicMode, GenIntRefMode, Hysteresis, OutputDataRate, PowerMode,
+    config::{Config, ConfigRegisters, clamp_i16, out_of_range, to_steps},
     registers::{
         Gen1IntConfig0, Gen1IntConfig1, Gen1IntConfig2, Gen1IntConfig3, Gen1IntConfig4,
         Gen1IntConfig5, Gen1IntConfig6, Gen1IntConfig7, Gen1IntConfig8, Gen1IntConfig9,
@@ -9,8 +11,12 @@ pub(crate) use crate::{
         Gen2IntConfig4, Gen2IntConfig5, Gen2IntConfig6, Gen2IntConfig7, Gen2IntConfig8,
         Gen2IntConfig9, Gen2IntConfig31,
     },
+    typestate::Unchecked,
 };
 
+/// Generic Interrupt 1 settings
+///
+/// Set with the same methods as [GenIntConfigBuilder], without a device
 #[derive(Clone, Default)]
 pub struct Gen1IntConfig {
     config0: Gen1IntConfig0,
@@ -24,14 +30,51 @@ pub struct Gen1IntConfig {
     config7: Gen1IntConfig7,
     config8: Gen1IntConfig8,
     config9: Gen1IntConfig9,
+    adjusted: ConfigErrors,
 }
 
 impl Gen1IntConfig {
-    pub fn src(&self) -> DataSource {
+    pub(crate) fn from_registers(registers: &ConfigRegisters) -> Self {
+        Self {
+            config0: registers.decode(),
+            config1: registers.decode(),
+            config2: registers.decode(),
+            config3: registers.decode(),
+            config31: registers.decode(),
+            config4: registers.decode(),
+            config5: registers.decode(),
+            config6: registers.decode(),
+            config7: registers.decode(),
+            config8: registers.decode(),
+            config9: registers.decode(),
+            adjusted: ConfigErrors::new(),
+        }
+    }
+    pub(crate) fn to_registers(&self, registers: &mut ConfigRegisters) {
+        registers.encode(self.config0);
+        registers.encode(self.config1);
+        registers.encode(self.config2);
+        registers.encode(self.config3);
+        registers.encode(self.config31);
+        registers.encode(self.config4);
+        registers.encode(self.config5);
+        registers.encode(self.config6);
+        registers.encode(self.config7);
+        registers.encode(self.config8);
+        registers.encode(self.config9);
+    }
+    pub(crate) fn adjusted(&self) -> ConfigErrors {
+        self.adjusted
+    }
+
+    pub(crate) fn src(&self) -> DataSource {
         self.config0.src()
     }
 }
 
+/// Generic Interrupt 2 settings
+///
+/// Set with the same methods as [GenIntConfigBuilder], without a device
 #[derive(Clone, Default)]
 pub struct Gen2IntConfig {
     config0: Gen2IntConfig0,
@@ -45,14 +88,188 @@ pub struct Gen2IntConfig {
     config7: Gen2IntConfig7,
     config8: Gen2IntConfig8,
     config9: Gen2IntConfig9,
+    adjusted: ConfigErrors,
 }
 
 impl Gen2IntConfig {
-    pub fn src(&self) -> DataSource {
+    pub(crate) fn from_registers(registers: &ConfigRegisters) -> Self {
+        Self {
+            config0: registers.decode(),
+            config1: registers.decode(),
+            config2: registers.decode(),
+            config3: registers.decode(),
+            config31: registers.decode(),
+            config4: registers.decode(),
+            config5: registers.decode(),
+            config6: registers.decode(),
+            config7: registers.decode(),
+            config8: registers.decode(),
+            config9: registers.decode(),
+            adjusted: ConfigErrors::new(),
+        }
+    }
+    pub(crate) fn to_registers(&self, registers: &mut ConfigRegisters) {
+        registers.encode(self.config0);
+        registers.encode(self.config1);
+        registers.encode(self.config2);
+        registers.encode(self.config3);
+        registers.encode(self.config31);
+        registers.encode(self.config4);
+        registers.encode(self.config5);
+        registers.encode(self.config6);
+        registers.encode(self.config7);
+        registers.encode(self.config8);
+        registers.encode(self.config9);
+    }
+    pub(crate) fn adjusted(&self) -> ConfigErrors {
+        self.adjusted
+    }
+
+    pub(crate) fn src(&self) -> DataSource {
         self.config0.src()
     }
 }
 
+// Generic interrupts 1 and 2 have identical register layouts
+macro_rules! gen_int_config {
+    ($name:ident, $($field:ident: $register:ident),+ $(,)?) => {
+        impl $name {
+            /// Returns the configuration the device has after a reset
+            pub const fn new() -> Self {
+                Self {
+                    $($field: $register::RESET,)+
+                    adjusted: ConfigErrors::new(),
+                }
+            }
+            // Config0
+            /// Select the axes to be considered when evaluating the generic interrupt criterion
+            pub const fn with_axes(mut self, x: bool, y: bool, z: bool) -> Self {
+                self.config0 = self.config0.with_x_axis(x).with_y_axis(y).with_z_axis(z);
+                self
+            }
+            /// Set the data source to use when evaluating the generic interrupt criterion
+            ///
+            /// Cannot use [DataSource::AccFilt2Lp]. If passed, this will default to
+            /// [DataSource::AccFilt2], or in strict mode [`ConfigError::GenIntInvalidSrc`] is
+            /// returned when writing
+            pub const fn with_src(mut self, src: DataSource) -> Self {
+                let invalid = matches!(src, DataSource::AccFilt2Lp);
+                self.adjusted = self.adjusted.with(ConfigError::GenIntInvalidSrc, invalid);
+                let src = match src {
+                    DataSource::AccFilt2Lp => DataSource::AccFilt2,
+                    _ => src,
+                };
+                self.config0 = self.config0.with_src(src);
+                self
+            }
+            /// Set the reference acceleration update mode for the generic interrupt
+            pub const fn with_ref_mode(mut self, mode: GenIntRefMode) -> Self {
+                self.config0 = self.config0.with_refu_mode(mode);
+                self
+            }
+            /// Set the amplitude of the hysteresis adjustment to the interrupt criteria
+            pub const fn with_hysteresis(mut self, hysteresis: Hysteresis) -> Self {
+                self.config0 = self.config0.with_act_hysteresis(hysteresis);
+                self
+            }
+            // Config1
+            /// Set the interrupt trigger condition (on Activity or Inactivity)
+            pub const fn with_criterion_mode(mut self, mode: GenIntCriterionMode) -> Self {
+                self.config1 = self.config1.with_criterion_sel(mode);
+                self
+            }
+            /// Set the interrupt trigger behavior when multiple axes are selected
+            pub const fn with_logic_mode(mut self, mode: GenIntLogicMode) -> Self {
+                self.config1 = self.config1.with_comb_sel(mode);
+                self
+            }
+            // Config2
+            /// Set the threshold above or below reference acceleration at which the interrupt
+            /// criterion evaluates to true
+            ///
+            /// This is not adjusted by scale, and is compared against the 8 msb of the
+            /// acceleration (8 milli-g resolution)
+            pub const fn with_threshold(mut self, threshold: u8) -> Self {
+                self.config2 = self.config2.with_threshold(threshold);
+                self
+            }
+            /// Set the threshold in milli-g, rounded to the nearest 8 milli-g
+            ///
+            /// Returns [`ConfigError::ThresholdOutOfRange`] above 2043 milli-g
+            pub const fn with_threshold_mg(self, threshold: u16) -> Result<Self, ConfigError> {
+                match to_steps(threshold as u32, 8, 1, 255, ConfigError::ThresholdOutOfRange) {
+                    Ok(threshold) => Ok(self.with_threshold(threshold as u8)),
+                    Err(e) => Err(e),
+                }
+            }
+            // Config3 and Config31
+            /// Set the number of cycles that the interrupt criterion must evaluate to true before
+            /// the interrupt triggers
+            ///
+            /// Note that the actual time duration depends on the ODR of the [DataSource] used.
+            /// Setting it in milliseconds needs the device's power mode and ODR, so is only
+            /// available on [GenIntConfigBuilder]
+            pub const fn with_duration(mut self, duration: u16) -> Self {
+                self.config3 = self.config3.with_duration_msb(duration.to_le_bytes()[1]);
+                self.config31 = self.config31.with_duration_lsb(duration.to_le_bytes()[0]);
+                self
+            }
+            // Config4-9
+            /// Manually set the reference acceleration for the interrupt criterion. This is
+            /// automatically overwritten if [`GenIntRefMode::Manual`] is not set.
+            ///
+            /// 12-bit, clamped to \[-2048, 2047\] (or in strict mode
+            /// [`ConfigError::RefAccelOutOfRange`] is returned when writing) and scales with
+            /// [crate::Scale]
+            pub const fn with_ref_accel(mut self, ref_x: i16, ref_y: i16, ref_z: i16) -> Self {
+                let adjusted = out_of_range(ref_x) || out_of_range(ref_y) || out_of_range(ref_z);
+                self.adjusted = self.adjusted.with(ConfigError::RefAccelOutOfRange, adjusted);
+                let (ref_x, ref_y, ref_z) = (
+                    clamp_i16(ref_x, -2048, 2047),
+                    clamp_i16(ref_y, -2048, 2047),
+                    clamp_i16(ref_z, -2048, 2047),
+                );
+                self.config4 = self.config4.with_ref_x_lsb(ref_x.to_le_bytes()[0]);
+                self.config5 = self.config5.with_ref_x_msb(ref_x.to_le_bytes()[1]);
+                self.config6 = self.config6.with_ref_y_lsb(ref_y.to_le_bytes()[0]);
+                self.config7 = self.config7.with_ref_y_msb(ref_y.to_le_bytes()[1]);
+                self.config8 = self.config8.with_ref_z_lsb(ref_z.to_le_bytes()[0]);
+                self.config9 = self.config9.with_ref_z_msb(ref_z.to_le_bytes()[1]);
+                self
+            }
+        }
+    };
+}
+
+gen_int_config!(
+    Gen1IntConfig,
+    config0: Gen1IntConfig0,
+    config1: Gen1IntConfig1,
+    config2: Gen1IntConfig2,
+    config3: Gen1IntConfig3,
+    config31: Gen1IntConfig31,
+    config4: Gen1IntConfig4,
+    config5: Gen1IntConfig5,
+    config6: Gen1IntConfig6,
+    config7: Gen1IntConfig7,
+    config8: Gen1IntConfig8,
+    config9: Gen1IntConfig9,
+);
+gen_int_config!(
+    Gen2IntConfig,
+    config0: Gen2IntConfig0,
+    config1: Gen2IntConfig1,
+    config2: Gen2IntConfig2,
+    config3: Gen2IntConfig3,
+    config31: Gen2IntConfig31,
+    config4: Gen2IntConfig4,
+    config5: Gen2IntConfig5,
+    config6: Gen2IntConfig6,
+    config7: Gen2IntConfig7,
+    config8: Gen2IntConfig8,
+    config9: Gen2IntConfig9,
+);
+
 pub enum GenIntConfig {
     Gen1Int(Gen1IntConfig),
     Gen2Int(Gen2IntConfig),
@@ -78,19 +295,32 @@ impl GenIntConfig {
 /// - Set the interrupt trigger threshold using [`with_threshold()`](GenIntConfigBuilder::with_threshold)
 /// - Set the number of cycles that the interrupt condition must be true before the interrupt triggers using [`with_duration()`](GenIntConfigBuilder::with_duration)
 /// - Manually set the reference acceleration for the interrupt trigger condition using [`with_ref_accel()`](GenIntConfigBuilder::with_ref_accel)
-pub struct GenIntConfigBuilder<'a, Interface> {
+pub struct GenIntConfigBuilder<'a, Interface, S = Unchecked> {
     config: GenIntConfig,
-    device: &'a mut BMA400<Interface>,
+    verify: bool,
+    device: &'a mut BMA400<Interface, S>,
 }
 
-#[cfg(not(feature = "embedded-hal-async"))]
-impl<'a, Interface, E> GenIntConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> GenIntConfigBuilder<'a, Interface, S>
 where
-    Interface: crate::blocking::WriteToRegister<Error = E>,
-    E: From<ConfigError>,
+    Interface: crate::blocking::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::blocking::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
-    pub fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes();
+        self.device.end_config_write(previous, result)
+    }
+
+    pub(crate) fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
+        self.device.check_strict(|config| match &self.config {
+            GenIntConfig::Gen1Int(gen1) => config.gen1int_config = gen1.clone(),
+            GenIntConfig::Gen2Int(gen2) => config.gen2int_config = gen2.clone(),
+        })?;
         let has_config0_changes = self.has_config0_changes_from(&self.device.config);
         let has_config1_changes = self.has_config1_changes_from(&self.device.config);
         let has_config2_changes = self.has_config2_changes_from(&self.device.config);
@@ -139,24 +369,24 @@ where
             GenIntConfig::Gen1Int(_) => {
                 if int_enabled {
                     int_config0 = int_config0.with_gen1_int(false);
-                    self.device.interface.write_register(int_config0)?;
+                    self.device.write_register(int_config0, self.verify)?;
                 }
             }
             GenIntConfig::Gen2Int(_) => {
                 if int_enabled {
                     int_config0 = int_config0.with_gen2_int(false);
-                    self.device.interface.write_register(int_config0)?;
+                    self.device.write_register(int_config0, self.verify)?;
                 }
             }
         }
         if has_config0_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config0)?;
+                    self.device.write_register(config.config0, self.verify)?;
                     self.device.config.gen1int_config.config0 = config.config0;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config0)?;
+                    self.device.write_register(config.config0, self.verify)?;
                     self.device.config.gen2int_config.config0 = config.config0;
                 }
             }
@@ -164,11 +394,11 @@ where
         if has_config1_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config1)?;
+                    self.device.write_register(config.config1, self.verify)?;
                     self.device.config.gen1int_config.config1 = config.config1;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config1)?;
+                    self.device.write_register(config.config1, self.verify)?;
                     self.device.config.gen2int_config.config1 = config.config1;
                 }
             }
@@ -176,11 +406,11 @@ where
         if has_config2_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config2)?;
+                    self.device.write_register(config.config2, self.verify)?;
                     self.device.config.gen1int_config.config2 = config.config2;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config2)?;
+                    self.device.write_register(config.config2, self.verify)?;
                     self.device.config.gen2int_config.config2 = config.config2;
                 }
             }
@@ -188,11 +418,11 @@ where
         if has_config3_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config3)?;
+                    self.device.write_register(config.config3, self.verify)?;
                     self.device.config.gen1int_config.config3 = config.config3;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config3)?;
+                    self.device.write_register(config.config3, self.verify)?;
                     self.device.config.gen2int_config.config3 = config.config3;
                 }
             }
@@ -200,11 +430,11 @@ where
         if has_config31_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config31)?;
+                    self.device.write_register(config.config31, self.verify)?;
                     self.device.config.gen1int_config.config31 = config.config31;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config31)?;
+                    self.device.write_register(config.config31, self.verify)?;
                     self.device.config.gen2int_config.config31 = config.config31;
                 }
             }
@@ -212,11 +442,11 @@ where
         if has_config4_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config4)?;
+                    self.device.write_register(config.config4, self.verify)?;
                     self.device.config.gen1int_config.config4 = config.config4;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config4)?;
+                    self.device.write_register(config.config4, self.verify)?;
                     self.device.config.gen2int_config.config4 = config.config4;
                 }
             }
@@ -224,11 +454,11 @@ where
         if has_config5_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config5)?;
+                    self.device.write_register(config.config5, self.verify)?;
                     self.device.config.gen1int_config.config5 = config.config5;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config5)?;
+                    self.device.write_register(config.config5, self.verify)?;
                     self.device.config.gen2int_config.config5 = config.config5;
                 }
             }
@@ -236,11 +466,11 @@ where
         if has_config6_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config6)?;
+                    self.device.write_register(config.config6, self.verify)?;
                     self.device.config.gen1int_config.config6 = config.config6;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config6)?;
+                    self.device.write_register(config.config6, self.verify)?;
                     self.device.config.gen2int_config.config6 = config.config6;
                 }
             }
@@ -248,11 +478,11 @@ where
         if has_config7_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config7)?;
+                    self.device.write_register(config.config7, self.verify)?;
                     self.device.config.gen1int_config.config7 = config.config7;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config7)?;
+                    self.device.write_register(config.config7, self.verify)?;
                     self.device.config.gen2int_config.config7 = config.config7;
                 }
             }
@@ -260,11 +490,11 @@ where
         if has_config8_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config8)?;
+                    self.device.write_register(config.config8, self.verify)?;
                     self.device.config.gen1int_config.config8 = config.config8;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config8)?;
+                    self.device.write_register(config.config8, self.verify)?;
                     self.device.config.gen2int_config.config8 = config.config8;
                 }
             }
@@ -272,11 +502,11 @@ where
         if has_config9_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config9)?;
+                    self.device.write_register(config.config9, self.verify)?;
                     self.device.config.gen1int_config.config9 = config.config9;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config9)?;
+                    self.device.write_register(config.config9, self.verify)?;
                     self.device.config.gen2int_config.config9 = config.config9;
                 }
             }
@@ -284,21 +514,33 @@ where
         // Re-enable interrupt, if it was disabled
         if int_config0.bits() != self.device.config.int_config.get_config0().bits() {
             self.device
-                .interface
-                .write_register(self.device.config.int_config.get_config0())?;
+                .write_register(self.device.config.int_config.get_config0(), self.verify)?;
         }
         Ok(())
     }
 }
 
 #[cfg(feature = "embedded-hal-async")]
-impl<'a, Interface, E> GenIntConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> GenIntConfigBuilder<'a, AsyncInterface<Interface>, S>
 where
-    Interface: crate::asynch::WriteToRegister<Error = E>,
-    E: From<ConfigError>,
+    AsyncInterface<Interface>: crate::asynch::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::asynch::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
-    pub async fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub async fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes().await;
+        self.device.end_config_write(previous, result).await
+    }
+
+    pub(crate) async fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
+        self.device.check_strict(|config| match &self.config {
+            GenIntConfig::Gen1Int(gen1) => config.gen1int_config = gen1.clone(),
+            GenIntConfig::Gen2Int(gen2) => config.gen2int_config = gen2.clone(),
+        })?;
         let has_config0_changes = self.has_config0_changes_from(&self.device.config);
         let has_config1_changes = self.has_config1_changes_from(&self.device.config);
         let has_config2_changes = self.has_config2_changes_from(&self.device.config);
@@ -347,24 +589,28 @@ where
             GenIntConfig::Gen1Int(_) => {
                 if int_enabled {
                     int_config0 = int_config0.with_gen1_int(false);
-                    self.device.interface.write_register(int_config0).await?;
+                    self.device.write_register(int_config0, self.verify).await?;
                 }
             }
             GenIntConfig::Gen2Int(_) => {
                 if int_enabled {
                     int_config0 = int_config0.with_gen2_int(false);
-                    self.device.interface.write_register(int_config0).await?;
+                    self.device.write_register(int_config0, self.verify).await?;
                 }
             }
         }
         if has_config0_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config0).await?;
+                    self.device
+                        .write_register(config.config0, self.verify)
+                        .await?;
                     self.device.config.gen1int_config.config0 = config.config0;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config0).await?;
+                    self.device
+                        .write_register(config.config0, self.verify)
+                        .await?;
                     self.device.config.gen2int_config.config0 = config.config0;
                 }
             }
@@ -372,11 +618,15 @@ where
         if has_config1_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config1).await?;
+                    self.device
+                        .write_register(config.config1, self.verify)
+                        .await?;
                     self.device.config.gen1int_config.config1 = config.config1;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config1).await?;
+                    self.device
+                        .write_register(config.config1, self.verify)
+                        .await?;
                     self.device.config.gen2int_config.config1 = config.config1;
                 }
             }
@@ -384,11 +634,15 @@ where
         if has_config2_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config2).await?;
+                    self.device
+                        .write_register(config.config2, self.verify)
+                        .await?;
                     self.device.config.gen1int_config.config2 = config.config2;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config2).await?;
+                    self.device
+                        .write_register(config.config2, self.verify)
+                        .await?;
                     self.device.config.gen2int_config.config2 = config.config2;
                 }
             }
@@ -396,11 +650,15 @@ where
         if has_config3_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config3).await?;
+                    self.device
+                        .write_register(config.config3, self.verify)
+                        .await?;
                     self.device.config.gen1int_config.config3 = config.config3;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config3).await?;
+                    self.device
+                        .write_register(config.config3, self.verify)
+                        .await?;
                     self.device.config.gen2int_config.config3 = config.config3;
                 }
             }
@@ -409,15 +667,13 @@ where
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
                     self.device
-                        .interface
-                        .write_register(config.config31)
+                        .write_register(config.config31, self.verify)
                         .await?;
                     self.device.config.gen1int_config.config31 = config.config31;
                 }
                 GenIntConfig::Gen2Int(config) => {
                     self.device
-                        .interface
-                        .write_register(config.config31)
+                        .write_register(config.config31, self.verify)
                         .await?;
                     self.device.config.gen2int_config.config31 = config.config31;
                 }
@@ -426,11 +682,15 @@ where
         if has_config4_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config4).await?;
+                    self.device
+                        .write_register(config.config4, self.verify)
+                        .await?;
                     self.device.config.gen1int_config.config4 = config.config4;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config4).await?;
+                    self.device
+                        .write_register(config.config4, self.verify)
+                        .await?;
                     self.device.config.gen2int_config.config4 = config.config4;
                 }
             }
@@ -438,11 +698,15 @@ where
         if has_config5_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config5).await?;
+                    self.device
+                        .write_register(config.config5, self.verify)
+                        .await?;
                     self.device.config.gen1int_config.config5 = config.config5;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config5).await?;
+                    self.device
+                        .write_register(config.config5, self.verify)
+                        .await?;
                     self.device.config.gen2int_config.config5 = config.config5;
                 }
             }
@@ -450,11 +714,15 @@ where
         if has_config6_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config6).await?;
+                    self.device
+                        .write_register(config.config6, self.verify)
+                        .await?;
                     self.device.config.gen1int_config.config6 = config.config6;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config6).await?;
+                    self.device
+                        .write_register(config.config6, self.verify)
+                        .await?;
                     self.device.config.gen2int_config.config6 = config.config6;
                 }
             }
@@ -462,11 +730,15 @@ where
         if has_config7_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config7).await?;
+                    self.device
+                        .write_register(config.config7, self.verify)
+                        .await?;
                     self.device.config.gen1int_config.config7 = config.config7;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config7).await?;
+                    self.device
+                        .write_register(config.config7, self.verify)
+                        .await?;
                     self.device.config.gen2int_config.config7 = config.config7;
                 }
             }
@@ -474,11 +746,15 @@ where
         if has_config8_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config8).await?;
+                    self.device
+                        .write_register(config.config8, self.verify)
+                        .await?;
                     self.device.config.gen1int_config.config8 = config.config8;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config8).await?;
+                    self.device
+                        .write_register(config.config8, self.verify)
+                        .await?;
                     self.device.config.gen2int_config.config8 = config.config8;
                 }
             }
@@ -486,11 +762,15 @@ where
         if has_config9_changes {
             match &self.config {
                 GenIntConfig::Gen1Int(config) => {
-                    self.device.interface.write_register(config.config9).await?;
+                    self.device
+                        .write_register(config.config9, self.verify)
+                        .await?;
                     self.device.config.gen1int_config.config9 = config.config9;
                 }
                 GenIntConfig::Gen2Int(config) => {
-                    self.device.interface.write_register(config.config9).await?;
+                    self.device
+                        .write_register(config.config9, self.verify)
+                        .await?;
                     self.device.config.gen2int_config.config9 = config.config9;
                 }
             }
@@ -498,26 +778,63 @@ where
         // Re-enable interrupt, if it was disabled
         if int_config0.bits() != self.device.config.int_config.get_config0().bits() {
             self.device
-                .interface
-                .write_register(self.device.config.int_config.get_config0())
+                .write_register(self.device.config.int_config.get_config0(), self.verify)
                 .await?;
         }
         Ok(())
     }
 }
 
-impl<'a, Interface> GenIntConfigBuilder<'a, Interface> {
+impl<'a, Interface, S> GenIntConfigBuilder<'a, Interface, S> {
     pub(crate) fn new_gen1(
-        device: &'a mut BMA400<Interface>,
-    ) -> GenIntConfigBuilder<'a, Interface> {
+        device: &'a mut BMA400<Interface, S>,
+    ) -> GenIntConfigBuilder<'a, Interface, S> {
         let config = GenIntConfig::Gen1Int(device.config.gen1int_config.clone());
-        GenIntConfigBuilder { config, device }
+        GenIntConfigBuilder {
+            config,
+            verify: device.verify_writes,
+            device,
+        }
     }
     pub(crate) fn new_gen2(
-        device: &'a mut BMA400<Interface>,
-    ) -> GenIntConfigBuilder<'a, Interface> {
+        device: &'a mut BMA400<Interface, S>,
+    ) -> GenIntConfigBuilder<'a, Interface, S> {
         let config = GenIntConfig::Gen2Int(device.config.gen2int_config.clone());
-        GenIntConfigBuilder { config, device }
+        GenIntConfigBuilder {
+            config,
+            verify: device.verify_writes,
+            device,
+        }
+    }
+    /// Start from `config` instead of the current device configuration
+    pub(crate) fn from_gen1_config(
+        device: &'a mut BMA400<Interface, S>,
+        config: Gen1IntConfig,
+    ) -> Self {
+        GenIntConfigBuilder {
+            config: GenIntConfig::Gen1Int(config),
+            verify: device.verify_writes,
+            device,
+        }
+    }
+    /// Start from `config` instead of the current device configuration
+    pub(crate) fn from_gen2_config(
+        device: &'a mut BMA400<Interface, S>,
+        config: Gen2IntConfig,
+    ) -> Self {
+        GenIntConfigBuilder {
+            config: GenIntConfig::Gen2Int(config),
+            verify: device.verify_writes,
+            device,
+        }
+    }
+    /// Read back each register written by [`write()`](GenIntConfigBuilder::write) and return
+    /// [`BMA400Error::VerifyFailed`] if the device did not accept the value
+    ///
+    /// Defaults to the device setting, see [`set_verify_writes()`](BMA400::set_verify_writes)
+    pub fn with_verify_writes(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
     }
     // Config0
     /// Select the axes to be considered when evaluating the generic interrupt criterion
@@ -534,16 +851,14 @@ impl<'a, Interface> GenIntConfigBuilder<'a, Interface> {
     }
     /// Set the data source to use when evaluating the generic interrupt criterion
     ///
-    /// Cannot use [DataSource::AccFilt2Lp]. If passed, this will default to [DataSource::AccFilt2]
+    /// Cannot use [DataSource::AccFilt2Lp]. If passed, this will default to
+    /// [DataSource::AccFilt2], or in strict mode [`ConfigError::GenIntInvalidSrc`] is returned
+    /// when writing
     pub fn with_src(mut self, src: DataSource) -> Self {
-        let src = match src {
-            DataSource::AccFilt2Lp => DataSource::AccFilt2,
-            _ => src,
+        self.config = match self.config {
+            GenIntConfig::Gen1Int(config) => GenIntConfig::Gen1Int(config.with_src(src)),
+            GenIntConfig::Gen2Int(config) => GenIntConfig::Gen2Int(config.with_src(src)),
         };
-        match &mut self.config {
-            GenIntConfig::Gen1Int(config) => config.config0 = config.config0.with_src(src),
-            GenIntConfig::Gen2Int(config) => config.config0 = config.config0.with_src(src),
-        }
         self
     }
     /// Set the reference acceleration update mode for the generic interrupt
@@ -604,6 +919,20 @@ impl<'a, Interface> GenIntConfigBuilder<'a, Interface> {
         }
         self
     }
+    /// Set the threshold in milli-g, rounded to the nearest 8 milli-g
+    ///
+    /// Returns [`ConfigError::ThresholdOutOfRange`] above 2043 milli-g
+    pub fn with_threshold_mg(mut self, threshold: u16) -> Result<Self, ConfigError> {
+        self.config = match self.config {
+            GenIntConfig::Gen1Int(config) => {
+                GenIntConfig::Gen1Int(config.with_threshold_mg(threshold)?)
+            }
+            GenIntConfig::Gen2Int(config) => {
+                GenIntConfig::Gen2Int(config.with_threshold_mg(threshold)?)
+            }
+        };
+        Ok(self)
+    }
     // Config3 and Config31
     /// Set the number of cycles that the interrupt criterion must evaluate to true before the
     /// interrupt triggers
@@ -622,35 +951,51 @@ impl<'a, Interface> GenIntConfigBuilder<'a, Interface> {
         }
         self
     }
+    /// Set the duration in milliseconds, converted to samples at the ODR of the [DataSource]
+    /// set so far (the accelerometer ODR for [DataSource::AccFilt1], 25Hz in
+    /// [`PowerMode::LowPower`], otherwise 100Hz), so set the data source, power mode and ODR
+    /// before the duration
+    ///
+    /// Rounded to the nearest sample. Returns [`ConfigError::DurationOutOfRange`] above 65535
+    /// samples. Only available on the builder, as the sample rate depends on the device
+    pub fn with_duration_ms(self, duration: u32) -> Result<Self, ConfigError> {
+        let acc_config = &self.device.config.acc_config;
+        let odr = match self.config.src() {
+            // Low power mode samples at a fixed 25Hz
+            DataSource::AccFilt1
+                if matches!(acc_config.get_config0().power_mode(), PowerMode::LowPower) =>
+            {
+                OutputDataRate::Hz25
+            }
+            DataSource::AccFilt1 => acc_config.odr(),
+            _ => OutputDataRate::Hz100,
+        };
+        // Sample period in sensor time ticks of 39.0625µs = 5 / 128ms
+        let duration = to_steps(
+            duration,
+            odr.period_ticks() * 5,
+            128,
+            u16::MAX.into(),
+            ConfigError::DurationOutOfRange,
+        )?;
+        Ok(self.with_duration(duration as u16))
+    }
     // Config4-9
     /// Manually set the reference acceleration for the interrupt criterion. This is
     /// automatically overwritten if [`GenIntRefMode::Manual`] is not set.
     ///
-    /// 12-bit, clamped to \[-2048, 2047\] and scales with [crate::Scale]
+    /// 12-bit, clamped to \[-2048, 2047\] (or in strict mode
+    /// [`ConfigError::RefAccelOutOfRange`] is returned when writing) and scales with
+    /// [crate::Scale]
     pub fn with_ref_accel(mut self, ref_x: i16, ref_y: i16, ref_z: i16) -> Self {
-        let (ref_x, ref_y, ref_z) = (
-            ref_x.clamp(-2048, 2047),
-            ref_y.clamp(-2048, 2047),
-            ref_z.clamp(-2048, 2047),
-        );
-        match &mut self.config {
+        self.config = match self.config {
             GenIntConfig::Gen1Int(config) => {
-                config.config4 = config.config4.with_ref_x_lsb(ref_x.to_le_bytes()[0]);
-                config.config5 = config.config5.with_ref_x_msb(ref_x.to_le_bytes()[1]);
-                config.config6 = config.config6.with_ref_y_lsb(ref_y.to_le_bytes()[0]);
-                config.config7 = config.config7.with_ref_y_msb(ref_y.to_le_bytes()[1]);
-                config.config8 = config.config8.with_ref_z_lsb(ref_z.to_le_bytes()[0]);
-                config.config9 = config.config9.with_ref_z_msb(ref_z.to_le_bytes()[1]);
+                GenIntConfig::Gen1Int(config.with_ref_accel(ref_x, ref_y, ref_z))
             }
             GenIntConfig::Gen2Int(config) => {
-                config.config4 = config.config4.with_ref_x_lsb(ref_x.to_le_bytes()[0]);
-                config.config5 = config.config5.with_ref_x_msb(ref_x.to_le_bytes()[1]);
-                config.config6 = config.config6.with_ref_y_lsb(ref_y.to_le_bytes()[0]);
-                config.config7 = config.config7.with_ref_y_msb(ref_y.to_le_bytes()[1]);
-                config.config8 = config.config8.with_ref_z_lsb(ref_z.to_le_bytes()[0]);
-                config.config9 = config.config9.with_ref_z_msb(ref_z.to_le_bytes()[1]);
+                GenIntConfig::Gen2Int(config.with_ref_accel(ref_x, ref_y, ref_z))
             }
-        }
+        };
         self
     }
 
@@ -1025,6 +1370,87 @@ mod tests {
         }
     }
     #[test]
+    fn test_threshold_mg() {
+        let mut device = get_test_device();
+        let builder = device.config_gen1_int().with_threshold_mg(100).unwrap();
+        if let GenIntConfig::Gen1Int(config) = &builder.config {
+            assert_eq!(config.config2.bits(), 13);
+        }
+        let builder = builder.with_threshold_mg(2043).unwrap();
+        if let GenIntConfig::Gen1Int(config) = &builder.config {
+            assert_eq!(config.config2.bits(), 0xFF);
+        }
+        assert!(matches!(
+            builder.with_threshold_mg(2044),
+            Err(ConfigError::ThresholdOutOfRange)
+        ));
+    }
+    #[test]
+    fn test_duration_ms() {
+        let mut device = get_test_device();
+        device.config.acc_config = device.config.acc_config.with_odr(OutputDataRate::Hz200);
+        // AccFilt1 at the accelerometer ODR
+        let builder = device
+            .config_gen1_int()
+            .with_src(DataSource::AccFilt1)
+            .with_duration_ms(1000)
+            .unwrap();
+        let GenIntConfig::Gen1Int(config) = &builder.config else {
+            panic!("expected Gen1Int");
+        };
+        assert_eq!(config.config3.bits(), 0x00);
+        assert_eq!(config.config31.bits(), 200);
+        // AccFilt2 at 100Hz
+        let builder = builder
+            .with_src(DataSource::AccFilt2)
+            .with_duration_ms(1000)
+            .unwrap();
+        let GenIntConfig::Gen1Int(config) = &builder.config else {
+            panic!("expected Gen1Int");
+        };
+        assert_eq!(config.config3.bits(), 0x00);
+        assert_eq!(config.config31.bits(), 100);
+        let builder = builder.with_duration_ms(655_350).unwrap();
+        let GenIntConfig::Gen1Int(config) = &builder.config else {
+            panic!("expected Gen1Int");
+        };
+        assert_eq!(config.config3.bits(), 0xFF);
+        assert_eq!(config.config31.bits(), 0xFF);
+        assert!(matches!(
+            builder.with_duration_ms(655_360),
+            Err(ConfigError::DurationOutOfRange)
+        ));
+    }
+    #[test]
+    fn test_duration_ms_low_power() {
+        let mut device = get_test_device();
+        device.config.acc_config = device
+            .config
+            .acc_config
+            .with_power_mode(PowerMode::LowPower)
+            .with_odr(OutputDataRate::Hz200);
+        // AccFilt1 at 25Hz, whatever the ODR
+        let builder = device
+            .config_gen2_int()
+            .with_src(DataSource::AccFilt1)
+            .with_duration_ms(1000)
+            .unwrap();
+        let GenIntConfig::Gen2Int(config) = &builder.config else {
+            panic!("expected Gen2Int");
+        };
+        assert_eq!(config.config3.bits(), 0x00);
+        assert_eq!(config.config31.bits(), 25);
+        // AccFilt2 stays at 100Hz
+        let builder = builder
+            .with_src(DataSource::AccFilt2)
+            .with_duration_ms(1000)
+            .unwrap();
+        let GenIntConfig::Gen2Int(config) = &builder.config else {
+            panic!("expected Gen2Int");
+        };
+        assert_eq!(config.config31.bits(), 100);
+    }
+    #[test]
     fn test_int1_config_err() {
         let mut device = get_test_device();
         // Change the data source to AccFilt2
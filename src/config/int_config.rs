@@ -1,8 +1,15 @@
+#[cfg(feature = "embedded-hal-async")]
+use crate::AsyncInterface;
 use crate::{
-    BMA400, ConfigError, DataSource, OutputDataRate,
+    BMA400, BMA400Error, ConfigError, DataSource, OutputDataRate,
+    config::ConfigRegisters,
     registers::{IntConfig0, IntConfig1},
+    typestate::Unchecked,
 };
 
+/// Interrupt enable and latching settings
+///
+/// Set with the same methods as [IntConfigBuilder], without a device
 #[derive(Clone, Default)]
 pub struct IntConfig {
     int_config0: IntConfig0,
@@ -10,31 +17,120 @@ pub struct IntConfig {
 }
 
 impl IntConfig {
+    pub(crate) fn from_registers(registers: &ConfigRegisters) -> Self {
+        Self {
+            int_config0: registers.decode(),
+            int_config1: registers.decode(),
+        }
+    }
+    pub(crate) fn to_registers(&self, registers: &mut ConfigRegisters) {
+        registers.encode(self.int_config0);
+        registers.encode(self.int_config1);
+    }
+
     // API to quickly enable / disable interrupts for config changes
-    pub fn get_config0(&self) -> IntConfig0 {
+    pub(crate) fn get_config0(&self) -> IntConfig0 {
         self.int_config0
     }
-    pub fn get_config1(&self) -> IntConfig1 {
+    pub(crate) fn get_config1(&self) -> IntConfig1 {
         self.int_config1
     }
 }
 
+impl IntConfig {
+    /// Returns the configuration the device has after a reset
+    pub const fn new() -> Self {
+        Self {
+            int_config0: IntConfig0::RESET,
+            int_config1: IntConfig1::RESET,
+        }
+    }
+    /// Enable/Disable the Data Ready Interrupt
+    pub const fn with_dta_rdy_int(mut self, enabled: bool) -> Self {
+        self.int_config0 = self.int_config0.with_dta_rdy_int(enabled);
+        self
+    }
+    /// Enable/Disable the Fifo Watermark Interrupt
+    pub const fn with_fwm_int(mut self, enabled: bool) -> Self {
+        self.int_config0 = self.int_config0.with_fwm_int(enabled);
+        self
+    }
+    /// Enable/Disable the Fifo Full Interrupt
+    pub const fn with_ffull_int(mut self, enabled: bool) -> Self {
+        self.int_config0 = self.int_config0.with_ffull_int(enabled);
+        self
+    }
+    /// Enable/Disable Generic Interrupt 2
+    pub const fn with_gen2_int(mut self, enabled: bool) -> Self {
+        self.int_config0 = self.int_config0.with_gen2_int(enabled);
+        self
+    }
+    /// Enable/Disable Generic Interrupt 1
+    pub const fn with_gen1_int(mut self, enabled: bool) -> Self {
+        self.int_config0 = self.int_config0.with_gen1_int(enabled);
+        self
+    }
+    /// Enable/Disable the Orientation Change Interrupt
+    pub const fn with_orientch_int(mut self, enabled: bool) -> Self {
+        self.int_config0 = self.int_config0.with_orientch_int(enabled);
+        self
+    }
+    /// Enable/Disable latched interrupt mode
+    ///
+    /// When enabled, all interrupts persist until the corresponding IntStatus is read
+    pub const fn with_latch_int(mut self, enabled: bool) -> Self {
+        self.int_config1 = self.int_config1.with_latch_int(enabled);
+        self
+    }
+    /// Enable/Disable Activity Changed Interrupt
+    pub const fn with_actch_int(mut self, enabled: bool) -> Self {
+        self.int_config1 = self.int_config1.with_actch_int(enabled);
+        self
+    }
+    /// Enable/Disable Double Tap Interrupt
+    pub const fn with_d_tap_int(mut self, enabled: bool) -> Self {
+        self.int_config1 = self.int_config1.with_d_tap_int(enabled);
+        self
+    }
+    /// Enable/Disable Single Tap Interrupt
+    pub const fn with_s_tap_int(mut self, enabled: bool) -> Self {
+        self.int_config1 = self.int_config1.with_s_tap_int(enabled);
+        self
+    }
+    /// Enable/Disable Step Interrupt
+    pub const fn with_step_int(mut self, enabled: bool) -> Self {
+        self.int_config1 = self.int_config1.with_step_int(enabled);
+        self
+    }
+}
+
 /// Enable or disable interrupts[^except] and set interrupt latch mode
 ///
 /// [^except]: To enable the Auto-Wakeup Interrupt see [`config_autowkup()`](BMA400::config_autowkup)
-pub struct IntConfigBuilder<'a, Interface> {
+pub struct IntConfigBuilder<'a, Interface, S = Unchecked> {
     config: IntConfig,
-    device: &'a mut BMA400<Interface>,
+    verify: bool,
+    device: &'a mut BMA400<Interface, S>,
 }
 
-#[cfg(not(feature = "embedded-hal-async"))]
-impl<'a, Interface, E> IntConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> IntConfigBuilder<'a, Interface, S>
 where
-    Interface: crate::blocking::WriteToRegister<Error = E>,
-    E: From<ConfigError>,
+    Interface: crate::blocking::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::blocking::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
-    pub fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes();
+        self.device.end_config_write(previous, result)
+    }
+
+    pub(crate) fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
+        self.device
+            .check_strict(|config| config.int_config = self.config.clone())?;
         if (self.config.int_config1.d_tap_int() || self.config.int_config1.s_tap_int())
             && !matches!(self.device.config.acc_config.odr(), OutputDataRate::Hz200)
         {
@@ -73,14 +169,12 @@ where
 
         if self.device.config.int_config.int_config0.bits() != self.config.int_config0.bits() {
             self.device
-                .interface
-                .write_register(self.config.int_config0)?;
+                .write_register(self.config.int_config0, self.verify)?;
             self.device.config.int_config.int_config0 = self.config.int_config0;
         }
         if self.device.config.int_config.int_config1.bits() != self.config.int_config1.bits() {
             self.device
-                .interface
-                .write_register(self.config.int_config1)?;
+                .write_register(self.config.int_config1, self.verify)?;
             self.device.config.int_config.int_config1 = self.config.int_config1;
         }
         Ok(())
@@ -88,13 +182,24 @@ where
 }
 
 #[cfg(feature = "embedded-hal-async")]
-impl<'a, Interface, E> IntConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> IntConfigBuilder<'a, AsyncInterface<Interface>, S>
 where
-    Interface: crate::asynch::WriteToRegister<Error = E>,
-    E: From<ConfigError>,
+    AsyncInterface<Interface>: crate::asynch::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::asynch::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
-    pub async fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub async fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes().await;
+        self.device.end_config_write(previous, result).await
+    }
+
+    pub(crate) async fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
+        self.device
+            .check_strict(|config| config.int_config = self.config.clone())?;
         if (self.config.int_config1.d_tap_int() || self.config.int_config1.s_tap_int())
             && !matches!(self.device.config.acc_config.odr(), OutputDataRate::Hz200)
         {
@@ -133,15 +238,13 @@ where
 
         if self.device.config.int_config.int_config0.bits() != self.config.int_config0.bits() {
             self.device
-                .interface
-                .write_register(self.config.int_config0)
+                .write_register(self.config.int_config0, self.verify)
                 .await?;
             self.device.config.int_config.int_config0 = self.config.int_config0;
         }
         if self.device.config.int_config.int_config1.bits() != self.config.int_config1.bits() {
             self.device
-                .interface
-                .write_register(self.config.int_config1)
+                .write_register(self.config.int_config1, self.verify)
                 .await?;
             self.device.config.int_config.int_config1 = self.config.int_config1;
         }
@@ -149,42 +252,59 @@ where
     }
 }
 
-impl<'a, Interface> IntConfigBuilder<'a, Interface> {
-    pub(crate) fn new(device: &'a mut BMA400<Interface>) -> IntConfigBuilder<'a, Interface> {
+impl<'a, Interface, S> IntConfigBuilder<'a, Interface, S> {
+    pub(crate) fn new(device: &'a mut BMA400<Interface, S>) -> IntConfigBuilder<'a, Interface, S> {
         IntConfigBuilder {
             config: device.config.int_config.clone(),
+            verify: device.verify_writes,
             device,
         }
     }
+    /// Start from `config` instead of the current device configuration
+    pub(crate) fn from_config(device: &'a mut BMA400<Interface, S>, config: IntConfig) -> Self {
+        IntConfigBuilder {
+            config,
+            verify: device.verify_writes,
+            device,
+        }
+    }
+    /// Read back each register written by [`write()`](IntConfigBuilder::write) and return
+    /// [`BMA400Error::VerifyFailed`] if the device did not accept the value
+    ///
+    /// Defaults to the device setting, see [`set_verify_writes()`](BMA400::set_verify_writes)
+    pub fn with_verify_writes(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
     // IntConfig0
     /// Enable/Disable the Data Ready Interrupt
     pub fn with_dta_rdy_int(mut self, enabled: bool) -> Self {
-        self.config.int_config0 = self.config.int_config0.with_dta_rdy_int(enabled);
+        self.config = self.config.with_dta_rdy_int(enabled);
         self
     }
     /// Enable/Disable the Fifo Watermark Interrupt
     pub fn with_fwm_int(mut self, enabled: bool) -> Self {
-        self.config.int_config0 = self.config.int_config0.with_fwm_int(enabled);
+        self.config = self.config.with_fwm_int(enabled);
         self
     }
     /// Enable/Disable the Fifo Full Interrupt
     pub fn with_ffull_int(mut self, enabled: bool) -> Self {
-        self.config.int_config0 = self.config.int_config0.with_ffull_int(enabled);
+        self.config = self.config.with_ffull_int(enabled);
         self
     }
     /// Enable/Disable Generic Interrupt 2
     pub fn with_gen2_int(mut self, enabled: bool) -> Self {
-        self.config.int_config0 = self.config.int_config0.with_gen2_int(enabled);
+        self.config = self.config.with_gen2_int(enabled);
         self
     }
     /// Enable/Disable Generic Interrupt 1
     pub fn with_gen1_int(mut self, enabled: bool) -> Self {
-        self.config.int_config0 = self.config.int_config0.with_gen1_int(enabled);
+        self.config = self.config.with_gen1_int(enabled);
         self
     }
     /// Enable/Disable the Orientation Change Interrupt
     pub fn with_orientch_int(mut self, enabled: bool) -> Self {
-        self.config.int_config0 = self.config.int_config0.with_orientch_int(enabled);
+        self.config = self.config.with_orientch_int(enabled);
         self
     }
 
@@ -194,27 +314,27 @@ impl<'a, Interface> IntConfigBuilder<'a, Interface> {
     ///
     /// When enabled, all interrupts persist until the corresponding IntStatus is read
     pub fn with_latch_int(mut self, enabled: bool) -> Self {
-        self.config.int_config1 = self.config.int_config1.with_latch_int(enabled);
+        self.config = self.config.with_latch_int(enabled);
         self
     }
     /// Enable/Disable Activity Changed Interrupt
     pub fn with_actch_int(mut self, enabled: bool) -> Self {
-        self.config.int_config1 = self.config.int_config1.with_actch_int(enabled);
+        self.config = self.config.with_actch_int(enabled);
         self
     }
     /// Enable/Disable Double Tap Interrupt
     pub fn with_d_tap_int(mut self, enabled: bool) -> Self {
-        self.config.int_config1 = self.config.int_config1.with_d_tap_int(enabled);
+        self.config = self.config.with_d_tap_int(enabled);
         self
     }
     /// Enable/Disable Single Tap Interrupt
     pub fn with_s_tap_int(mut self, enabled: bool) -> Self {
-        self.config.int_config1 = self.config.int_config1.with_s_tap_int(enabled);
+        self.config = self.config.with_s_tap_int(enabled);
         self
     }
     /// Enable/Disable Step Interrupt
     pub fn with_step_int(mut self, enabled: bool) -> Self {
-        self.config.int_config1 = self.config.int_config1.with_step_int(enabled);
+        self.config = self.config.with_step_int(enabled);
         self
     }
 }
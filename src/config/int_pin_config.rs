@@ -1,8 +1,15 @@
+#[cfg(feature = "embedded-hal-async")]
+use crate::AsyncInterface;
 use crate::{
-    BMA400, InterruptPins, PinOutputConfig,
+    BMA400, BMA400Error, InterruptPins, PinOutputConfig,
+    config::ConfigRegisters,
     registers::{Int1Map, Int2Map, Int12IOCtrl, Int12Map},
+    typestate::Unchecked,
 };
 
+/// Interrupt pin mapping and electrical settings
+///
+/// Set with the same methods as [IntPinConfigBuilder], without a device
 #[derive(Clone, Default)]
 pub struct IntPinConfig {
     int1_map: Int1Map,
@@ -12,36 +19,174 @@ pub struct IntPinConfig {
 }
 
 impl IntPinConfig {
-    pub fn drdy_map(&self) -> InterruptPins {
+    pub(crate) fn from_registers(registers: &ConfigRegisters) -> Self {
+        Self {
+            int1_map: registers.decode(),
+            int2_map: registers.decode(),
+            int12_map: registers.decode(),
+            int12_io_ctrl: registers.decode(),
+        }
+    }
+    pub(crate) fn to_registers(&self, registers: &mut ConfigRegisters) {
+        registers.encode(self.int1_map);
+        registers.encode(self.int2_map);
+        registers.encode(self.int12_map);
+        registers.encode(self.int12_io_ctrl);
+    }
+
+    pub(crate) fn drdy_map(&self) -> InterruptPins {
         mapped_pins(self.int1_map.drdy_int(), self.int2_map.drdy_int())
     }
-    pub fn fwm_map(&self) -> InterruptPins {
+    pub(crate) fn fwm_map(&self) -> InterruptPins {
         mapped_pins(self.int1_map.fwm_int(), self.int2_map.fwm_int())
     }
-    pub fn ffull_map(&self) -> InterruptPins {
+    pub(crate) fn ffull_map(&self) -> InterruptPins {
         mapped_pins(self.int1_map.ffull_int(), self.int2_map.ffull_int())
     }
-    pub fn gen1_map(&self) -> InterruptPins {
+    pub(crate) fn gen1_map(&self) -> InterruptPins {
         mapped_pins(self.int1_map.gen1_int(), self.int2_map.gen1_int())
     }
-    pub fn gen2_map(&self) -> InterruptPins {
+    pub(crate) fn gen2_map(&self) -> InterruptPins {
         mapped_pins(self.int1_map.gen2_int(), self.int2_map.gen2_int())
     }
-    pub fn wkup_map(&self) -> InterruptPins {
+    pub(crate) fn wkup_map(&self) -> InterruptPins {
         mapped_pins(self.int1_map.wkup_int(), self.int2_map.wkup_int())
     }
-    pub fn orientch_map(&self) -> InterruptPins {
+    pub(crate) fn orientch_map(&self) -> InterruptPins {
         mapped_pins(self.int1_map.orientch_int(), self.int2_map.orientch_int())
     }
-    pub fn actch_map(&self) -> InterruptPins {
+    pub(crate) fn actch_map(&self) -> InterruptPins {
         mapped_pins(self.int12_map.actch_int1(), self.int12_map.actch_int2())
     }
-    pub fn tap_map(&self) -> InterruptPins {
+    pub(crate) fn tap_map(&self) -> InterruptPins {
         mapped_pins(self.int12_map.tap_int1(), self.int12_map.tap_int2())
     }
-    pub fn step_map(&self) -> InterruptPins {
+    pub(crate) fn step_map(&self) -> InterruptPins {
         mapped_pins(self.int12_map.step_int1(), self.int12_map.step_int2())
     }
+    // Whether any interrupt, including the engine overrun, is routed to the pin
+    #[cfg(feature = "embedded-hal-async")]
+    pub(crate) fn int1_mapped(&self) -> bool {
+        !self.int1_map.is_empty()
+            || self.int12_map.actch_int1()
+            || self.int12_map.tap_int1()
+            || self.int12_map.step_int1()
+    }
+    #[cfg(feature = "embedded-hal-async")]
+    pub(crate) fn int2_mapped(&self) -> bool {
+        !self.int2_map.is_empty()
+            || self.int12_map.actch_int2()
+            || self.int12_map.tap_int2()
+            || self.int12_map.step_int2()
+    }
+    #[cfg(feature = "embedded-hal-async")]
+    pub(crate) fn int1_active_high(&self) -> bool {
+        self.int12_io_ctrl.int1_active_high()
+    }
+    #[cfg(feature = "embedded-hal-async")]
+    pub(crate) fn int2_active_high(&self) -> bool {
+        self.int12_io_ctrl.int2_active_high()
+    }
+}
+
+impl IntPinConfig {
+    /// Returns the configuration the device has after a reset
+    pub const fn new() -> Self {
+        Self {
+            int1_map: Int1Map::RESET,
+            int2_map: Int2Map::RESET,
+            int12_map: Int12Map::RESET,
+            int12_io_ctrl: Int12IOCtrl::RESET,
+        }
+    }
+    /// Map Data Ready Interrupt to [InterruptPins]
+    pub const fn with_drdy(mut self, mapped_to: InterruptPins) -> Self {
+        let (int1, int2) = match_mapped(mapped_to);
+        self.int1_map = self.int1_map.with_drdy(int1);
+        self.int2_map = self.int2_map.with_drdy(int2);
+        self
+    }
+    /// Map Fifo Watermark Interrupt to [InterruptPins]
+    pub const fn with_fifo_wm(mut self, mapped_to: InterruptPins) -> Self {
+        let (int1, int2) = match_mapped(mapped_to);
+        self.int1_map = self.int1_map.with_fwm(int1);
+        self.int2_map = self.int2_map.with_fwm(int2);
+        self
+    }
+    /// Map Fifo Full Interrupt to [InterruptPins]
+    pub const fn with_ffull(mut self, mapped_to: InterruptPins) -> Self {
+        let (int1, int2) = match_mapped(mapped_to);
+        self.int1_map = self.int1_map.with_ffull(int1);
+        self.int2_map = self.int2_map.with_ffull(int2);
+        self
+    }
+    /// Map Interrupt Engine Overrun Interrupt to [InterruptPins]
+    pub const fn with_ieng_ovrrn(mut self, mapped_to: InterruptPins) -> Self {
+        let (int1, int2) = match_mapped(mapped_to);
+        self.int1_map = self.int1_map.with_ovrrn(int1);
+        self.int2_map = self.int2_map.with_ovrrn(int2);
+        self
+    }
+    /// Map Generic Interrupt 2 to [InterruptPins]
+    pub const fn with_gen2(mut self, mapped_to: InterruptPins) -> Self {
+        let (int1, int2) = match_mapped(mapped_to);
+        self.int1_map = self.int1_map.with_gen2(int1);
+        self.int2_map = self.int2_map.with_gen2(int2);
+        self
+    }
+    /// Map Generic Interrupt 1 to [InterruptPins]
+    pub const fn with_gen1(mut self, mapped_to: InterruptPins) -> Self {
+        let (int1, int2) = match_mapped(mapped_to);
+        self.int1_map = self.int1_map.with_gen1(int1);
+        self.int2_map = self.int2_map.with_gen1(int2);
+        self
+    }
+    /// Map Orientation Change Interrupt to [InterruptPins]
+    pub const fn with_orientch(mut self, mapped_to: InterruptPins) -> Self {
+        let (int1, int2) = match_mapped(mapped_to);
+        self.int1_map = self.int1_map.with_orientch(int1);
+        self.int2_map = self.int2_map.with_orientch(int2);
+        self
+    }
+    /// Map Wakeup Interrupt to [InterruptPins]
+    pub const fn with_wkup(mut self, mapped_to: InterruptPins) -> Self {
+        let (int1, int2) = match_mapped(mapped_to);
+        self.int1_map = self.int1_map.with_wkup(int1);
+        self.int2_map = self.int2_map.with_wkup(int2);
+        self
+    }
+    /// Map Activity Changed Interrupt to [InterruptPins]
+    pub const fn with_actch(mut self, mapped_to: InterruptPins) -> Self {
+        let (int1, int2) = match_mapped(mapped_to);
+        self.int12_map = self.int12_map.with_actch1(int1).with_actch2(int2);
+        self
+    }
+    /// Map Tap Interrupt to [InterruptPins]
+    pub const fn with_tap(mut self, mapped_to: InterruptPins) -> Self {
+        let (int1, int2) = match_mapped(mapped_to);
+        self.int12_map = self.int12_map.with_tap1(int1).with_tap2(int2);
+        self
+    }
+    /// Map Step Interrupt to [InterruptPins]
+    pub const fn with_step(mut self, mapped_to: InterruptPins) -> Self {
+        let (int1, int2) = match_mapped(mapped_to);
+        self.int12_map = self.int12_map.with_step1(int1).with_step2(int2);
+        self
+    }
+    /// Int1 Pin Output Mode
+    ///
+    /// See Datasheet p.39
+    pub const fn with_int1_cfg(mut self, config: PinOutputConfig) -> Self {
+        self.int12_io_ctrl = self.int12_io_ctrl.with_int1_cfg(config);
+        self
+    }
+    /// Int2 Pin Output Mode
+    ///
+    /// See Datasheet p.39
+    pub const fn with_int2_cfg(mut self, config: PinOutputConfig) -> Self {
+        self.int12_io_ctrl = self.int12_io_ctrl.with_int2_cfg(config);
+        self
+    }
 }
 
 /// Map interrupts to the [InterruptPins::Int1] / [InterruptPins::Int2] hardware interrupt pins
@@ -49,9 +194,10 @@ impl IntPinConfig {
 /// - Control the pin electrical behavior using [`with_int1_cfg()`](IntPinConfigBuilder::with_int1_cfg) / [`with_int2_cfg()`](IntPinConfigBuilder::with_int2_cfg)
 ///    - [`PinOutputConfig::PushPull`] High = VDDIO, Low = GND
 ///    - [`PinOutputConfig::OpenDrain`] High = VDDIO, Low = High Impedance
-pub struct IntPinConfigBuilder<'a, Interface> {
+pub struct IntPinConfigBuilder<'a, Interface, S = Unchecked> {
     config: IntPinConfig,
-    device: &'a mut BMA400<Interface>,
+    verify: bool,
+    device: &'a mut BMA400<Interface, S>,
 }
 
 fn mapped_pins(int1: bool, int2: bool) -> InterruptPins {
@@ -63,7 +209,7 @@ fn mapped_pins(int1: bool, int2: bool) -> InterruptPins {
     }
 }
 
-fn match_mapped(mapped_to: InterruptPins) -> (bool, bool) {
+const fn match_mapped(mapped_to: InterruptPins) -> (bool, bool) {
     match mapped_to {
         InterruptPins::None => (false, false),
         InterruptPins::Int1 => (true, false),
@@ -72,15 +218,24 @@ fn match_mapped(mapped_to: InterruptPins) -> (bool, bool) {
     }
 }
 
-#[cfg(not(feature = "embedded-hal-async"))]
-impl<'a, Interface, E> IntPinConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> IntPinConfigBuilder<'a, Interface, S>
 where
-    Interface: crate::blocking::WriteToRegister<Error = E>,
+    Interface: crate::blocking::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::blocking::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes();
+        self.device.end_config_write(previous, result)
+    }
+
     // Clippy: ignore lint for intentional XOR with self, avoiding an awkward import / function call
     #[allow(clippy::eq_op)]
-    pub fn write(self) -> Result<(), E> {
+    pub(crate) fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
         // Any change of an interrupt configuration must be executed when the corresponding
         // interrupt is disabled. (Datasheet p. 40)
 
@@ -153,60 +308,70 @@ where
         }
         // Write the temporary changes
         if int_config0.bits() != tmp_int_config0.bits() {
-            self.device.interface.write_register(tmp_int_config0)?;
+            self.device.write_register(tmp_int_config0, self.verify)?;
         }
         if int_config1.bits() != tmp_int_config1.bits() {
-            self.device.interface.write_register(tmp_int_config1)?;
+            self.device.write_register(tmp_int_config1, self.verify)?;
         }
         if wkup_int_config0.bits() != tmp_wkup_int_config0.bits() {
-            self.device.interface.write_register(wkup_int_config0)?;
+            self.device.write_register(wkup_int_config0, self.verify)?;
         }
         // Write the config changes
         if self.device.config.int_pin_config.int1_map.bits() != self.config.int1_map.bits() {
-            self.device.interface.write_register(self.config.int1_map)?;
+            self.device
+                .write_register(self.config.int1_map, self.verify)?;
             self.device.config.int_pin_config.int1_map = self.config.int1_map;
         }
         if self.device.config.int_pin_config.int2_map.bits() != self.config.int2_map.bits() {
-            self.device.interface.write_register(self.config.int2_map)?;
+            self.device
+                .write_register(self.config.int2_map, self.verify)?;
             self.device.config.int_pin_config.int2_map = self.config.int2_map;
         }
         if self.device.config.int_pin_config.int12_map.bits() != self.config.int12_map.bits() {
             self.device
-                .interface
-                .write_register(self.config.int12_map)?;
+                .write_register(self.config.int12_map, self.verify)?;
             self.device.config.int_pin_config.int12_map = self.config.int12_map;
         }
         if self.device.config.int_pin_config.int12_io_ctrl.bits()
             != self.config.int12_io_ctrl.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.int12_io_ctrl)?;
+                .write_register(self.config.int12_io_ctrl, self.verify)?;
             self.device.config.int_pin_config.int12_io_ctrl = self.config.int12_io_ctrl;
         }
         // Restore the disabled interrupts
         if self.device.config.int_config.get_config0().bits() != tmp_int_config0.bits() {
-            self.device.interface.write_register(int_config0)?;
+            self.device.write_register(int_config0, self.verify)?;
         }
         if self.device.config.int_config.get_config1().bits() != tmp_int_config0.bits() {
-            self.device.interface.write_register(int_config1)?;
+            self.device.write_register(int_config1, self.verify)?;
         }
         if wkup_int_config0.bits() != tmp_wkup_int_config0.bits() {
-            self.device.interface.write_register(wkup_int_config0)?;
+            self.device.write_register(wkup_int_config0, self.verify)?;
         }
         Ok(())
     }
 }
 
 #[cfg(feature = "embedded-hal-async")]
-impl<'a, Interface, E> IntPinConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> IntPinConfigBuilder<'a, AsyncInterface<Interface>, S>
 where
-    Interface: crate::asynch::WriteToRegister<Error = E>,
+    AsyncInterface<Interface>: crate::asynch::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::asynch::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub async fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes().await;
+        self.device.end_config_write(previous, result).await
+    }
+
     // Clippy: ignore lint for intentional XOR with self, avoiding an awkward import / function call
     #[allow(clippy::eq_op)]
-    pub async fn write(self) -> Result<(), E> {
+    pub(crate) async fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
         // Any change of an interrupt configuration must be executed when the corresponding
         // interrupt is disabled. (Datasheet p. 40)
 
@@ -280,41 +445,35 @@ where
         // Write the temporary changes
         if int_config0.bits() != tmp_int_config0.bits() {
             self.device
-                .interface
-                .write_register(tmp_int_config0)
+                .write_register(tmp_int_config0, self.verify)
                 .await?;
         }
         if int_config1.bits() != tmp_int_config1.bits() {
             self.device
-                .interface
-                .write_register(tmp_int_config1)
+                .write_register(tmp_int_config1, self.verify)
                 .await?;
         }
         if wkup_int_config0.bits() != tmp_wkup_int_config0.bits() {
             self.device
-                .interface
-                .write_register(wkup_int_config0)
+                .write_register(wkup_int_config0, self.verify)
                 .await?;
         }
         // Write the config changes
         if self.device.config.int_pin_config.int1_map.bits() != self.config.int1_map.bits() {
             self.device
-                .interface
-                .write_register(self.config.int1_map)
+                .write_register(self.config.int1_map, self.verify)
                 .await?;
             self.device.config.int_pin_config.int1_map = self.config.int1_map;
         }
         if self.device.config.int_pin_config.int2_map.bits() != self.config.int2_map.bits() {
             self.device
-                .interface
-                .write_register(self.config.int2_map)
+                .write_register(self.config.int2_map, self.verify)
                 .await?;
             self.device.config.int_pin_config.int2_map = self.config.int2_map;
         }
         if self.device.config.int_pin_config.int12_map.bits() != self.config.int12_map.bits() {
             self.device
-                .interface
-                .write_register(self.config.int12_map)
+                .write_register(self.config.int12_map, self.verify)
                 .await?;
             self.device.config.int_pin_config.int12_map = self.config.int12_map;
         }
@@ -322,111 +481,109 @@ where
             != self.config.int12_io_ctrl.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.int12_io_ctrl)
+                .write_register(self.config.int12_io_ctrl, self.verify)
                 .await?;
             self.device.config.int_pin_config.int12_io_ctrl = self.config.int12_io_ctrl;
         }
         // Restore the disabled interrupts
         if self.device.config.int_config.get_config0().bits() != tmp_int_config0.bits() {
-            self.device.interface.write_register(int_config0).await?;
+            self.device.write_register(int_config0, self.verify).await?;
         }
         if self.device.config.int_config.get_config1().bits() != tmp_int_config0.bits() {
-            self.device.interface.write_register(int_config1).await?;
+            self.device.write_register(int_config1, self.verify).await?;
         }
         if wkup_int_config0.bits() != tmp_wkup_int_config0.bits() {
             self.device
-                .interface
-                .write_register(wkup_int_config0)
+                .write_register(wkup_int_config0, self.verify)
                 .await?;
         }
         Ok(())
     }
 }
 
-impl<'a, Interface> IntPinConfigBuilder<'a, Interface> {
-    pub(crate) fn new(device: &'a mut BMA400<Interface>) -> IntPinConfigBuilder<'a, Interface> {
+impl<'a, Interface, S> IntPinConfigBuilder<'a, Interface, S> {
+    pub(crate) fn new(
+        device: &'a mut BMA400<Interface, S>,
+    ) -> IntPinConfigBuilder<'a, Interface, S> {
         IntPinConfigBuilder {
             config: device.config.int_pin_config.clone(),
+            verify: device.verify_writes,
             device,
         }
     }
+    /// Start from `config` instead of the current device configuration
+    pub(crate) fn from_config(device: &'a mut BMA400<Interface, S>, config: IntPinConfig) -> Self {
+        IntPinConfigBuilder {
+            config,
+            verify: device.verify_writes,
+            device,
+        }
+    }
+    /// Read back each register written by [`write()`](IntPinConfigBuilder::write) and return
+    /// [`BMA400Error::VerifyFailed`] if the device did not accept the value
+    ///
+    /// Defaults to the device setting, see [`set_verify_writes()`](BMA400::set_verify_writes)
+    pub fn with_verify_writes(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
 
     // Int1Map / Int2Map
     /// Map Data Ready Interrupt to [InterruptPins]
     pub fn with_drdy(mut self, mapped_to: InterruptPins) -> Self {
-        let (int1, int2) = match_mapped(mapped_to);
-        self.config.int1_map = self.config.int1_map.with_drdy(int1);
-        self.config.int2_map = self.config.int2_map.with_drdy(int2);
+        self.config = self.config.with_drdy(mapped_to);
         self
     }
     /// Map Fifo Watermark Interrupt to [InterruptPins]
     pub fn with_fifo_wm(mut self, mapped_to: InterruptPins) -> Self {
-        let (int1, int2) = match_mapped(mapped_to);
-        self.config.int1_map = self.config.int1_map.with_fwm(int1);
-        self.config.int2_map = self.config.int2_map.with_fwm(int2);
+        self.config = self.config.with_fifo_wm(mapped_to);
         self
     }
     /// Map Fifo Full Interrupt to [InterruptPins]
     pub fn with_ffull(mut self, mapped_to: InterruptPins) -> Self {
-        let (int1, int2) = match_mapped(mapped_to);
-        self.config.int1_map = self.config.int1_map.with_ffull(int1);
-        self.config.int2_map = self.config.int2_map.with_ffull(int2);
+        self.config = self.config.with_ffull(mapped_to);
         self
     }
     /// Map Interrupt Engine Overrun Interrupt to [InterruptPins]
     pub fn with_ieng_ovrrn(mut self, mapped_to: InterruptPins) -> Self {
-        let (int1, int2) = match_mapped(mapped_to);
-        self.config.int1_map = self.config.int1_map.with_ovrrn(int1);
-        self.config.int2_map = self.config.int2_map.with_ovrrn(int2);
+        self.config = self.config.with_ieng_ovrrn(mapped_to);
         self
     }
     /// Map Generic Interrupt 2 to [InterruptPins]
     pub fn with_gen2(mut self, mapped_to: InterruptPins) -> Self {
-        let (int1, int2) = match_mapped(mapped_to);
-        self.config.int1_map = self.config.int1_map.with_gen2(int1);
-        self.config.int2_map = self.config.int2_map.with_gen2(int2);
+        self.config = self.config.with_gen2(mapped_to);
         self
     }
     /// Map Generic Interrupt 1 to [InterruptPins]
     pub fn with_gen1(mut self, mapped_to: InterruptPins) -> Self {
-        let (int1, int2) = match_mapped(mapped_to);
-        self.config.int1_map = self.config.int1_map.with_gen1(int1);
-        self.config.int2_map = self.config.int2_map.with_gen1(int2);
+        self.config = self.config.with_gen1(mapped_to);
         self
     }
     /// Map Orientation Change Interrupt to [InterruptPins]
     pub fn with_orientch(mut self, mapped_to: InterruptPins) -> Self {
-        let (int1, int2) = match_mapped(mapped_to);
-        self.config.int1_map = self.config.int1_map.with_orientch(int1);
-        self.config.int2_map = self.config.int2_map.with_orientch(int2);
+        self.config = self.config.with_orientch(mapped_to);
         self
     }
     /// Map Wakeup Interrupt to [InterruptPins]
     pub fn with_wkup(mut self, mapped_to: InterruptPins) -> Self {
-        let (int1, int2) = match_mapped(mapped_to);
-        self.config.int1_map = self.config.int1_map.with_wkup(int1);
-        self.config.int2_map = self.config.int2_map.with_wkup(int2);
+        self.config = self.config.with_wkup(mapped_to);
         self
     }
 
     // Int12Map
     /// Map Activity Changed Interrupt to [InterruptPins]
     pub fn with_actch(mut self, mapped_to: InterruptPins) -> Self {
-        let (int1, int2) = match_mapped(mapped_to);
-        self.config.int12_map = self.config.int12_map.with_actch1(int1).with_actch2(int2);
+        self.config = self.config.with_actch(mapped_to);
         self
     }
     /// Map Tap Interrupt to [InterruptPins]
     pub fn with_tap(mut self, mapped_to: InterruptPins) -> Self {
-        let (int1, int2) = match_mapped(mapped_to);
-        self.config.int12_map = self.config.int12_map.with_tap1(int1).with_tap2(int2);
+        self.config = self.config.with_tap(mapped_to);
         self
     }
     /// Map Step Interrupt to [InterruptPins]
     pub fn with_step(mut self, mapped_to: InterruptPins) -> Self {
-        let (int1, int2) = match_mapped(mapped_to);
-        self.config.int12_map = self.config.int12_map.with_step1(int1).with_step2(int2);
+        self.config = self.config.with_step(mapped_to);
         self
     }
 
@@ -435,14 +592,14 @@ impl<'a, Interface> IntPinConfigBuilder<'a, Interface> {
     ///
     /// See Datasheet p.39
     pub fn with_int1_cfg(mut self, config: PinOutputConfig) -> Self {
-        self.config.int12_io_ctrl = self.config.int12_io_ctrl.with_int1_cfg(config);
+        self.config = self.config.with_int1_cfg(config);
         self
     }
     /// Int2 Pin Output Mode
     ///
     /// See Datasheet p.39
     pub fn with_int2_cfg(mut self, config: PinOutputConfig) -> Self {
-        self.config.int12_io_ctrl = self.config.int12_io_ctrl.with_int2_cfg(config);
+        self.config = self.config.with_int2_cfg(config);
         self
     }
 }
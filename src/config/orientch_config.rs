@@ -1,11 +1,18 @@
+#[cfg(feature = "embedded-hal-async")]
+use crate::AsyncInterface;
 use crate::{
-    BMA400, DataSource, OrientIntRefMode,
+    BMA400, BMA400Error, ConfigError, ConfigErrors, DataSource, OrientIntRefMode,
+    config::{ConfigRegisters, clamp_i16, out_of_range, to_steps},
     registers::{
         OrientChgConfig0, OrientChgConfig1, OrientChgConfig3, OrientChgConfig4, OrientChgConfig5,
         OrientChgConfig6, OrientChgConfig7, OrientChgConfig8, OrientChgConfig9,
     },
+    typestate::Unchecked,
 };
 
+/// Orientation Change Interrupt settings
+///
+/// Set with the same methods as [OrientChgConfigBuilder], without a device
 #[derive(Clone, Default)]
 pub struct OrientChgConfig {
     orientch_config0: OrientChgConfig0,
@@ -17,6 +24,151 @@ pub struct OrientChgConfig {
     orientch_config7: OrientChgConfig7,
     orientch_config8: OrientChgConfig8,
     orientch_config9: OrientChgConfig9,
+    adjusted: ConfigErrors,
+}
+
+impl OrientChgConfig {
+    pub(crate) fn from_registers(registers: &ConfigRegisters) -> Self {
+        Self {
+            orientch_config0: registers.decode(),
+            orientch_config1: registers.decode(),
+            orientch_config3: registers.decode(),
+            orientch_config4: registers.decode(),
+            orientch_config5: registers.decode(),
+            orientch_config6: registers.decode(),
+            orientch_config7: registers.decode(),
+            orientch_config8: registers.decode(),
+            orientch_config9: registers.decode(),
+            adjusted: ConfigErrors::new(),
+        }
+    }
+    pub(crate) fn to_registers(&self, registers: &mut ConfigRegisters) {
+        registers.encode(self.orientch_config0);
+        registers.encode(self.orientch_config1);
+        registers.encode(self.orientch_config3);
+        registers.encode(self.orientch_config4);
+        registers.encode(self.orientch_config5);
+        registers.encode(self.orientch_config6);
+        registers.encode(self.orientch_config7);
+        registers.encode(self.orientch_config8);
+        registers.encode(self.orientch_config9);
+    }
+    pub(crate) fn adjusted(&self) -> ConfigErrors {
+        self.adjusted
+    }
+}
+
+impl OrientChgConfig {
+    /// Returns the configuration the device has after a reset
+    pub const fn new() -> Self {
+        Self {
+            orientch_config0: OrientChgConfig0::RESET,
+            orientch_config1: OrientChgConfig1::RESET,
+            orientch_config3: OrientChgConfig3::RESET,
+            orientch_config4: OrientChgConfig4::RESET,
+            orientch_config5: OrientChgConfig5::RESET,
+            orientch_config6: OrientChgConfig6::RESET,
+            orientch_config7: OrientChgConfig7::RESET,
+            orientch_config8: OrientChgConfig8::RESET,
+            orientch_config9: OrientChgConfig9::RESET,
+            adjusted: ConfigErrors::new(),
+        }
+    }
+    /// Enable/Disable the axes evaluated for the interrupt trigger condition
+    pub const fn with_axes(mut self, x_en: bool, y_en: bool, z_en: bool) -> Self {
+        self.orientch_config0 = self
+            .orientch_config0
+            .with_x_axis(x_en)
+            .with_y_axis(y_en)
+            .with_z_axis(z_en);
+        self
+    }
+    /// Set the data source used for evaluating the interrupt trigger condition
+    ///
+    /// Cannot use [DataSource::AccFilt1]. If passed, this will default to AccFilt2, or in
+    /// strict mode [`ConfigError::OrientChgInvalidSrc`] is returned when writing
+    pub const fn with_src(mut self, src: DataSource) -> Self {
+        let invalid = matches!(src, DataSource::AccFilt1);
+        self.adjusted = self
+            .adjusted
+            .with(ConfigError::OrientChgInvalidSrc, invalid);
+        self.orientch_config0 = match src {
+            DataSource::AccFilt1 => self.orientch_config0.with_data_src(DataSource::AccFilt2),
+            _ => self.orientch_config0.with_data_src(src),
+        };
+        self
+    }
+    /// Set reference update mode for the interrupt
+    pub const fn with_ref_mode(mut self, mode: OrientIntRefMode) -> Self {
+        self.orientch_config0 = self.orientch_config0.with_update_mode(mode);
+        self
+    }
+    /// Set threshold above reference acceleration for the interrupt evaluation (8 milli-g / lsb)
+    pub const fn with_threshold(mut self, threshold: u8) -> Self {
+        self.orientch_config1 = self.orientch_config1.with_orient_thresh(threshold);
+        self
+    }
+    /// Set the threshold in milli-g, rounded to the nearest 8 milli-g
+    ///
+    /// Returns [`ConfigError::ThresholdOutOfRange`] above 2043 milli-g
+    pub const fn with_threshold_mg(self, threshold: u16) -> Result<Self, ConfigError> {
+        match to_steps(
+            threshold as u32,
+            8,
+            1,
+            255,
+            ConfigError::ThresholdOutOfRange,
+        ) {
+            Ok(threshold) => Ok(self.with_threshold(threshold as u8)),
+            Err(e) => Err(e),
+        }
+    }
+    /// Set the duration (in number of samples) that a new detected orientation must be in effect
+    /// before the interrupt is triggered.
+    ///
+    /// The ODR of the data source is 100Hz, so this value is in multiples of 10ms
+    pub const fn with_duration(mut self, duration: u8) -> Self {
+        self.orientch_config3 = self.orientch_config3.with_orient_dur(duration);
+        self
+    }
+    /// Set the duration in milliseconds, rounded to the nearest 10ms
+    ///
+    /// Returns [`ConfigError::DurationOutOfRange`] above 2554ms
+    pub const fn with_duration_ms(self, duration: u32) -> Result<Self, ConfigError> {
+        match to_steps(duration, 10, 1, 255, ConfigError::DurationOutOfRange) {
+            Ok(duration) => Ok(self.with_duration(duration as u8)),
+            Err(e) => Err(e),
+        }
+    }
+    /// Manually set the reference acceleration for the x,y,z axes (use with
+    /// [OrientIntRefMode::Manual])
+    ///
+    /// In order for an axis to be evaluated it must be enabled using `with_axes()`
+    ///
+    /// 12-bit, clamped to \[-2048, 2047\], or in strict mode
+    /// [`ConfigError::RefAccelOutOfRange`] is returned when writing
+    pub const fn with_ref_accel(mut self, ref_x: i16, ref_y: i16, ref_z: i16) -> Self {
+        let adjusted = out_of_range(ref_x) || out_of_range(ref_y) || out_of_range(ref_z);
+        self.adjusted = self
+            .adjusted
+            .with(ConfigError::RefAccelOutOfRange, adjusted);
+        let (ref_x, ref_y, ref_z) = (
+            clamp_i16(ref_x, -2048, 2047),
+            clamp_i16(ref_y, -2048, 2047),
+            clamp_i16(ref_z, -2048, 2047),
+        );
+
+        self.orientch_config4 = self.orientch_config4.with_refx_lsb(ref_x);
+        self.orientch_config5 = self.orientch_config5.with_refx_msb(ref_x);
+
+        self.orientch_config6 = self.orientch_config6.with_refy_lsb(ref_y);
+        self.orientch_config7 = self.orientch_config7.with_refy_msb(ref_y);
+
+        self.orientch_config8 = self.orientch_config8.with_refz_lsb(ref_z);
+        self.orientch_config9 = self.orientch_config9.with_refz_msb(ref_z);
+
+        self
+    }
 }
 
 /// Configure Orientation Change Interrupt settings
@@ -26,18 +178,30 @@ pub struct OrientChgConfig {
 /// - Set the [OrientIntRefMode] (reference acceleration update mode) using [`with_ref_mode()`](OrientChgConfigBuilder::with_ref_mode)
 /// - Set the number of samples that a newly detected orientation must be in effect before the interrupt is triggered with [`with_duration()`](OrientChgConfigBuilder::with_duration)
 /// - Manually set the reference acceleration for the interrupt trigger condition using [`with_ref_accel()`](OrientChgConfigBuilder::with_ref_accel)
-pub struct OrientChgConfigBuilder<'a, Interface> {
+pub struct OrientChgConfigBuilder<'a, Interface, S = Unchecked> {
     config: OrientChgConfig,
-    device: &'a mut BMA400<Interface>,
+    verify: bool,
+    device: &'a mut BMA400<Interface, S>,
 }
 
-#[cfg(not(feature = "embedded-hal-async"))]
-impl<'a, Interface, E> OrientChgConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> OrientChgConfigBuilder<'a, Interface, S>
 where
-    Interface: crate::blocking::WriteToRegister<Error = E>,
+    Interface: crate::blocking::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::blocking::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
-    pub fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes();
+        self.device.end_config_write(previous, result)
+    }
+
+    pub(crate) fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
+        self.device
+            .check_strict(|config| config.orientch_config = self.config.clone())?;
         let has_config0_changes = self.device.config.orientch_config.orientch_config0.bits()
             != self.config.orientch_config0.bits();
         let has_config1_changes = self.device.config.orientch_config.orientch_config1.bits()
@@ -71,80 +235,82 @@ where
         // Temporarily disable interrupt, if active
         if tmp_int_config0.orientch_int() && has_changes {
             tmp_int_config0 = tmp_int_config0.with_orientch_int(false);
-            self.device.interface.write_register(tmp_int_config0)?;
+            self.device.write_register(tmp_int_config0, self.verify)?;
         }
         // Write the changes
         if has_config0_changes {
             self.device
-                .interface
-                .write_register(self.config.orientch_config0)?;
+                .write_register(self.config.orientch_config0, self.verify)?;
             self.device.config.orientch_config.orientch_config0 = self.config.orientch_config0;
         }
         if has_config1_changes {
             self.device
-                .interface
-                .write_register(self.config.orientch_config1)?;
+                .write_register(self.config.orientch_config1, self.verify)?;
             self.device.config.orientch_config.orientch_config1 = self.config.orientch_config1;
         }
         if has_config3_changes {
             self.device
-                .interface
-                .write_register(self.config.orientch_config3)?;
+                .write_register(self.config.orientch_config3, self.verify)?;
             self.device.config.orientch_config.orientch_config3 = self.config.orientch_config3;
         }
         if has_config4_changes {
             self.device
-                .interface
-                .write_register(self.config.orientch_config4)?;
+                .write_register(self.config.orientch_config4, self.verify)?;
             self.device.config.orientch_config.orientch_config4 = self.config.orientch_config4;
         }
         if has_config5_changes {
             self.device
-                .interface
-                .write_register(self.config.orientch_config5)?;
+                .write_register(self.config.orientch_config5, self.verify)?;
             self.device.config.orientch_config.orientch_config5 = self.config.orientch_config5;
         }
         if has_config6_changes {
             self.device
-                .interface
-                .write_register(self.config.orientch_config6)?;
+                .write_register(self.config.orientch_config6, self.verify)?;
             self.device.config.orientch_config.orientch_config6 = self.config.orientch_config6;
         }
         if has_config7_changes {
             self.device
-                .interface
-                .write_register(self.config.orientch_config7)?;
+                .write_register(self.config.orientch_config7, self.verify)?;
             self.device.config.orientch_config.orientch_config7 = self.config.orientch_config7;
         }
         if has_config8_changes {
             self.device
-                .interface
-                .write_register(self.config.orientch_config8)?;
+                .write_register(self.config.orientch_config8, self.verify)?;
             self.device.config.orientch_config.orientch_config8 = self.config.orientch_config8;
         }
         if has_config9_changes {
             self.device
-                .interface
-                .write_register(self.config.orientch_config9)?;
+                .write_register(self.config.orientch_config9, self.verify)?;
             self.device.config.orientch_config.orientch_config9 = self.config.orientch_config9;
         }
         // Re-enable interrupt, if disabled
         if self.device.config.int_config.get_config0().bits() != tmp_int_config0.bits() {
             self.device
-                .interface
-                .write_register(self.device.config.int_config.get_config0())?;
+                .write_register(self.device.config.int_config.get_config0(), self.verify)?;
         }
         Ok(())
     }
 }
 
 #[cfg(feature = "embedded-hal-async")]
-impl<'a, Interface, E> OrientChgConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> OrientChgConfigBuilder<'a, AsyncInterface<Interface>, S>
 where
-    Interface: crate::asynch::WriteToRegister<Error = E>,
+    AsyncInterface<Interface>: crate::asynch::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::asynch::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
-    pub async fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub async fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes().await;
+        self.device.end_config_write(previous, result).await
+    }
+
+    pub(crate) async fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
+        self.device
+            .check_strict(|config| config.orientch_config = self.config.clone())?;
         let has_config0_changes = self.device.config.orientch_config.orientch_config0.bits()
             != self.config.orientch_config0.bits();
         let has_config1_changes = self.device.config.orientch_config.orientch_config1.bits()
@@ -179,130 +345,138 @@ where
         if tmp_int_config0.orientch_int() && has_changes {
             tmp_int_config0 = tmp_int_config0.with_orientch_int(false);
             self.device
-                .interface
-                .write_register(tmp_int_config0)
+                .write_register(tmp_int_config0, self.verify)
                 .await?;
         }
         // Write the changes
         if has_config0_changes {
             self.device
-                .interface
-                .write_register(self.config.orientch_config0)
+                .write_register(self.config.orientch_config0, self.verify)
                 .await?;
             self.device.config.orientch_config.orientch_config0 = self.config.orientch_config0;
         }
         if has_config1_changes {
             self.device
-                .interface
-                .write_register(self.config.orientch_config1)
+                .write_register(self.config.orientch_config1, self.verify)
                 .await?;
             self.device.config.orientch_config.orientch_config1 = self.config.orientch_config1;
         }
         if has_config3_changes {
             self.device
-                .interface
-                .write_register(self.config.orientch_config3)
+                .write_register(self.config.orientch_config3, self.verify)
                 .await?;
             self.device.config.orientch_config.orientch_config3 = self.config.orientch_config3;
         }
         if has_config4_changes {
             self.device
-                .interface
-                .write_register(self.config.orientch_config4)
+                .write_register(self.config.orientch_config4, self.verify)
                 .await?;
             self.device.config.orientch_config.orientch_config4 = self.config.orientch_config4;
         }
         if has_config5_changes {
             self.device
-                .interface
-                .write_register(self.config.orientch_config5)
+                .write_register(self.config.orientch_config5, self.verify)
                 .await?;
             self.device.config.orientch_config.orientch_config5 = self.config.orientch_config5;
         }
         if has_config6_changes {
             self.device
-                .interface
-                .write_register(self.config.orientch_config6)
+                .write_register(self.config.orientch_config6, self.verify)
                 .await?;
             self.device.config.orientch_config.orientch_config6 = self.config.orientch_config6;
         }
         if has_config7_changes {
             self.device
-                .interface
-                .write_register(self.config.orientch_config7)
+                .write_register(self.config.orientch_config7, self.verify)
                 .await?;
             self.device.config.orientch_config.orientch_config7 = self.config.orientch_config7;
         }
         if has_config8_changes {
             self.device
-                .interface
-                .write_register(self.config.orientch_config8)
+                .write_register(self.config.orientch_config8, self.verify)
                 .await?;
             self.device.config.orientch_config.orientch_config8 = self.config.orientch_config8;
         }
         if has_config9_changes {
             self.device
-                .interface
-                .write_register(self.config.orientch_config9)
+                .write_register(self.config.orientch_config9, self.verify)
                 .await?;
             self.device.config.orientch_config.orientch_config9 = self.config.orientch_config9;
         }
         // Re-enable interrupt, if disabled
         if self.device.config.int_config.get_config0().bits() != tmp_int_config0.bits() {
             self.device
-                .interface
-                .write_register(self.device.config.int_config.get_config0())
+                .write_register(self.device.config.int_config.get_config0(), self.verify)
                 .await?;
         }
         Ok(())
     }
 }
 
-impl<'a, Interface> OrientChgConfigBuilder<'a, Interface> {
-    pub(crate) fn new(device: &'a mut BMA400<Interface>) -> OrientChgConfigBuilder<'a, Interface> {
+impl<'a, Interface, S> OrientChgConfigBuilder<'a, Interface, S> {
+    pub(crate) fn new(
+        device: &'a mut BMA400<Interface, S>,
+    ) -> OrientChgConfigBuilder<'a, Interface, S> {
         OrientChgConfigBuilder {
             config: device.config.orientch_config.clone(),
+            verify: device.verify_writes,
             device,
         }
     }
+    /// Start from `config` instead of the current device configuration
+    pub(crate) fn from_config(
+        device: &'a mut BMA400<Interface, S>,
+        config: OrientChgConfig,
+    ) -> Self {
+        OrientChgConfigBuilder {
+            config,
+            verify: device.verify_writes,
+            device,
+        }
+    }
+    /// Read back each register written by [`write()`](OrientChgConfigBuilder::write) and return
+    /// [`BMA400Error::VerifyFailed`] if the device did not accept the value
+    ///
+    /// Defaults to the device setting, see [`set_verify_writes()`](BMA400::set_verify_writes)
+    pub fn with_verify_writes(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
 
     // OrientChgConfig0
 
     /// Enable/Disable the axes evaluated for the interrupt trigger condition
     pub fn with_axes(mut self, x_en: bool, y_en: bool, z_en: bool) -> Self {
-        self.config.orientch_config0 = self
-            .config
-            .orientch_config0
-            .with_x_axis(x_en)
-            .with_y_axis(y_en)
-            .with_z_axis(z_en);
+        self.config = self.config.with_axes(x_en, y_en, z_en);
         self
     }
     /// Set the data source used for evaluating the interrupt trigger condition
     ///
-    /// Cannot use [DataSource::AccFilt1]. If passed, this will default to AccFilt2
+    /// Cannot use [DataSource::AccFilt1]. If passed, this will default to AccFilt2, or in
+    /// strict mode [`ConfigError::OrientChgInvalidSrc`] is returned when writing
     pub fn with_src(mut self, src: DataSource) -> Self {
-        self.config.orientch_config0 = match src {
-            DataSource::AccFilt1 => self
-                .config
-                .orientch_config0
-                .with_data_src(DataSource::AccFilt2),
-            _ => self.config.orientch_config0.with_data_src(src),
-        };
+        self.config = self.config.with_src(src);
         self
     }
     /// Set reference update mode for the interrupt
     pub fn with_ref_mode(mut self, mode: OrientIntRefMode) -> Self {
-        self.config.orientch_config0 = self.config.orientch_config0.with_update_mode(mode);
+        self.config = self.config.with_ref_mode(mode);
         self
     }
 
     // OrientChgConfig1
     /// Set threshold above reference acceleration for the interrupt evaluation (8 milli-g / lsb)
     pub fn with_threshold(mut self, threshold: u8) -> Self {
-        self.config.orientch_config1 = self.config.orientch_config1.with_orient_thresh(threshold);
+        self.config = self.config.with_threshold(threshold);
         self
     }
+    /// Set the threshold in milli-g, rounded to the nearest 8 milli-g
+    ///
+    /// Returns [`ConfigError::ThresholdOutOfRange`] above 2043 milli-g
+    pub fn with_threshold_mg(mut self, threshold: u16) -> Result<Self, ConfigError> {
+        self.config = self.config.with_threshold_mg(threshold)?;
+        Ok(self)
+    }
 
     // OrientChgConfig3
     /// Set the duration (in number of samples) that a new detected orientation must be in effect
@@ -310,31 +484,27 @@ impl<'a, Interface> OrientChgConfigBuilder<'a, Interface> {
     ///
     /// The ODR of the data source is 100Hz, so this value is in multiples of 10ms
     pub fn with_duration(mut self, duration: u8) -> Self {
-        self.config.orientch_config3 = self.config.orientch_config3.with_orient_dur(duration);
+        self.config = self.config.with_duration(duration);
         self
     }
+    /// Set the duration in milliseconds, rounded to the nearest 10ms
+    ///
+    /// Returns [`ConfigError::DurationOutOfRange`] above 2554ms
+    pub fn with_duration_ms(mut self, duration: u32) -> Result<Self, ConfigError> {
+        self.config = self.config.with_duration_ms(duration)?;
+        Ok(self)
+    }
 
     // OrientChgConfig4-9
     /// Manually set the reference acceleration for the x,y,z axes (use with
     /// [OrientIntRefMode::Manual])
     ///
     /// In order for an axis to be evaluated it must be enabled using `with_axes()`
+    ///
+    /// 12-bit, clamped to \[-2048, 2047\], or in strict mode
+    /// [`ConfigError::RefAccelOutOfRange`] is returned when writing
     pub fn with_ref_accel(mut self, ref_x: i16, ref_y: i16, ref_z: i16) -> Self {
-        let (ref_x, ref_y, ref_z) = (
-            ref_x.clamp(-2048, 2047),
-            ref_y.clamp(-2048, 2047),
-            ref_z.clamp(-2048, 2047),
-        );
-
-        self.config.orientch_config4 = self.config.orientch_config4.with_refx_lsb(ref_x);
-        self.config.orientch_config5 = self.config.orientch_config5.with_refx_msb(ref_x);
-
-        self.config.orientch_config6 = self.config.orientch_config6.with_refy_lsb(ref_y);
-        self.config.orientch_config7 = self.config.orientch_config7.with_refy_msb(ref_y);
-
-        self.config.orientch_config8 = self.config.orientch_config8.with_refz_lsb(ref_z);
-        self.config.orientch_config9 = self.config.orientch_config9.with_refz_msb(ref_z);
-
+        self.config = self.config.with_ref_accel(ref_x, ref_y, ref_z);
         self
     }
 }
@@ -386,6 +556,21 @@ mod tests {
         assert_eq!(builder.config.orientch_config1.bits(), 0x00);
     }
     #[test]
+    fn test_threshold_mg() {
+        let mut device = get_test_device();
+        let builder = device
+            .config_orientchg_int()
+            .with_threshold_mg(100)
+            .unwrap();
+        assert_eq!(builder.config.orientch_config1.bits(), 13);
+        let builder = builder.with_threshold_mg(2043).unwrap();
+        assert_eq!(builder.config.orientch_config1.bits(), 0xFF);
+        assert!(matches!(
+            builder.with_threshold_mg(2044),
+            Err(ConfigError::ThresholdOutOfRange)
+        ));
+    }
+    #[test]
     fn test_duration() {
         let mut device = get_test_device();
         let builder = device.config_orientchg_int();
@@ -395,6 +580,18 @@ mod tests {
         assert_eq!(builder.config.orientch_config3.bits(), 0x00);
     }
     #[test]
+    fn test_duration_ms() {
+        let mut device = get_test_device();
+        let builder = device.config_orientchg_int().with_duration_ms(104).unwrap();
+        assert_eq!(builder.config.orientch_config3.bits(), 10);
+        let builder = builder.with_duration_ms(2554).unwrap();
+        assert_eq!(builder.config.orientch_config3.bits(), 0xFF);
+        assert!(matches!(
+            builder.with_duration_ms(2555),
+            Err(ConfigError::DurationOutOfRange)
+        ));
+    }
+    #[test]
     fn test_ref_accel() {
         let mut device = get_test_device();
         let builder = device.config_orientchg_int();
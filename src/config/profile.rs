@@ -0,0 +1,70 @@
+use crate::config::{
+    AccConfig, ActChgConfig, AutoLpConfig, AutoWakeupConfig, FifoConfig, Gen1IntConfig,
+    Gen2IntConfig, IntConfig, IntPinConfig, OrientChgConfig, TapConfig, WakeupIntConfig,
+    config_sections,
+};
+
+macro_rules! profile {
+    ($($name:ident, $setter:ident, $field:ident: $ty:ident, $builder:ident, $desc:literal;)+) => {
+        /// A set of configuration values which can be declared without a device, e.g. as a `const`,
+        /// and written to one or more devices using [`apply()`](crate::BMA400::apply)
+        ///
+        /// Only the sections that were set are written, the other settings of the device are left
+        /// as they are. Each section is written as a whole, starting from its reset values
+        ///
+        /// # Examples
+        /// ```
+        /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+        /// use bma400::{
+        ///     BMA400, InterruptPins, OutputDataRate, PowerMode,
+        ///     config::{AccConfig, IntConfig, IntPinConfig, Profile},
+        /// };
+        ///
+        /// const TAP_DETECTION: Profile = Profile::new()
+        ///     .with_accel(
+        ///         AccConfig::new()
+        ///             .with_power_mode(PowerMode::Normal)
+        ///             .with_odr(OutputDataRate::Hz200),
+        ///     )
+        ///     .with_interrupts(IntConfig::new().with_s_tap_int(true))
+        ///     .with_int_pins(IntPinConfig::new().with_tap(InterruptPins::Int1));
+        ///
+        /// # let ADDR = 0b10100;
+        /// # let expected = vec![
+        /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+        /// #        Transaction::write(ADDR, vec![0x19, 0x02]),
+        /// #        Transaction::write(ADDR, vec![0x23, 0x04]),
+        /// #        Transaction::write(ADDR, vec![0x20, 0x04]),
+        /// #    ];
+        /// # let mut i2c = Mock::new(&expected);
+        /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+        /// bma400.apply(&TAP_DETECTION).unwrap();
+        /// # i2c.done();
+        /// ```
+        #[derive(Clone, Default)]
+        pub struct Profile {
+            $(pub(crate) $name: Option<$ty>,)+
+        }
+
+        impl Profile {
+            /// An empty profile, applying it leaves the device unchanged
+            pub const fn new() -> Self {
+                Self {
+                    $($name: None,)+
+                }
+            }
+            $(
+                #[doc = concat!(
+                    $desc, ", see [`", stringify!($builder), "()`](crate::BMA400::",
+                    stringify!($builder), ")"
+                )]
+                pub const fn $setter(mut self, config: $ty) -> Self {
+                    self.$name = Some(config);
+                    self
+                }
+            )+
+        }
+    };
+}
+
+config_sections!(profile);
@@ -1,14 +1,72 @@
+#[cfg(feature = "embedded-hal-async")]
+use crate::AsyncInterface;
 use crate::{
-    Axis, BMA400, ConfigError, DoubleTapDuration, MaxTapDuration, MinTapDuration, TapSensitivity,
+    Axis, BMA400, BMA400Error, DoubleTapDuration, MaxTapDuration, MinTapDuration, TapSensitivity,
+    config::ConfigRegisters,
     registers::{TapConfig0, TapConfig1},
+    typestate::Unchecked,
 };
 
+/// Advanced Tap Interrupt settings
+///
+/// Set with the same methods as [TapConfigBuilder], without a device
 #[derive(Clone, Default)]
 pub struct TapConfig {
     tap_config0: TapConfig0,
     tap_config1: TapConfig1,
 }
 
+impl TapConfig {
+    pub(crate) fn from_registers(registers: &ConfigRegisters) -> Self {
+        Self {
+            tap_config0: registers.decode(),
+            tap_config1: registers.decode(),
+        }
+    }
+    pub(crate) fn to_registers(&self, registers: &mut ConfigRegisters) {
+        registers.encode(self.tap_config0);
+        registers.encode(self.tap_config1);
+    }
+}
+
+impl TapConfig {
+    /// Returns the configuration the device has after a reset
+    pub const fn new() -> Self {
+        Self {
+            tap_config0: TapConfig0::RESET,
+            tap_config1: TapConfig1::RESET,
+        }
+    }
+    /// Select axis to use when evaluating interrupt
+    pub const fn with_axis(mut self, axis: Axis) -> Self {
+        self.tap_config0 = self.tap_config0.with_axis(axis);
+        self
+    }
+    /// Select the sensitivity level
+    pub const fn with_sensitivity(mut self, sensitivity: TapSensitivity) -> Self {
+        self.tap_config0 = self.tap_config0.with_sensitivity(sensitivity);
+        self
+    }
+    /// Select the minimum number of samples that must elapse between two peaks for it to be
+    /// considered as a separate tap
+    pub const fn with_min_duration_btn_taps(mut self, duration: MinTapDuration) -> Self {
+        self.tap_config1 = self.tap_config1.with_min_tap_duration(duration);
+        self
+    }
+    /// Select the maximum number of samples that can elapse between two peaks for it to be
+    /// considered as a double tap
+    pub const fn with_max_double_tap_window(mut self, duration: DoubleTapDuration) -> Self {
+        self.tap_config1 = self.tap_config1.with_double_tap_duration(duration);
+        self
+    }
+    /// Select the maximuim number of samples that can elapse between the high and low peak of a tap
+    /// for it to be considered a tap
+    pub const fn with_max_tap_duration(mut self, duration: MaxTapDuration) -> Self {
+        self.tap_config1 = self.tap_config1.with_max_tap_duration(duration);
+        self
+    }
+}
+
 /// Configure Advanced Tap Interrupt Settings
 ///
 /// - Set the axis evaluated for the interrupt trigger condition using [`with_axis()`](TapConfigBuilder::with_axis)
@@ -16,19 +74,28 @@ pub struct TapConfig {
 /// - [MinTapDuration] using [`with_min_duration_btn_taps()`](TapConfigBuilder::with_min_duration_btn_taps)
 /// - [DoubleTapDuration] using [`with_max_double_tap_window()`](TapConfigBuilder::with_max_double_tap_window)
 /// - [MaxTapDuration] using [`with_max_tap_duration()`](TapConfigBuilder::with_max_tap_duration)
-pub struct TapConfigBuilder<'a, Interface> {
+pub struct TapConfigBuilder<'a, Interface, S = Unchecked> {
     config: TapConfig,
-    device: &'a mut BMA400<Interface>,
+    verify: bool,
+    device: &'a mut BMA400<Interface, S>,
 }
 
-#[cfg(not(feature = "embedded-hal-async"))]
-impl<'a, Interface, E> TapConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> TapConfigBuilder<'a, Interface, S>
 where
-    Interface: crate::blocking::WriteToRegister<Error = E>,
-    E: From<ConfigError>,
+    Interface: crate::blocking::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::blocking::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
-    pub fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes();
+        self.device.end_config_write(previous, result)
+    }
+
+    pub(crate) fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
         let tap1_changes =
             self.device.config.tap_config.tap_config0.bits() != self.config.tap_config0.bits();
         let tap2_changes =
@@ -42,38 +109,44 @@ where
             && tap_changes
         {
             tmp_int_config = tmp_int_config.with_s_tap_int(false).with_d_tap_int(false);
-            self.device.interface.write_register(tmp_int_config)?;
+            self.device.write_register(tmp_int_config, self.verify)?;
         }
         if tap1_changes {
             self.device
-                .interface
-                .write_register(self.config.tap_config0)?;
+                .write_register(self.config.tap_config0, self.verify)?;
             self.device.config.tap_config.tap_config0 = self.config.tap_config0;
         }
         if tap2_changes {
             self.device
-                .interface
-                .write_register(self.config.tap_config1)?;
+                .write_register(self.config.tap_config1, self.verify)?;
             self.device.config.tap_config.tap_config1 = self.config.tap_config1;
         }
         // Re-enable the interrupt, if disabled
         if self.device.config.int_config.get_config1().bits() != tmp_int_config.bits() {
             self.device
-                .interface
-                .write_register(self.device.config.int_config.get_config1())?;
+                .write_register(self.device.config.int_config.get_config1(), self.verify)?;
         }
         Ok(())
     }
 }
 
 #[cfg(feature = "embedded-hal-async")]
-impl<'a, Interface, E> TapConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> TapConfigBuilder<'a, AsyncInterface<Interface>, S>
 where
-    Interface: crate::asynch::WriteToRegister<Error = E>,
-    E: From<ConfigError>,
+    AsyncInterface<Interface>: crate::asynch::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::asynch::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
-    pub async fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub async fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes().await;
+        self.device.end_config_write(previous, result).await
+    }
+
+    pub(crate) async fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
         let tap1_changes =
             self.device.config.tap_config.tap_config0.bits() != self.config.tap_config0.bits();
         let tap2_changes =
@@ -87,50 +160,66 @@ where
             && tap_changes
         {
             tmp_int_config = tmp_int_config.with_s_tap_int(false).with_d_tap_int(false);
-            self.device.interface.write_register(tmp_int_config).await?;
+            self.device
+                .write_register(tmp_int_config, self.verify)
+                .await?;
         }
         if tap1_changes {
             self.device
-                .interface
-                .write_register(self.config.tap_config0)
+                .write_register(self.config.tap_config0, self.verify)
                 .await?;
             self.device.config.tap_config.tap_config0 = self.config.tap_config0;
         }
         if tap2_changes {
             self.device
-                .interface
-                .write_register(self.config.tap_config1)
+                .write_register(self.config.tap_config1, self.verify)
                 .await?;
             self.device.config.tap_config.tap_config1 = self.config.tap_config1;
         }
         // Re-enable the interrupt, if disabled
         if self.device.config.int_config.get_config1().bits() != tmp_int_config.bits() {
             self.device
-                .interface
-                .write_register(self.device.config.int_config.get_config1())
+                .write_register(self.device.config.int_config.get_config1(), self.verify)
                 .await?;
         }
         Ok(())
     }
 }
 
-impl<'a, Interface> TapConfigBuilder<'a, Interface> {
-    pub(crate) fn new(device: &'_ mut BMA400<Interface>) -> TapConfigBuilder<'_, Interface> {
+impl<'a, Interface, S> TapConfigBuilder<'a, Interface, S> {
+    pub(crate) fn new(device: &'_ mut BMA400<Interface, S>) -> TapConfigBuilder<'_, Interface, S> {
         TapConfigBuilder {
             config: device.config.tap_config.clone(),
+            verify: device.verify_writes,
+            device,
+        }
+    }
+    /// Start from `config` instead of the current device configuration
+    pub(crate) fn from_config(device: &'a mut BMA400<Interface, S>, config: TapConfig) -> Self {
+        TapConfigBuilder {
+            config,
+            verify: device.verify_writes,
             device,
         }
     }
+    /// Read back each register written by [`write()`](TapConfigBuilder::write) and return
+    /// [`BMA400Error::VerifyFailed`] if the device did not accept the value
+    ///
+    /// Defaults to the device setting, see [`set_verify_writes()`](BMA400::set_verify_writes)
+    pub fn with_verify_writes(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
     // TapConfig0
 
     /// Select axis to use when evaluating interrupt
     pub fn with_axis(mut self, axis: Axis) -> Self {
-        self.config.tap_config0 = self.config.tap_config0.with_axis(axis);
+        self.config = self.config.with_axis(axis);
         self
     }
     /// Select the sensitivity level
     pub fn with_sensitivity(mut self, sensitivity: TapSensitivity) -> Self {
-        self.config.tap_config0 = self.config.tap_config0.with_sensitivity(sensitivity);
+        self.config = self.config.with_sensitivity(sensitivity);
         self
     }
 
@@ -139,19 +228,19 @@ impl<'a, Interface> TapConfigBuilder<'a, Interface> {
     /// Select the minimum number of samples that must elapse between two peaks for it to be
     /// considered as a separate tap
     pub fn with_min_duration_btn_taps(mut self, duration: MinTapDuration) -> Self {
-        self.config.tap_config1 = self.config.tap_config1.with_min_tap_duration(duration);
+        self.config = self.config.with_min_duration_btn_taps(duration);
         self
     }
     /// Select the maximum number of samples that can elapse between two peaks for it to be
     /// considered as a double tap
     pub fn with_max_double_tap_window(mut self, duration: DoubleTapDuration) -> Self {
-        self.config.tap_config1 = self.config.tap_config1.with_double_tap_duration(duration);
+        self.config = self.config.with_max_double_tap_window(duration);
         self
     }
     /// Select the maximuim number of samples that can elapse between the high and low peak of a tap
     /// for it to be considered a tap
     pub fn with_max_tap_duration(mut self, duration: MaxTapDuration) -> Self {
-        self.config.tap_config1 = self.config.tap_config1.with_max_tap_duration(duration);
+        self.config = self.config.with_max_tap_duration(duration);
         self
     }
 }
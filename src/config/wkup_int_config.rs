@@ -1,10 +1,17 @@
+#[cfg(feature = "embedded-hal-async")]
+use crate::AsyncInterface;
 use crate::{
-    BMA400, WakeupIntRefMode,
+    BMA400, BMA400Error, ConfigError, ConfigErrors, WakeupIntRefMode,
+    config::{ConfigRegisters, clamp_u8, to_steps},
     registers::{
         WakeupIntConfig0, WakeupIntConfig1, WakeupIntConfig2, WakeupIntConfig3, WakeupIntConfig4,
     },
+    typestate::Unchecked,
 };
 
+/// Wake-up Interrupt settings
+///
+/// Set with the same methods as [WakeupIntConfigBuilder], without a device
 #[derive(Clone, Default)]
 pub struct WakeupIntConfig {
     wkup_int_config0: WakeupIntConfig0,
@@ -12,17 +19,108 @@ pub struct WakeupIntConfig {
     wkup_int_config2: WakeupIntConfig2,
     wkup_int_config3: WakeupIntConfig3,
     wkup_int_config4: WakeupIntConfig4,
+    adjusted: ConfigErrors,
 }
 
 impl WakeupIntConfig {
-    pub fn is_int_en(&self) -> bool {
+    pub(crate) fn from_registers(registers: &ConfigRegisters) -> Self {
+        Self {
+            wkup_int_config0: registers.decode(),
+            wkup_int_config1: registers.decode(),
+            wkup_int_config2: registers.decode(),
+            wkup_int_config3: registers.decode(),
+            wkup_int_config4: registers.decode(),
+            adjusted: ConfigErrors::new(),
+        }
+    }
+    pub(crate) fn to_registers(&self, registers: &mut ConfigRegisters) {
+        registers.encode(self.wkup_int_config0);
+        registers.encode(self.wkup_int_config1);
+        registers.encode(self.wkup_int_config2);
+        registers.encode(self.wkup_int_config3);
+        registers.encode(self.wkup_int_config4);
+    }
+    pub(crate) fn adjusted(&self) -> ConfigErrors {
+        self.adjusted
+    }
+
+    pub(crate) fn is_int_en(&self) -> bool {
         self.wkup_int_config0.wkup_int_en()
     }
-    pub fn get_config0(&self) -> WakeupIntConfig0 {
+    pub(crate) fn get_config0(&self) -> WakeupIntConfig0 {
         self.wkup_int_config0
     }
 }
 
+impl WakeupIntConfig {
+    /// Returns the configuration the device has after a reset
+    pub const fn new() -> Self {
+        Self {
+            wkup_int_config0: WakeupIntConfig0::RESET,
+            wkup_int_config1: WakeupIntConfig1::RESET,
+            wkup_int_config2: WakeupIntConfig2::RESET,
+            wkup_int_config3: WakeupIntConfig3::RESET,
+            wkup_int_config4: WakeupIntConfig4::RESET,
+            adjusted: ConfigErrors::new(),
+        }
+    }
+    /// Set Reference mode for the Wake-up Interrupt
+    pub const fn with_ref_mode(mut self, mode: WakeupIntRefMode) -> Self {
+        self.wkup_int_config0 = self.wkup_int_config0.with_reference_mode(mode);
+        self
+    }
+    /// Number of consecutive samples that must exceed reference acceleration + / - threshold before
+    /// interrupt is triggered.
+    ///
+    /// This value is clamped to \[1, 8\], or in strict mode
+    /// [`ConfigError::WkupIntNumSamplesOutOfRange`] is returned when writing
+    pub const fn with_num_samples(mut self, num_samples: u8) -> Self {
+        let adjusted = num_samples < 1 || num_samples > 8;
+        self.adjusted = self
+            .adjusted
+            .with(ConfigError::WkupIntNumSamplesOutOfRange, adjusted);
+        self.wkup_int_config0 = self
+            .wkup_int_config0
+            .with_num_samples(clamp_u8(num_samples, 1, 8) - 1);
+        self
+    }
+    /// Select the axes to be used in evaluating the wake-up interrupt condition ()
+    pub const fn with_axes(mut self, x_en: bool, y_en: bool, z_en: bool) -> Self {
+        self.wkup_int_config0 = self
+            .wkup_int_config0
+            .with_x_axis(x_en)
+            .with_y_axis(y_en)
+            .with_z_axis(z_en);
+        self
+    }
+    /// Set the amount by which the measured acceleration must exceed the reference acceleration
+    /// before the interrupt is triggered.
+    ///
+    /// This threshold has unsigned 8-bit resolution corresponding to the upper 8 bits of a 12bit
+    /// acceleration (<< 4).
+    ///
+    /// The evaluated condition is abs(measured - reference) > (threshold << 4) for _any_ enabled
+    /// axis (logic OR). Setting it in milli-g needs the device's [Scale](crate::Scale), so is
+    /// only available on [WakeupIntConfigBuilder]
+    pub const fn with_threshold(mut self, threshold: u8) -> Self {
+        self.wkup_int_config1 = self.wkup_int_config1.with_threshold(threshold);
+        self
+    }
+    /// Manually set the reference acceleration for the x,y,z axes (use with
+    /// [WakeupIntRefMode::Manual])
+    ///
+    /// these values have signed 8-bit resolution corresponding to the upper 8 bits of a 12-bit
+    /// acceleration (<< 4)
+    ///
+    /// In order for an axis to be evaluated it must be enabled using `with_axes()`
+    pub const fn with_ref_accel(mut self, x_ref: i8, y_ref: i8, z_ref: i8) -> Self {
+        self.wkup_int_config2 = self.wkup_int_config2.with_x_ref(x_ref.to_le_bytes()[0]);
+        self.wkup_int_config3 = self.wkup_int_config3.with_y_ref(y_ref.to_le_bytes()[0]);
+        self.wkup_int_config4 = self.wkup_int_config4.with_z_ref(z_ref.to_le_bytes()[0]);
+        self
+    }
+}
+
 /// Configure Wake-up Interrupt settings
 ///
 /// - [WakeupIntRefMode] using [`with_ref_mode()`](WakeupIntConfigBuilder::with_ref_mode)
@@ -30,18 +128,30 @@ impl WakeupIntConfig {
 /// - Enable / Disable axes to be evaluated against the condition using [`with_axes()`](WakeupIntConfigBuilder::with_axes)
 /// - Set the interrupt trigger threshold using [`with_threshold()`](WakeupIntConfigBuilder::with_threshold)
 /// - Set the reference acceleration using [`with_ref_accel()`](WakeupIntConfigBuilder::with_ref_accel)
-pub struct WakeupIntConfigBuilder<'a, Interface> {
+pub struct WakeupIntConfigBuilder<'a, Interface, S = Unchecked> {
     config: WakeupIntConfig,
-    device: &'a mut BMA400<Interface>,
+    verify: bool,
+    device: &'a mut BMA400<Interface, S>,
 }
 
-#[cfg(not(feature = "embedded-hal-async"))]
-impl<'a, Interface, E> WakeupIntConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> WakeupIntConfigBuilder<'a, Interface, S>
 where
-    Interface: crate::blocking::WriteToRegister<Error = E>,
+    Interface: crate::blocking::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::blocking::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
-    pub fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes();
+        self.device.end_config_write(previous, result)
+    }
+
+    pub(crate) fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
+        self.device
+            .check_strict(|config| config.wkup_int_config = self.config.clone())?;
         let has_wkup_config0_changes = self.device.config.wkup_int_config.wkup_int_config0.bits()
             != self.config.wkup_int_config0.bits();
         let has_wkup_config1_changes = self.device.config.wkup_int_config.wkup_int_config1.bits()
@@ -60,7 +170,7 @@ where
 
         // Disable the interrupt
         if self.device.config.wkup_int_config.is_int_en() && has_wkup_config_changes {
-            self.device.interface.write_register(
+            self.device.write_register(
                 self.device
                     .config
                     .wkup_int_config
@@ -68,6 +178,7 @@ where
                     .with_x_axis(false)
                     .with_y_axis(false)
                     .with_z_axis(false),
+                self.verify,
             )?;
         }
         // Write the config changes
@@ -75,32 +186,28 @@ where
             != self.config.wkup_int_config1.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.wkup_int_config1)?;
+                .write_register(self.config.wkup_int_config1, self.verify)?;
             self.device.config.wkup_int_config.wkup_int_config1 = self.config.wkup_int_config1;
         }
         if self.device.config.wkup_int_config.wkup_int_config2.bits()
             != self.config.wkup_int_config2.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.wkup_int_config2)?;
+                .write_register(self.config.wkup_int_config2, self.verify)?;
             self.device.config.wkup_int_config.wkup_int_config2 = self.config.wkup_int_config2;
         }
         if self.device.config.wkup_int_config.wkup_int_config3.bits()
             != self.config.wkup_int_config3.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.wkup_int_config3)?;
+                .write_register(self.config.wkup_int_config3, self.verify)?;
             self.device.config.wkup_int_config.wkup_int_config3 = self.config.wkup_int_config3;
         }
         if self.device.config.wkup_int_config.wkup_int_config4.bits()
             != self.config.wkup_int_config4.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.wkup_int_config4)?;
+                .write_register(self.config.wkup_int_config4, self.verify)?;
             self.device.config.wkup_int_config.wkup_int_config4 = self.config.wkup_int_config4;
         }
         // (Re)-enable the interrupt
@@ -108,8 +215,7 @@ where
             != self.config.wkup_int_config0.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.wkup_int_config0)?;
+                .write_register(self.config.wkup_int_config0, self.verify)?;
             self.device.config.wkup_int_config.wkup_int_config0 = self.config.wkup_int_config0;
         }
         Ok(())
@@ -117,12 +223,24 @@ where
 }
 
 #[cfg(feature = "embedded-hal-async")]
-impl<'a, Interface, E> WakeupIntConfigBuilder<'a, Interface>
+impl<'a, Interface, S, E> WakeupIntConfigBuilder<'a, AsyncInterface<Interface>, S>
 where
-    Interface: crate::asynch::WriteToRegister<Error = E>,
+    AsyncInterface<Interface>: crate::asynch::ReadFromRegister<Error = BMA400Error<E>>
+        + crate::asynch::WriteToRegister<Error = BMA400Error<E>>,
 {
     /// Write this configuration to device registers
-    pub async fn write(self) -> Result<(), E> {
+    ///
+    /// If writing fails part way through, the registers already written are restored and the
+    /// previous configuration is kept, see [`BMA400Error::RollbackFailed`]
+    pub async fn write(mut self) -> Result<(), BMA400Error<E>> {
+        let previous = self.device.begin_config_write();
+        let result = self.write_changes().await;
+        self.device.end_config_write(previous, result).await
+    }
+
+    pub(crate) async fn write_changes(&mut self) -> Result<(), BMA400Error<E>> {
+        self.device
+            .check_strict(|config| config.wkup_int_config = self.config.clone())?;
         let has_wkup_config0_changes = self.device.config.wkup_int_config.wkup_int_config0.bits()
             != self.config.wkup_int_config0.bits();
         let has_wkup_config1_changes = self.device.config.wkup_int_config.wkup_int_config1.bits()
@@ -142,7 +260,6 @@ where
         // Disable the interrupt
         if self.device.config.wkup_int_config.is_int_en() && has_wkup_config_changes {
             self.device
-                .interface
                 .write_register(
                     self.device
                         .config
@@ -151,6 +268,7 @@ where
                         .with_x_axis(false)
                         .with_y_axis(false)
                         .with_z_axis(false),
+                    self.verify,
                 )
                 .await?;
         }
@@ -159,8 +277,7 @@ where
             != self.config.wkup_int_config1.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.wkup_int_config1)
+                .write_register(self.config.wkup_int_config1, self.verify)
                 .await?;
             self.device.config.wkup_int_config.wkup_int_config1 = self.config.wkup_int_config1;
         }
@@ -168,8 +285,7 @@ where
             != self.config.wkup_int_config2.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.wkup_int_config2)
+                .write_register(self.config.wkup_int_config2, self.verify)
                 .await?;
             self.device.config.wkup_int_config.wkup_int_config2 = self.config.wkup_int_config2;
         }
@@ -177,8 +293,7 @@ where
             != self.config.wkup_int_config3.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.wkup_int_config3)
+                .write_register(self.config.wkup_int_config3, self.verify)
                 .await?;
             self.device.config.wkup_int_config.wkup_int_config3 = self.config.wkup_int_config3;
         }
@@ -186,8 +301,7 @@ where
             != self.config.wkup_int_config4.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.wkup_int_config4)
+                .write_register(self.config.wkup_int_config4, self.verify)
                 .await?;
             self.device.config.wkup_int_config.wkup_int_config4 = self.config.wkup_int_config4;
         }
@@ -196,8 +310,7 @@ where
             != self.config.wkup_int_config0.bits()
         {
             self.device
-                .interface
-                .write_register(self.config.wkup_int_config0)
+                .write_register(self.config.wkup_int_config0, self.verify)
                 .await?;
             self.device.config.wkup_int_config.wkup_int_config0 = self.config.wkup_int_config0;
         }
@@ -205,38 +318,53 @@ where
     }
 }
 
-impl<'a, Interface> WakeupIntConfigBuilder<'a, Interface> {
-    pub(crate) fn new(device: &'a mut BMA400<Interface>) -> WakeupIntConfigBuilder<'a, Interface> {
+impl<'a, Interface, S> WakeupIntConfigBuilder<'a, Interface, S> {
+    pub(crate) fn new(
+        device: &'a mut BMA400<Interface, S>,
+    ) -> WakeupIntConfigBuilder<'a, Interface, S> {
         WakeupIntConfigBuilder {
             config: device.config.wkup_int_config.clone(),
+            verify: device.verify_writes,
             device,
         }
     }
+    /// Start from `config` instead of the current device configuration
+    pub(crate) fn from_config(
+        device: &'a mut BMA400<Interface, S>,
+        config: WakeupIntConfig,
+    ) -> Self {
+        WakeupIntConfigBuilder {
+            config,
+            verify: device.verify_writes,
+            device,
+        }
+    }
+    /// Read back each register written by [`write()`](WakeupIntConfigBuilder::write) and return
+    /// [`BMA400Error::VerifyFailed`] if the device did not accept the value
+    ///
+    /// Defaults to the device setting, see [`set_verify_writes()`](BMA400::set_verify_writes)
+    pub fn with_verify_writes(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
     // WkupIntConfig0
     /// Set Reference mode for the Wake-up Interrupt
     pub fn with_ref_mode(mut self, mode: WakeupIntRefMode) -> Self {
-        self.config.wkup_int_config0 = self.config.wkup_int_config0.with_reference_mode(mode);
+        self.config = self.config.with_ref_mode(mode);
         self
     }
     /// Number of consecutive samples that must exceed reference acceleration + / - threshold before
     /// interrupt is triggered.
     ///
-    /// This value is clamped to \[1, 8\]
+    /// This value is clamped to \[1, 8\], or in strict mode
+    /// [`ConfigError::WkupIntNumSamplesOutOfRange`] is returned when writing
     pub fn with_num_samples(mut self, num_samples: u8) -> Self {
-        self.config.wkup_int_config0 = self
-            .config
-            .wkup_int_config0
-            .with_num_samples(num_samples.clamp(1, 8) - 1);
+        self.config = self.config.with_num_samples(num_samples);
         self
     }
     /// Select the axes to be used in evaluating the wake-up interrupt condition ()
     pub fn with_axes(mut self, x_en: bool, y_en: bool, z_en: bool) -> Self {
-        self.config.wkup_int_config0 = self
-            .config
-            .wkup_int_config0
-            .with_x_axis(x_en)
-            .with_y_axis(y_en)
-            .with_z_axis(z_en);
+        self.config = self.config.with_axes(x_en, y_en, z_en);
         self
     }
     // WkupIntConfig1
@@ -250,9 +378,26 @@ impl<'a, Interface> WakeupIntConfigBuilder<'a, Interface> {
     /// The evaluated condition is abs(measured - reference) > (threshold << 4) for _any_ enabled
     /// axis (logic OR).
     pub fn with_threshold(mut self, threshold: u8) -> Self {
-        self.config.wkup_int_config1 = self.config.wkup_int_config1.with_threshold(threshold);
+        self.config = self.config.with_threshold(threshold);
         self
     }
+    /// Set the threshold in milli-g, converted using the current [Scale](crate::Scale)
+    ///
+    /// Each step is 1/256 of the full range (e.g. 31.25 milli-g at ±4g), the value is rounded to
+    /// the nearest step. Returns [`ConfigError::ThresholdOutOfRange`] above 255 steps, so set
+    /// the scale before the threshold. Only available on the builder, as the step size depends
+    /// on the device
+    pub fn with_threshold_mg(self, threshold: u16) -> Result<Self, ConfigError> {
+        let full_range_mg = self.device.config.acc_config.scale().full_range_mg();
+        let threshold = to_steps(
+            threshold.into(),
+            full_range_mg,
+            256,
+            255,
+            ConfigError::ThresholdOutOfRange,
+        )?;
+        Ok(self.with_threshold(threshold as u8))
+    }
 
     // WkupIntConfig2 / WkupIntConfig3 / WkupIntConfig4
 
@@ -264,18 +409,7 @@ impl<'a, Interface> WakeupIntConfigBuilder<'a, Interface> {
     ///
     /// In order for an axis to be evaluated it must be enabled using `with_axes()`
     pub fn with_ref_accel(mut self, x_ref: i8, y_ref: i8, z_ref: i8) -> Self {
-        self.config.wkup_int_config2 = self
-            .config
-            .wkup_int_config2
-            .with_x_ref(x_ref.to_le_bytes()[0]);
-        self.config.wkup_int_config3 = self
-            .config
-            .wkup_int_config3
-            .with_y_ref(y_ref.to_le_bytes()[0]);
-        self.config.wkup_int_config4 = self
-            .config
-            .wkup_int_config4
-            .with_z_ref(z_ref.to_le_bytes()[0]);
+        self.config = self.config.with_ref_accel(x_ref, y_ref, z_ref);
         self
     }
 }
@@ -283,7 +417,7 @@ impl<'a, Interface> WakeupIntConfigBuilder<'a, Interface> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tests::get_test_device;
+    use crate::{Scale, tests::get_test_device};
     #[test]
     fn test_ref_mode() {
         let mut device = get_test_device();
@@ -325,6 +459,22 @@ mod tests {
         assert_eq!(builder.config.wkup_int_config1.bits(), 0x00);
     }
     #[test]
+    fn test_threshold_mg() {
+        let mut device = get_test_device();
+        // 31.25mg steps at 4g
+        let builder = device.config_wkup_int().with_threshold_mg(1000).unwrap();
+        assert_eq!(builder.config.wkup_int_config1.bits(), 32);
+        let mut device = get_test_device();
+        device.config.acc_config = device.config.acc_config.with_scale(Scale::Range2G);
+        // 15.625mg steps at 2g
+        let builder = device.config_wkup_int().with_threshold_mg(1000).unwrap();
+        assert_eq!(builder.config.wkup_int_config1.bits(), 64);
+        assert!(matches!(
+            builder.with_threshold_mg(4000),
+            Err(ConfigError::ThresholdOutOfRange)
+        ));
+    }
+    #[test]
     fn test_ref_accel() {
         let mut device = get_test_device();
         let builder = device.config_wkup_int();
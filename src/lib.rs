@@ -47,8 +47,10 @@
 //! let mut accelerometer = BMA400::new_spi(&mut spi).unwrap();
 //! # spi.done();
 //! ```
+//! Other transports (e.g. a sensor hub or USB bridge) - implement [`RegisterInterface`] and use
+//! [`BMA400::new_with_interface()`]
 //!
-//! From here it's the same API for both:
+//! From here it's the same API for all of them:
 //! ```
 //! # use embedded_hal_mock::eh1::spi::{Mock, Transaction};
 //! # use bma400::{
@@ -66,10 +68,11 @@
 //! #   Transaction::read_vec(vec![0x90]),
 //! #   Transaction::transaction_end(),
 //! #   Transaction::transaction_start(),
-//! #   Transaction::write_vec(vec![0x19, 0x02]),
+//! #   Transaction::write_vec(vec![0x19, 0x02, 0x09]),
 //! #   Transaction::transaction_end(),
 //! #   Transaction::transaction_start(),
-//! #   Transaction::write_vec(vec![0x1A, 0x09]),
+//! #   Transaction::write_vec(vec![0x83, 0x00]),
+//! #   Transaction::read_vec(vec![0x14]),
 //! #   Transaction::transaction_end(),
 //! #   Transaction::transaction_start(),
 //! #   Transaction::write_vec(vec![0x84, 0x00]),
@@ -95,13 +98,20 @@
 //! }
 //! # spi.done();
 //! ```
+//! The power mode and ODR can also be tracked in the type of the device, making the methods
+//! they don't support unavailable, see [`typestate`]
+//!
 //! # Features
 //! BMA400 can currently be compiled with the following feature flags:
 //! - i2c: Use I²C
 //! - spi: Use SPI
-//! - float: Enable functions returning floating point values. Currently just `get_temp_celsius()`
-//! - embedded-hal-async: Swaps blocking API for async API implemented using embedded-hal-async
-//! traits
+//! - float: Enable functions returning floating point values, e.g. `get_temp_celsius()` and
+//!   `get_data_g()`
+//! - embedded-hal-async: Adds an async API implemented using embedded-hal-async traits alongside
+//!   the blocking API, e.g. `BMA400::new_i2c_async()`, and `InterruptListener` for awaiting
+//!   interrupts on the INT1 / INT2 pins
+//! - sim: Adds `bma400::sim`, an emulated device for testing application code without hardware
+//! - defmt: Implements `defmt::Format` for diagnostic types, e.g. `RegisterSnapshot`
 //!
 //! # The Bosch BMA400 Accelerometer
 //! [Datasheet](https://www.bosch-sensortec.com/media/boschsensortec/downloads/datasheets/bst-bma400-ds000.pdf)
@@ -147,30 +157,356 @@
 #![deny(missing_docs)]
 #![warn(missing_docs, unsafe_code)]
 #![no_std]
-pub(crate) use embedded_hal;
+use core::marker::PhantomData;
 use embedded_hal::delay::DelayNs;
 pub mod types;
-#[cfg(any(feature = "embedded-hal-async"))]
-pub(crate) use embedded_hal_async;
 pub use types::*;
-#[cfg(any(feature = "embedded-hal-async"))]
+#[cfg(feature = "embedded-hal-async")]
 mod asynch;
-#[cfg(not(feature = "embedded-hal-async"))]
+#[cfg(feature = "embedded-hal-async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "embedded-hal-async")))]
+pub use asynch::{InterruptListener, NoPin};
 mod blocking;
 pub mod config;
-use config::Config;
+use config::{Config, DeviceConfig, PendingWrites};
 pub(crate) mod registers;
+use registers::AccConfig0;
+#[cfg(feature = "sim")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sim")))]
+pub mod sim;
+pub mod typestate;
+use typestate::Unchecked;
+
+// Interval between status register reads while waiting on the device
+pub(crate) const STATUS_POLL_US: u32 = 100;
+// Time allowed for the command decoder to become ready
+pub(crate) const CMD_READY_TIMEOUT_US: u32 = 1_000;
+// Upper bound on the time taken to switch power modes
+pub(crate) const POWER_MODE_TIMEOUT_US: u32 = 5_000;
+// Status register reads before giving up on a busy command decoder when there is no timer
+pub(crate) const CMD_READY_POLLS: u32 = 10;
+// Status register reads before giving up on a power mode change when there is no timer
+pub(crate) const POWER_MODE_POLLS: u32 = 100;
+// Time allowed for the device to start up after a soft reset
+pub(crate) const SOFT_RESET_DELAY_MS: u32 = 5;
 
 mod private {
     pub trait Sealed {}
     impl<SPI> Sealed for crate::SPIInterface<SPI> {}
     impl<I2C> Sealed for crate::I2CInterface<I2C> {}
+    impl<T> Sealed for crate::CustomInterface<T> {}
+    #[cfg(feature = "embedded-hal-async")]
+    impl<T> Sealed for crate::AsyncInterface<T> {}
 }
 
 /// A BMA400 device
-pub struct BMA400<T> {
+///
+/// `S` optionally tracks the power mode and ODR at compile time, see [`typestate`]
+pub struct BMA400<T, S = Unchecked> {
     interface: T,
     config: Config,
+    verify_writes: bool,
+    strict_config: bool,
+    // Registers written since the start of the current configuration write
+    written: RegisterSet,
+    // Adjacent registers not yet written to the device
+    pending: PendingWrites,
+    // Settings of the oldest FIFO data not yet handed to a FifoSampleDecoder
+    fifo_settings: FifoSettings,
+    // FIFO settings changes not yet handed to a FifoSampleDecoder
+    fifo_changes: SettingsQueue,
+    reset_detection: ResetDetection,
+    // Register reads since the reset detection flag was last checked
+    reads_since_check: u16,
+    state: PhantomData<S>,
+}
+
+impl<T> BMA400<T> {
+    fn new(interface: T) -> Self {
+        BMA400 {
+            interface,
+            config: Config::default(),
+            verify_writes: false,
+            strict_config: false,
+            written: RegisterSet::default(),
+            pending: PendingWrites::new(),
+            fifo_settings: FifoSettings::default(),
+            fifo_changes: SettingsQueue::default(),
+            reset_detection: ResetDetection::default(),
+            reads_since_check: 0,
+            state: PhantomData,
+        }
+    }
+}
+
+impl<T, S> BMA400<T, S> {
+    /// Moves the device to another typestate, without writing to it
+    pub(crate) fn into_state<S2>(self) -> BMA400<T, S2> {
+        BMA400 {
+            interface: self.interface,
+            config: self.config,
+            verify_writes: self.verify_writes,
+            strict_config: self.strict_config,
+            written: self.written,
+            pending: self.pending,
+            fifo_settings: self.fifo_settings,
+            fifo_changes: self.fifo_changes,
+            reset_detection: self.reset_detection,
+            reads_since_check: self.reads_since_check,
+            state: PhantomData,
+        }
+    }
+
+    /// Read back each configuration register after it is written and return
+    /// [`BMA400Error::VerifyFailed`] if the device did not accept the value. Also checks the
+    /// command error register after each write
+    ///
+    /// Disabled by default. Can be overridden for a single configuration change using
+    /// the `with_verify_writes()` method of each configuration builder
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// # use bma400::{BMA400, BMA400Error, PowerMode};
+    /// # let ADDR = 0b10100;
+    /// # let expected = vec![
+    /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+    /// #        Transaction::write(ADDR, vec![0x19, 0x02]),
+    /// #        Transaction::write_read(ADDR, vec![0x19], vec![0x00]),
+    /// #        Transaction::write(ADDR, vec![0x19, 0x00]),
+    /// #    ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+    /// bma400.set_verify_writes(true);
+    /// let result = bma400.config_accel().with_power_mode(PowerMode::Normal).write();
+    /// // The write was not applied
+    /// assert!(matches!(
+    ///     result,
+    ///     Err(BMA400Error::VerifyFailed { register: 0x19, expected: 0x02, actual: 0x00 })
+    /// ));
+    /// # i2c.done();
+    /// ```
+    pub fn set_verify_writes(&mut self, verify: bool) {
+        self.verify_writes = verify;
+    }
+
+    /// Return a [`ConfigError`] from configuration writes instead of adjusting unsupported
+    /// settings, e.g. clamping a value or replacing a data source the feature can't use.
+    /// Also rejects auto low power and auto wake-up triggers relying on a disabled interrupt
+    ///
+    /// Disabled by default. Nothing is written to the device when a check fails. A builder only
+    /// reports errors its write introduces, not ones already in the configuration
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// # use bma400::{BMA400, BMA400Error, ConfigError, DataSource};
+    /// # let ADDR = 0b10100;
+    /// # let expected = vec![Transaction::write_read(ADDR, vec![0x00], vec![0x90])];
+    /// # let mut i2c = Mock::new(&expected);
+    /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+    /// bma400.set_strict_config(true);
+    /// let result = bma400.config_fifo().with_watermark_thresh(2000).write();
+    /// assert!(matches!(
+    ///     result,
+    ///     Err(BMA400Error::ConfigBuildError(ConfigError::FifoWatermarkOutOfRange))
+    /// ));
+    /// let result = bma400.config_gen1_int().with_src(DataSource::AccFilt2Lp).write();
+    /// assert!(matches!(
+    ///     result,
+    ///     Err(BMA400Error::ConfigBuildError(ConfigError::GenIntInvalidSrc))
+    /// ));
+    /// # i2c.done();
+    /// ```
+    pub fn set_strict_config(&mut self, strict: bool) {
+        self.strict_config = strict;
+    }
+
+    /// Check for unexpected resets of the device, e.g. by a brown-out, and write the stored
+    /// configuration back to the device when one is found
+    ///
+    /// Disabled by default. When a reset is detected, the read that found it returns
+    /// [`BMA400Error::DeviceWasReset`] after the configuration has been restored
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// # use bma400::{BMA400, BMA400Error, PowerMode, ResetDetection};
+    /// # let ADDR = 0b10100;
+    /// # let expected = vec![
+    /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+    /// #        Transaction::write(ADDR, vec![0x19, 0x02]),
+    /// #        Transaction::write_read(ADDR, vec![0x03], vec![0x14]),
+    /// #        Transaction::write_read(ADDR, vec![0x0D], vec![0x01]),
+    /// #        Transaction::write(ADDR, vec![0x19, 0x02]),
+    /// #        Transaction::write_read(ADDR, vec![0x0D], vec![0x00]),
+    /// #        Transaction::write_read(ADDR, vec![0x0E], vec![0x00, 0x00, 0x00]),
+    /// #    ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+    /// bma400.set_reset_detection(ResetDetection::OnInterruptStatus);
+    /// bma400.config_accel().with_power_mode(PowerMode::Normal).write().unwrap();
+    /// // The device lost power, normal mode is restored
+    /// assert!(matches!(bma400.get_interrupts(), Err(BMA400Error::DeviceWasReset)));
+    /// let interrupts = bma400.get_interrupts().unwrap();
+    /// # i2c.done();
+    /// ```
+    pub fn set_reset_detection(&mut self, detection: ResetDetection) {
+        self.reset_detection = detection;
+        self.reads_since_check = 0;
+    }
+
+    /// Compares the configuration registers in `snapshot` with the configuration the driver
+    /// last wrote. The driver's values are on the left of each [RegisterDiff]
+    ///
+    /// Differences are expected where the device changes its own configuration, e.g. the
+    /// power mode after an auto low power or auto wakeup transition
+    ///
+    /// # Examples
+    /// ```
+    /// # use core::cell::RefCell;
+    /// # use bma400::{BMA400, PowerMode, sim::{EmulatedDevice, SharedDevice}};
+    /// # let sim = RefCell::new(EmulatedDevice::new());
+    /// # let mut bma400 = BMA400::new_i2c(SharedDevice::new(&sim)).unwrap();
+    /// bma400.config_accel().with_power_mode(PowerMode::Normal).write().unwrap();
+    /// // The device is reset behind the driver's back
+    /// sim.borrow_mut().power_on_reset();
+    /// let snapshot = bma400.dump_registers().unwrap();
+    /// let diff = bma400.diff_config(&snapshot).next().unwrap();
+    /// assert_eq!("AccConfig0", diff.name);
+    /// assert_eq!(0b10, diff.changed_bits());
+    /// ```
+    pub fn diff_config(&self, snapshot: &RegisterSnapshot) -> RegisterDiffs {
+        snapshot.diff_config(&self.config.to_registers())
+    }
+
+    /// Returns the configuration the driver last wrote, e.g. to change part of it before
+    /// writing it back using `apply_config()`
+    pub fn device_config(&self) -> DeviceConfig {
+        DeviceConfig::from_config(self.config.clone())
+    }
+
+    /// In strict mode, checks the configuration as it will be after a builder writes its
+    /// section, applied to a copy of the current configuration by `patch`
+    ///
+    /// Only errors the write introduces are returned, so a conflict left in another section
+    /// doesn't fail unrelated writes
+    pub(crate) fn check_strict(&self, patch: impl FnOnce(&mut Config)) -> Result<(), ConfigError> {
+        if !self.strict_config {
+            return Ok(());
+        }
+        let mut config = self.config.clone();
+        patch(&mut config);
+        let mut errors = config.strict_errors();
+        errors.difference(self.config.strict_errors());
+        match errors.iter().next() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the reset detection flag should be checked before the next register read
+    pub(crate) fn reset_check_due(&mut self, int_status: bool) -> bool {
+        match self.reset_detection {
+            ResetDetection::Disabled => false,
+            ResetDetection::OnInterruptStatus => int_status,
+            ResetDetection::EveryNthRead(n) => {
+                self.reads_since_check += 1;
+                if self.reads_since_check >= n {
+                    self.reads_since_check = 0;
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    /// Create a [FifoSampleDecoder] for the data currently in the FIFO
+    ///
+    /// The decoder starts from the settings the oldest data in the FIFO was measured with and
+    /// is told about later configuration changes by
+    /// [`read_fifo_samples()`](BMA400::read_fifo_samples)
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// # use bma400::{BMA400, Scale};
+    /// # let ADDR = 0b10100;
+    /// # let expected = vec![
+    /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+    /// #        Transaction::write(ADDR, vec![0x26, 0xE0]),
+    /// #        Transaction::write(ADDR, vec![0x1A, 0x89]),
+    /// #    ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+    /// bma400.config_fifo().with_axes(true, true, true).write().unwrap();
+    /// // Data is written to the FIFO at the default 4g scale...
+    /// bma400.config_accel().with_scale(Scale::Range8G).write().unwrap();
+    /// // ...and at 8g after the control frame that follows
+    /// let decoder = bma400.fifo_sample_decoder();
+    /// assert_eq!(Scale::Range4G, decoder.settings().scale);
+    /// # i2c.done();
+    /// ```
+    pub fn fifo_sample_decoder(&mut self) -> FifoSampleDecoder {
+        let mut decoder = FifoSampleDecoder::new(self.fifo_settings);
+        self.take_fifo_changes(&mut decoder);
+        decoder
+    }
+
+    /// Hands the FIFO settings changes not yet known to `decoder` over to it
+    pub(crate) fn take_fifo_changes(&mut self, decoder: &mut FifoSampleDecoder) {
+        decoder.append_changes(&mut self.fifo_changes);
+        self.fifo_settings = self.config.fifo_settings();
+    }
+
+    /// Forgets the FIFO settings history after the FIFO was emptied
+    pub(crate) fn fifo_flushed(&mut self) {
+        self.fifo_changes.clear();
+        self.fifo_settings = self.config.fifo_settings();
+    }
+
+    /// Queues the FIFO settings to move to at each control frame written by the device as a
+    /// result of changing the configuration from `previous`
+    pub(crate) fn record_fifo_changes(&mut self, previous: &Config) {
+        let acc_config = self.config.acc_config();
+        let fifo_config0 = self.config.fifo_config().get_config0();
+        let power_mode = |config: &Config| {
+            config
+                .acc_config()
+                .get_config0()
+                .intersection(AccConfig0::PWR_MODE)
+        };
+        if fifo_config0.pwr_flush() && power_mode(previous) != power_mode(&self.config) {
+            self.fifo_flushed();
+            return;
+        }
+        let target = self.config.fifo_settings();
+        if !fifo_config0.has_axes() {
+            // No control frame is written, any data written later uses the new settings
+            match self.fifo_changes.last_mut() {
+                Some(settings) => *settings = target,
+                None => self.fifo_settings = target,
+            }
+            return;
+        }
+        // A control frame is written for each register changed, in address order
+        let mut settings = previous.fifo_settings();
+        if settings.filt1_bw != target.filt1_bw {
+            settings.filt1_bw = target.filt1_bw;
+            self.fifo_changes.push(settings);
+        }
+        if previous.acc_config().get_config1() != acc_config.get_config1() {
+            settings.scale = target.scale;
+            settings.odr = target.odr;
+            settings.osr = target.osr;
+            self.fifo_changes.push(settings);
+        }
+        if settings.src != target.src {
+            settings.src = target.src;
+            self.fifo_changes.push(settings);
+        }
+    }
 }
 
 /// I²C Interface wrapper
@@ -204,6 +540,92 @@ impl<SPI> SPIInterface<SPI> {
     }
 }
 
+/// Register access over a custom transport
+///
+/// Implement this to use the BMA400 over a bus other than a directly attached I²C / SPI
+/// peripheral, e.g. through a sensor hub or a USB bridge, then create the device using
+/// [`new_with_interface()`](BMA400::new_with_interface)
+///
+/// # Examples
+/// ```
+/// use bma400::{BMA400, RegisterInterface};
+///
+/// // Forwards register reads / writes to a sensor hub
+/// struct Hub;
+///
+/// impl RegisterInterface for Hub {
+///     type Error = ();
+///
+///     fn read_registers(&mut self, start: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+///         // Send a read request to the hub...
+///         # if start == 0x00 { buffer[0] = 0x90; }
+///         Ok(())
+///     }
+///
+///     fn write_registers(&mut self, start: u8, data: &[u8]) -> Result<(), Self::Error> {
+///         // Send a write request to the hub...
+///         Ok(())
+///     }
+/// }
+///
+/// let mut accelerometer = BMA400::new_with_interface(Hub).unwrap();
+/// ```
+pub trait RegisterInterface {
+    /// Error type of the underlying transport
+    type Error;
+
+    /// Reads `buffer.len()` bytes starting at register `start`
+    ///
+    /// The register address is incremented after each byte, with the exception of
+    /// FIFO data (0x14), which is read repeatedly from the same address
+    fn read_registers(&mut self, start: u8, buffer: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Writes `data` to consecutive registers starting at `start`
+    ///
+    /// `data` is never longer than [MAX_WRITE_LEN] bytes, the size of the configuration
+    /// register block
+    fn write_registers(&mut self, start: u8, data: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// The most bytes the driver writes in a single
+/// [`write_registers()`](RegisterInterface::write_registers) call
+pub const MAX_WRITE_LEN: usize = config::CONFIG_BLOCK_LEN;
+
+/// Custom Interface wrapper
+// Wrapper class to instantiate BMA400 with a user-provided RegisterInterface
+#[derive(Debug)]
+pub struct CustomInterface<T> {
+    interface: T,
+}
+
+impl<T> CustomInterface<T> {
+    /// Consumes the Interface returning the underlying [`RegisterInterface`]
+    pub fn destroy(self) -> T {
+        self.interface
+    }
+}
+
+/// Async Interface wrapper
+// Wrapper class to instantiate BMA400 with an I²C / SPI interface implementing the
+// embedded-hal-async traits. Keeping the async API on a separate type allows it to be
+// compiled alongside the blocking API
+///
+/// Create one using [`new_i2c_async()`](BMA400::new_i2c_async) or
+/// [`new_spi_async()`](BMA400::new_spi_async)
+#[cfg(feature = "embedded-hal-async")]
+#[derive(Debug)]
+pub struct AsyncInterface<T> {
+    interface: T,
+}
+
+#[cfg(feature = "embedded-hal-async")]
+impl<T> AsyncInterface<T> {
+    /// Consumes the Interface returning the underlying [`I2CInterface`] / [`SPIInterface`]
+    pub fn destroy(self) -> T {
+        self.interface
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -233,11 +655,12 @@ mod tests {
         fn write_register<T: ConfigReg>(&mut self, _register: T) -> Result<(), Self::Error> {
             Ok(())
         }
+
+        fn write_registers(&mut self, _start: u8, _data: &[u8]) -> Result<(), Self::Error> {
+            Ok(())
+        }
     }
     pub fn get_test_device() -> BMA400<NoOpInterface> {
-        BMA400 {
-            interface: NoOpInterface,
-            config: Config::default(),
-        }
+        BMA400::new(NoOpInterface)
     }
 }
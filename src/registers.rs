@@ -1,3 +1,5 @@
+use core::fmt;
+
 use crate::{
     GenIntCriterionMode, GenIntLogicMode, GenIntRefMode, Hysteresis,
     types::{
@@ -22,6 +24,10 @@ where
     fn to_byte(&self) -> u8;
 }
 
+pub trait FromByte {
+    fn from_byte(byte: u8) -> Self;
+}
+
 /// RegisterName: Address
 macro_rules! r_register {
     ($name:ident: $address:literal) => {
@@ -41,6 +47,10 @@ macro_rules! cfg_register {
                 $(const $field_name = $bitmask;)+
             }
         }
+        impl $name {
+            /// Value after a reset
+            pub const RESET: Self = Self::from_bits_truncate($default);
+        }
         impl ReadReg for $name {
             const ADDR: u8 = $address;
         }
@@ -51,7 +61,12 @@ macro_rules! cfg_register {
         }
         impl Default for $name {
             fn default() -> Self {
-                Self::from_bits_truncate($default)
+                Self::RESET
+            }
+        }
+        impl FromByte for $name {
+            fn from_byte(byte: u8) -> Self {
+                Self::from_bits_truncate(byte)
             }
         }
     }
@@ -116,6 +131,13 @@ cfg_register! {
 }
 
 impl AccConfig0 {
+    pub const fn filt1_bw(&self) -> Filter1Bandwidth {
+        if self.intersects(Self::FILT_BW) {
+            Filter1Bandwidth::Low
+        } else {
+            Filter1Bandwidth::High
+        }
+    }
     pub const fn with_filt1_bw(self, bandwidth: Filter1Bandwidth) -> Self {
         match bandwidth {
             Filter1Bandwidth::High => self.difference(Self::FILT_BW),
@@ -130,6 +152,15 @@ impl AccConfig0 {
             OversampleRate::OSR3 => self.union(Self::OSR_LP),
         }
     }
+    pub const fn power_mode(&self) -> PowerMode {
+        if self.intersects(Self::PW_CFG1) {
+            PowerMode::Normal
+        } else if self.intersects(Self::PW_CFG0) {
+            PowerMode::LowPower
+        } else {
+            PowerMode::Sleep
+        }
+    }
     pub const fn with_power_mode(self, power_mode: PowerMode) -> Self {
         match power_mode {
             PowerMode::Sleep => self.difference(Self::PWR_MODE),
@@ -173,6 +204,14 @@ impl AccConfig1 {
             Scale::Range16G => self.union(Self::ACC_RANGE),
         }
     }
+    pub const fn osr(&self) -> OversampleRate {
+        match self.intersection(Self::OSR).bits() >> 4 {
+            0x00 => OversampleRate::OSR0,
+            0x01 => OversampleRate::OSR1,
+            0x02 => OversampleRate::OSR2,
+            _ => OversampleRate::OSR3,
+        }
+    }
     pub const fn with_osr(self, osr: OversampleRate) -> Self {
         match osr {
             OversampleRate::OSR0 => self.difference(Self::OSR),
@@ -309,6 +348,10 @@ cfg_register! {
 }
 
 impl IntConfig1 {
+    #[cfg(feature = "embedded-hal-async")]
+    pub const fn latch_int(&self) -> bool {
+        self.intersects(Self::LATCH_INT)
+    }
     pub const fn with_latch_int(self, enabled: bool) -> Self {
         if enabled {
             self.union(Self::LATCH_INT)
@@ -628,6 +671,14 @@ cfg_register! {
 }
 
 impl Int12IOCtrl {
+    #[cfg(feature = "embedded-hal-async")]
+    pub const fn int1_active_high(&self) -> bool {
+        self.intersects(Self::INT1_LV)
+    }
+    #[cfg(feature = "embedded-hal-async")]
+    pub const fn int2_active_high(&self) -> bool {
+        self.intersects(Self::INT2_LV)
+    }
     pub const fn with_int1_cfg(self, config: PinOutputConfig) -> Self {
         match config {
             PinOutputConfig::PushPull(level) => match level {
@@ -668,6 +719,19 @@ cfg_register! {
 }
 
 impl FifoConfig0 {
+    pub const fn has_axes(&self) -> bool {
+        self.intersects(Self::FIFO_X.union(Self::FIFO_Y).union(Self::FIFO_Z))
+    }
+    pub const fn fifo_src(&self) -> DataSource {
+        if self.intersects(Self::FIFO_SRC) {
+            DataSource::AccFilt2
+        } else {
+            DataSource::AccFilt1
+        }
+    }
+    pub const fn pwr_flush(&self) -> bool {
+        self.intersects(Self::PWR_FLUSH)
+    }
     pub const fn with_fifo_z(self, enabled: bool) -> Self {
         if enabled {
             self.union(Self::FIFO_Z)
@@ -1910,7 +1974,7 @@ impl TapConfig1 {
     }
 }
 
-#[cfg(any(feature = "spi", test))]
+#[cfg(any(feature = "spi", feature = "sim", test))]
 cfg_register! {
     InterfaceConfig: 0x7C = 0x00 {
         const SPI3 = 0b0000_0001;
@@ -1956,3 +2020,152 @@ impl ConfigReg for Command {
         }
     }
 }
+
+/// A configuration register value where the register is only known at runtime,
+/// used to restore previous register values
+#[derive(Clone, Copy)]
+pub struct RawConfigReg {
+    addr: u8,
+    byte: u8,
+}
+
+impl RawConfigReg {
+    pub const fn new(addr: u8, byte: u8) -> Self {
+        Self { addr, byte }
+    }
+}
+
+impl ReadReg for RawConfigReg {
+    // Unused, the address is provided by addr()
+    const ADDR: u8 = 0x00;
+    fn addr(&self) -> u8 {
+        self.addr
+    }
+}
+
+impl ConfigReg for RawConfigReg {
+    fn to_byte(&self) -> u8 {
+        self.byte
+    }
+}
+
+/// Name, defined bits and decoded representation of a register
+pub(crate) struct RegisterInfo {
+    pub name: &'static str,
+    pub mask: u8,
+    pub fmt: fn(u8, &mut fmt::Formatter<'_>) -> fmt::Result,
+}
+
+macro_rules! register_info {
+    (
+        read: [$($r_name:literal: $r_addr:literal),+ $(,)?],
+        config: [$($c_name:ident),+ $(,)?] $(,)?
+    ) => {
+        /// Returns the [RegisterInfo] of the register at `addr`, or [None] if it is reserved
+        pub(crate) fn register_info(addr: u8) -> Option<RegisterInfo> {
+            match addr {
+                $($r_addr => Some(RegisterInfo {
+                    name: $r_name,
+                    mask: 0xFF,
+                    fmt: |value, f| write!(f, "{:#04X}", value),
+                }),)+
+                $($c_name::ADDR => Some(RegisterInfo {
+                    name: stringify!($c_name),
+                    mask: $c_name::all().bits(),
+                    fmt: |value, f| fmt::Debug::fmt(&$c_name::from_bits_truncate(value), f),
+                }),)+
+                _ => None,
+            }
+        }
+    };
+}
+
+register_info! {
+    read: [
+        "ChipId": 0x00,
+        "ErrReg": 0x02,
+        "Status": 0x03,
+        "AccXLSB": 0x04,
+        "AccXMSB": 0x05,
+        "AccYLSB": 0x06,
+        "AccYMSB": 0x07,
+        "AccZLSB": 0x08,
+        "AccZMSB": 0x09,
+        "SensorTime0": 0x0A,
+        "SensorTime1": 0x0B,
+        "SensorTime2": 0x0C,
+        "Event": 0x0D,
+        "InterruptStatus0": 0x0E,
+        "InterruptStatus1": 0x0F,
+        "InterruptStatus2": 0x10,
+        "TempData": 0x11,
+        "FifoLength0": 0x12,
+        "FifoLength1": 0x13,
+        "StepCount0": 0x15,
+        "StepCount1": 0x16,
+        "StepCount2": 0x17,
+        "StepStatus": 0x18,
+        // Only defined with SPI support
+        "InterfaceConfig": 0x7C,
+    ],
+    config: [
+        AccConfig0,
+        AccConfig1,
+        AccConfig2,
+        IntConfig0,
+        IntConfig1,
+        Int1Map,
+        Int2Map,
+        Int12Map,
+        Int12IOCtrl,
+        FifoConfig0,
+        FifoConfig1,
+        FifoConfig2,
+        FifoPwrConfig,
+        AutoLowPow0,
+        AutoLowPow1,
+        AutoWakeup0,
+        AutoWakeup1,
+        WakeupIntConfig0,
+        WakeupIntConfig1,
+        WakeupIntConfig2,
+        WakeupIntConfig3,
+        WakeupIntConfig4,
+        OrientChgConfig0,
+        OrientChgConfig1,
+        OrientChgConfig3,
+        OrientChgConfig4,
+        OrientChgConfig5,
+        OrientChgConfig6,
+        OrientChgConfig7,
+        OrientChgConfig8,
+        OrientChgConfig9,
+        Gen1IntConfig0,
+        Gen1IntConfig1,
+        Gen1IntConfig2,
+        Gen1IntConfig3,
+        Gen1IntConfig31,
+        Gen1IntConfig4,
+        Gen1IntConfig5,
+        Gen1IntConfig6,
+        Gen1IntConfig7,
+        Gen1IntConfig8,
+        Gen1IntConfig9,
+        Gen2IntConfig0,
+        Gen2IntConfig1,
+        Gen2IntConfig2,
+        Gen2IntConfig3,
+        Gen2IntConfig31,
+        Gen2IntConfig4,
+        Gen2IntConfig5,
+        Gen2IntConfig6,
+        Gen2IntConfig7,
+        Gen2IntConfig8,
+        Gen2IntConfig9,
+        ActChgConfig0,
+        ActChgConfig1,
+        TapConfig0,
+        TapConfig1,
+        SelfTest,
+    ],
+}
@@ -0,0 +1,713 @@
+//! A software model of the BMA400 register map for host-side testing
+//!
+//! [`EmulatedDevice`] implements the embedded-hal [`I2c`] and [`SpiDevice`] traits, so it can be
+//! passed to any of the constructors in place of a bus. Unlike a transaction script it responds to
+//! whatever register traffic it receives:
+//! - Configuration registers start at their reset values and hold what was written to them
+//! - `FlushFifo`, `ClearStepCount` and `SoftReset` commands are applied
+//! - Acceleration data, the sensor clock and the FIFO are fed from samples injected with
+//!   [`push_sample()`](EmulatedDevice::push_sample)
+//! - Interrupt status is raised for enabled interrupts and, with latching enabled, held until read
+//!
+//! The on-chip filters and interrupt engines are not modelled. Every data source returns the
+//! injected signal, and engine interrupts are raised explicitly with
+//! [`trigger()`](EmulatedDevice::trigger)
+//!
+//! # Examples
+//! ```
+//! use core::cell::RefCell;
+//! use bma400::{BMA400, PowerMode, Scale, sim::{EmulatedDevice, SharedDevice}};
+//!
+//! let sim = RefCell::new(EmulatedDevice::new());
+//! // The driver and the test share the emulated device
+//! let mut accelerometer = BMA400::new_i2c(SharedDevice::new(&sim)).unwrap();
+//! accelerometer
+//!     .config_accel()
+//!     .with_power_mode(PowerMode::Normal)
+//!     .with_scale(Scale::Range2G)
+//!     .write()
+//!     .unwrap();
+//! // Samples are only taken outside of sleep mode
+//! sim.borrow_mut().push_sample(30, 16, 988);
+//! let m = accelerometer.get_data_mg().unwrap();
+//! assert_eq!((30, 16, 988), (m.x, m.y, m.z));
+//! ```
+use crate::{
+    Activity,
+    config::Config,
+    registers::{
+        AccConfig0, AccConfig1, AccXLSB, AutoWakeup1, ChipId, Command, ErrReg, Event, FifoConfig0,
+        FifoConfig1, FifoConfig2, FifoData, FifoLength0, FifoPwrConfig, IntConfig0, IntConfig1,
+        InterfaceConfig, InterruptStatus0, InterruptStatus1, InterruptStatus2, ReadReg, SelfTest,
+        SensorTime0, StatusReg, StepCount0, StepStatus, TapConfig1,
+    },
+};
+use core::cell::RefCell;
+use embedded_hal::{
+    i2c::{self, I2c, NoAcknowledgeSource, SevenBitAddress},
+    spi::{self, SpiDevice},
+};
+
+/// Size of the on-chip FIFO in bytes
+pub const FIFO_CAPACITY: usize = 1024;
+
+const REGISTER_COUNT: usize = 0x80;
+
+// Sensor clock ticks (39.0625µs) per sample at 12.5Hz, halved for each ODR step above it
+const TICKS_12_5HZ: u32 = 2048;
+// Low power mode always samples at 25Hz
+const TICKS_LOW_POWER: u32 = TICKS_12_5HZ / 2;
+
+/// Errors returned by [`EmulatedDevice`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimError {
+    /// An I²C transaction was addressed to a different device
+    NoAcknowledge,
+}
+
+impl i2c::Error for SimError {
+    fn kind(&self) -> i2c::ErrorKind {
+        match self {
+            SimError::NoAcknowledge => i2c::ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address),
+        }
+    }
+}
+
+impl spi::Error for SimError {
+    fn kind(&self) -> spi::ErrorKind {
+        spi::ErrorKind::Other
+    }
+}
+
+/// Interrupt engine events which can be raised with [`trigger()`](EmulatedDevice::trigger)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineEvent {
+    /// Generic interrupt 1
+    Gen1,
+    /// Generic interrupt 2
+    Gen2,
+    /// Orientation change
+    OrientationChange,
+    /// Wake-up from low power mode
+    Wakeup,
+    /// Single tap
+    SingleTap,
+    /// Double tap
+    DoubleTap,
+    /// Activity change on the flagged axes
+    ActivityChange {
+        /// Activity changed on the x-axis
+        x: bool,
+        /// Activity changed on the y-axis
+        y: bool,
+        /// Activity changed on the z-axis
+        z: bool,
+    },
+}
+
+/// An emulated BMA400 implementing the embedded-hal [`I2c`] and [`SpiDevice`] traits
+///
+/// See the [module documentation](crate::sim)
+#[derive(Debug, Clone)]
+pub struct EmulatedDevice {
+    addr: u8,
+    spi_mode: bool,
+    regs: [u8; REGISTER_COUNT],
+    fifo: [u8; FIFO_CAPACITY],
+    fifo_len: usize,
+    // Bytes read past the end of the FIFO during the current transaction
+    overread: usize,
+    sensor_time: u32,
+    step_count: u32,
+}
+
+impl Default for EmulatedDevice {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EmulatedDevice {
+    /// Create a powered-on device responding to the default I²C address (0b010100)
+    pub fn new() -> Self {
+        Self::with_address(0b10100)
+    }
+
+    /// Create a powered-on device responding to the alternate I²C address (0b010101)
+    pub fn new_alt() -> Self {
+        Self::with_address(0b10101)
+    }
+
+    fn with_address(addr: u8) -> Self {
+        let mut device = EmulatedDevice {
+            addr,
+            spi_mode: false,
+            regs: [0u8; REGISTER_COUNT],
+            fifo: [0u8; FIFO_CAPACITY],
+            fifo_len: 0,
+            overread: 0,
+            sensor_time: 0,
+            step_count: 0,
+        };
+        device.reset();
+        device
+    }
+
+    /// Returns the current value of the register at `addr` without the side effects of a bus
+    /// read (e.g. clearing interrupt status)
+    pub fn register(&self, addr: u8) -> u8 {
+        match addr {
+            // Values derived from the emulator state
+            0x0A..=0x0C => (self.sensor_time >> (8 * (addr - SensorTime0::ADDR))) as u8,
+            FifoLength0::ADDR => self.fifo_len as u8,
+            0x13 => (self.fifo_len >> 8) as u8 & 0x07,
+            0x15..=0x17 => (self.step_count >> (8 * (addr - StepCount0::ADDR))) as u8,
+            StatusReg::ADDR => {
+                let int_active = (InterruptStatus0::ADDR..=InterruptStatus2::ADDR)
+                    .any(|addr| self.regs[addr as usize] != 0);
+                self.regs[StatusReg::ADDR as usize] | int_active as u8
+            }
+            _ => self.regs[addr as usize % REGISTER_COUNT],
+        }
+    }
+
+    /// Returns the number of bytes currently held in the FIFO
+    pub fn fifo_len(&self) -> usize {
+        self.fifo_len
+    }
+
+    /// Returns the number of steps counted
+    pub fn step_count(&self) -> u32 {
+        self.step_count
+    }
+
+    /// Takes a sample of the acceleration signal, given in milli-g for each axis
+    ///
+    /// Values are converted using the configured [`Scale`](crate::Scale) and saturate at its
+    /// limits. The sample updates the data registers and sensor clock, is appended to the FIFO
+    /// for the enabled axes, and raises the data ready, FIFO watermark and FIFO full interrupts
+    /// if enabled. Ignored in sleep mode
+    pub fn push_sample(&mut self, x: i16, y: i16, z: i16) {
+        let acc_config0 = AccConfig0::from_bits_truncate(self.regs[AccConfig0::ADDR as usize]);
+        let acc_config1 = AccConfig1::from_bits_truncate(self.regs[AccConfig1::ADDR as usize]);
+        let ticks = match (acc_config0 & (AccConfig0::PW_CFG1 | AccConfig0::PW_CFG0)).bits() {
+            0 => return,
+            1 => TICKS_LOW_POWER,
+            _ => {
+                let odr = (acc_config1 & AccConfig1::ACC_ODR).bits().clamp(0x05, 0x0B);
+                TICKS_12_5HZ >> (odr - 0x05)
+            }
+        };
+        self.clear_unlatched();
+        self.sensor_time = (self.sensor_time + ticks) & 0xFF_FFFF;
+
+        // Acceleration counts, the inverse of Scale::raw_to_mg (4096 LSB over the full range)
+        let shift = (acc_config1 & AccConfig1::ACC_RANGE).bits() >> 6;
+        let full_range_mg = 4000i32 << shift;
+        let counts = [x, y, z].map(|mg| {
+            (i32::from(mg) * 4096 + full_range_mg / 2)
+                .div_euclid(full_range_mg)
+                .clamp(-2048, 2047) as i16
+        });
+        for (i, count) in counts.iter().enumerate() {
+            let [lsb, msb] = count.to_le_bytes();
+            self.regs[AccXLSB::ADDR as usize + 2 * i] = lsb;
+            self.regs[AccXLSB::ADDR as usize + 2 * i + 1] = msb & 0x0F;
+        }
+        self.regs[StatusReg::ADDR as usize] |= 0b1000_0000;
+        self.raise(
+            InterruptStatus0::ADDR,
+            IntConfig0::DRDY_INT_EN.bits(),
+            self.int_config0().contains(IntConfig0::DRDY_INT_EN),
+        );
+
+        self.push_data_frame(counts);
+    }
+
+    /// Adds `steps` to the step counter and raises the step interrupt if enabled
+    pub fn add_steps(&mut self, steps: u32) {
+        if steps == 0 {
+            return;
+        }
+        self.step_count = (self.step_count + steps).min(0xFF_FFFF);
+        let status = if steps == 1 { 0b01 } else { 0b10 };
+        self.raise(
+            InterruptStatus1::ADDR,
+            status,
+            self.int_config1().contains(IntConfig1::STEP_INT),
+        );
+    }
+
+    /// Sets the activity reported by the step counter
+    pub fn set_activity(&mut self, activity: Activity) {
+        self.regs[StepStatus::ADDR as usize] = match activity {
+            Activity::Still => 0x00,
+            Activity::Walk => 0x01,
+            Activity::Run => 0x02,
+        };
+    }
+
+    /// Raises the interrupt status for `event` if its interrupt is enabled
+    pub fn trigger(&mut self, event: EngineEvent) {
+        let int_config0 = self.int_config0();
+        let int_config1 = self.int_config1();
+        let (reg, bits, enabled) = match event {
+            EngineEvent::Gen1 => (
+                InterruptStatus0::ADDR,
+                0b0000_0100,
+                int_config0.contains(IntConfig0::GEN1_INT_EN),
+            ),
+            EngineEvent::Gen2 => (
+                InterruptStatus0::ADDR,
+                0b0000_1000,
+                int_config0.contains(IntConfig0::GEN2_INT_EN),
+            ),
+            EngineEvent::OrientationChange => (
+                InterruptStatus0::ADDR,
+                0b0000_0010,
+                int_config0.contains(IntConfig0::ORIENTCH_INT_EN),
+            ),
+            EngineEvent::Wakeup => (
+                InterruptStatus0::ADDR,
+                0b0000_0001,
+                AutoWakeup1::from_bits_truncate(self.regs[AutoWakeup1::ADDR as usize])
+                    .contains(AutoWakeup1::WKUP_INT),
+            ),
+            EngineEvent::SingleTap => (
+                InterruptStatus1::ADDR,
+                0b0000_0100,
+                int_config1.contains(IntConfig1::S_TAP_INT),
+            ),
+            EngineEvent::DoubleTap => (
+                InterruptStatus1::ADDR,
+                0b0000_1000,
+                int_config1.contains(IntConfig1::D_TAP_INT),
+            ),
+            EngineEvent::ActivityChange { x, y, z } => (
+                InterruptStatus2::ADDR,
+                (z as u8) << 2 | (y as u8) << 1 | x as u8,
+                int_config1.contains(IntConfig1::ACTCH_INT),
+            ),
+        };
+        self.raise(reg, bits, enabled);
+    }
+
+    /// Power-on reset: returns all registers to their reset values, clears the FIFO and step
+    /// counter and sets the reset detected flag
+    pub fn power_on_reset(&mut self) {
+        self.reset();
+        self.spi_mode = false;
+    }
+
+    fn reset(&mut self) {
+        self.regs = [0u8; REGISTER_COUNT];
+        self.regs[ChipId::ADDR as usize] = 0x90;
+        // Command ready
+        self.regs[StatusReg::ADDR as usize] = 0b0001_0000;
+        // Reset detected
+        self.regs[Event::ADDR as usize] = 0x01;
+        let defaults = Config::default().to_registers();
+        for addr in AccConfig0::ADDR..=TapConfig1::ADDR {
+            self.regs[addr as usize] = defaults.get(addr);
+        }
+        self.fifo_len = 0;
+        self.sensor_time = 0;
+        self.step_count = 0;
+    }
+
+    fn int_config0(&self) -> IntConfig0 {
+        IntConfig0::from_bits_truncate(self.regs[IntConfig0::ADDR as usize])
+    }
+
+    fn int_config1(&self) -> IntConfig1 {
+        IntConfig1::from_bits_truncate(self.regs[IntConfig1::ADDR as usize])
+    }
+
+    fn fifo_config0(&self) -> FifoConfig0 {
+        FifoConfig0::from_bits_truncate(self.regs[FifoConfig0::ADDR as usize])
+    }
+
+    fn raise(&mut self, reg: u8, bits: u8, enabled: bool) {
+        if enabled {
+            self.regs[reg as usize] |= bits;
+        }
+    }
+
+    // Without latching, interrupt status is only held until the next sample
+    fn clear_unlatched(&mut self) {
+        if !self.int_config1().contains(IntConfig1::LATCH_INT) {
+            for addr in InterruptStatus0::ADDR..=InterruptStatus2::ADDR {
+                self.regs[addr as usize] = 0;
+            }
+        }
+    }
+
+    fn push_data_frame(&mut self, counts: [i16; 3]) {
+        let fifo_config = self.fifo_config0();
+        let axes = [
+            FifoConfig0::FIFO_X,
+            FifoConfig0::FIFO_Y,
+            FifoConfig0::FIFO_Z,
+        ];
+        if !fifo_config.intersects(axes[0] | axes[1] | axes[2]) {
+            return;
+        }
+        let resolution_12bit = !fifo_config.contains(FifoConfig0::FIFO_8);
+        let mut frame = [0u8; 7];
+        let mut len = 1;
+        frame[0] = 0x80 | (resolution_12bit as u8) << 4;
+        for (i, axis) in axes.iter().enumerate() {
+            if !fifo_config.contains(*axis) {
+                continue;
+            }
+            frame[0] |= 0b10 << i;
+            let count = counts[i] as u16;
+            if resolution_12bit {
+                frame[len] = count as u8 & 0x0F;
+                frame[len + 1] = (count >> 4) as u8;
+                len += 2;
+            } else {
+                frame[len] = (count >> 4) as u8;
+                len += 1;
+            }
+        }
+        self.push_frame(&frame[..len]);
+
+        let watermark = u16::from_le_bytes([
+            self.regs[FifoConfig1::ADDR as usize],
+            self.regs[FifoConfig2::ADDR as usize] & 0x07,
+        ]) as usize;
+        let int_config0 = self.int_config0();
+        if self.fifo_len >= watermark {
+            self.raise(
+                InterruptStatus0::ADDR,
+                0b0100_0000,
+                int_config0.contains(IntConfig0::FWM_INT_EN),
+            );
+        }
+        // Full once there is no room for another frame of this size
+        if FIFO_CAPACITY - self.fifo_len < len {
+            self.raise(
+                InterruptStatus0::ADDR,
+                0b0010_0000,
+                int_config0.contains(IntConfig0::FFULL_INT_EN),
+            );
+        }
+    }
+
+    fn push_control_frame(&mut self, changes: u8) {
+        let fifo_config = self.fifo_config0();
+        if fifo_config.intersects(FifoConfig0::FIFO_X | FifoConfig0::FIFO_Y | FifoConfig0::FIFO_Z) {
+            self.push_frame(&[0x48, changes]);
+        }
+    }
+
+    fn push_frame(&mut self, frame: &[u8]) {
+        if self.fifo_len + frame.len() > FIFO_CAPACITY {
+            if self.fifo_config0().contains(FifoConfig0::FIFO_STOP) {
+                return;
+            }
+            // Discard the oldest frames to make room
+            let mut start = 0;
+            while self.fifo_len - start + frame.len() > FIFO_CAPACITY {
+                start += frame_len(self.fifo[start]);
+            }
+            self.fifo.copy_within(start..self.fifo_len, 0);
+            self.fifo_len -= start;
+        }
+        self.fifo[self.fifo_len..self.fifo_len + frame.len()].copy_from_slice(frame);
+        self.fifo_len += frame.len();
+    }
+
+    fn pop_fifo(&mut self) -> u8 {
+        let read_disabled =
+            FifoPwrConfig::from_bits_truncate(self.regs[FifoPwrConfig::ADDR as usize])
+                .contains(FifoPwrConfig::READ_DISABLE);
+        if self.fifo_len > 0 && !read_disabled {
+            let byte = self.fifo[0];
+            self.fifo.copy_within(1..self.fifo_len, 0);
+            self.fifo_len -= 1;
+            return byte;
+        }
+        // Reading past the end returns a sensor time frame if enabled, then empty frames
+        let mut index = self.overread;
+        self.overread += 1;
+        if self.fifo_config0().contains(FifoConfig0::FIFO_TIME) && !read_disabled {
+            if index < 4 {
+                let time = self.sensor_time.to_le_bytes();
+                return [0xA0, time[0], time[1], time[2]][index];
+            }
+            index -= 4;
+        }
+        if index.is_multiple_of(2) { 0x80 } else { 0x00 }
+    }
+
+    fn read_byte(&mut self, addr: u8) -> u8 {
+        let addr = addr % REGISTER_COUNT as u8;
+        match addr {
+            FifoData::ADDR => self.pop_fifo(),
+            // Cleared on read
+            ErrReg::ADDR | Event::ADDR | 0x0E..=0x10 => {
+                let value = self.register(addr);
+                self.regs[addr as usize] = 0;
+                value
+            }
+            0x04..=0x09 => {
+                self.regs[StatusReg::ADDR as usize] &= !0b1000_0000;
+                self.register(addr)
+            }
+            _ => self.register(addr),
+        }
+    }
+
+    fn write_byte(&mut self, addr: u8, value: u8) {
+        let addr = addr % REGISTER_COUNT as u8;
+        match addr {
+            Command::ADDR => self.command(value),
+            AccConfig0::ADDR..=TapConfig1::ADDR | InterfaceConfig::ADDR | SelfTest::ADDR => {
+                let previous = self.regs[addr as usize];
+                self.regs[addr as usize] = value;
+                self.config_changed(addr, previous, value);
+            }
+            // Read only
+            _ => {}
+        }
+    }
+
+    fn config_changed(&mut self, addr: u8, previous: u8, value: u8) {
+        let changed = previous ^ value;
+        match addr {
+            AccConfig0::ADDR => {
+                let power_mode = AccConfig0::PW_CFG1.bits() | AccConfig0::PW_CFG0.bits();
+                if changed & power_mode != 0 {
+                    let status = &mut self.regs[StatusReg::ADDR as usize];
+                    *status = *status & !0b0000_0110 | (value & power_mode) << 1;
+                    if self.fifo_config0().contains(FifoConfig0::PWR_FLUSH) {
+                        self.fifo_len = 0;
+                    }
+                }
+                if changed & AccConfig0::FILT_BW.bits() != 0 {
+                    self.push_control_frame(0b0100);
+                }
+            }
+            AccConfig1::ADDR if changed != 0 => self.push_control_frame(0b1000),
+            FifoConfig0::ADDR if changed & FifoConfig0::FIFO_SRC.bits() != 0 => {
+                self.push_control_frame(0b0010)
+            }
+            _ => {}
+        }
+    }
+
+    fn command(&mut self, value: u8) {
+        match value {
+            0xB0 => self.fifo_len = 0,
+            0xB1 => self.step_count = 0,
+            0xB6 => self.reset(),
+            // Invalid command
+            _ => self.regs[ErrReg::ADDR as usize] |= 0b0000_0010,
+        }
+    }
+}
+
+// Length of the FIFO frame starting with `header`, including the header
+fn frame_len(header: u8) -> usize {
+    match header {
+        // Sensor time
+        h if h & 0xA0 == 0xA0 => 4,
+        // Control
+        h if h & 0x40 != 0 => 2,
+        h => {
+            let axes = (h >> 1 & 0b111).count_ones() as usize;
+            match axes {
+                // Empty frame
+                0 => 2,
+                n if h & 0x10 != 0 => 1 + 2 * n,
+                n => 1 + n,
+            }
+        }
+    }
+}
+
+impl i2c::ErrorType for EmulatedDevice {
+    type Error = SimError;
+}
+
+impl I2c<SevenBitAddress> for EmulatedDevice {
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if address != self.addr {
+            return Err(SimError::NoAcknowledge);
+        }
+        self.overread = 0;
+        let mut pointer = 0u8;
+        for operation in operations {
+            match operation {
+                i2c::Operation::Write(bytes) => {
+                    if let Some((addr, data)) = bytes.split_first() {
+                        pointer = *addr;
+                        for byte in data {
+                            self.write_byte(pointer, *byte);
+                            pointer = next_addr(pointer);
+                        }
+                    }
+                }
+                i2c::Operation::Read(buffer) => {
+                    for byte in buffer.iter_mut() {
+                        *byte = self.read_byte(pointer);
+                        pointer = next_addr(pointer);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl spi::ErrorType for EmulatedDevice {
+    type Error = SimError;
+}
+
+// Progress through an SPI transaction
+enum SpiState {
+    Command,
+    Dummy(u8),
+    Read(u8),
+    Write(u8),
+}
+
+impl SpiDevice for EmulatedDevice {
+    fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        // The first transaction after power on only switches the interface to SPI
+        if !self.spi_mode {
+            self.spi_mode = true;
+            for operation in operations {
+                match operation {
+                    spi::Operation::Read(buffer) => buffer.fill(0),
+                    spi::Operation::Transfer(read, _) => read.fill(0),
+                    spi::Operation::TransferInPlace(buffer) => buffer.fill(0),
+                    _ => {}
+                }
+            }
+            return Ok(());
+        }
+        self.overread = 0;
+        let mut state = SpiState::Command;
+        for operation in operations {
+            match operation {
+                spi::Operation::Read(buffer) => {
+                    for byte in buffer.iter_mut() {
+                        *byte = self.spi_byte(&mut state, 0);
+                    }
+                }
+                spi::Operation::Write(bytes) => {
+                    for byte in bytes.iter() {
+                        self.spi_byte(&mut state, *byte);
+                    }
+                }
+                spi::Operation::Transfer(read, write) => {
+                    for i in 0..read.len().max(write.len()) {
+                        let miso = self.spi_byte(&mut state, write.get(i).copied().unwrap_or(0));
+                        if let Some(byte) = read.get_mut(i) {
+                            *byte = miso;
+                        }
+                    }
+                }
+                spi::Operation::TransferInPlace(buffer) => {
+                    for byte in buffer.iter_mut() {
+                        *byte = self.spi_byte(&mut state, *byte);
+                    }
+                }
+                spi::Operation::DelayNs(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl EmulatedDevice {
+    // Clocks one byte through the SPI interface, returning the byte sent back
+    fn spi_byte(&mut self, state: &mut SpiState, mosi: u8) -> u8 {
+        match *state {
+            SpiState::Command => {
+                let addr = mosi & 0x7F;
+                *state = if mosi & 0x80 != 0 {
+                    SpiState::Dummy(addr)
+                } else {
+                    SpiState::Write(addr)
+                };
+                0
+            }
+            SpiState::Dummy(addr) => {
+                *state = SpiState::Read(addr);
+                0
+            }
+            SpiState::Read(addr) => {
+                *state = SpiState::Read(next_addr(addr));
+                self.read_byte(addr)
+            }
+            SpiState::Write(addr) => {
+                *state = SpiState::Write(next_addr(addr));
+                self.write_byte(addr, mosi);
+                0
+            }
+        }
+    }
+}
+
+// The register address auto-increments, except for FIFO data
+fn next_addr(addr: u8) -> u8 {
+    if addr == FifoData::ADDR {
+        addr
+    } else {
+        addr.wrapping_add(1) % REGISTER_COUNT as u8
+    }
+}
+
+/// A handle to an [`EmulatedDevice`] shared through a [`RefCell`]
+///
+/// Pass this to the driver to keep access to the device, e.g. to inject samples while
+/// application code holds the driver
+#[derive(Debug, Clone, Copy)]
+pub struct SharedDevice<'a> {
+    device: &'a RefCell<EmulatedDevice>,
+}
+
+impl<'a> SharedDevice<'a> {
+    /// Create a new handle to `device`
+    pub fn new(device: &'a RefCell<EmulatedDevice>) -> Self {
+        SharedDevice { device }
+    }
+}
+
+impl i2c::ErrorType for SharedDevice<'_> {
+    type Error = SimError;
+}
+
+impl I2c<SevenBitAddress> for SharedDevice<'_> {
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        I2c::transaction(&mut *self.device.borrow_mut(), address, operations)
+    }
+}
+
+impl spi::ErrorType for SharedDevice<'_> {
+    type Error = SimError;
+}
+
+impl SpiDevice for SharedDevice<'_> {
+    fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        SpiDevice::transaction(&mut *self.device.borrow_mut(), operations)
+    }
+}
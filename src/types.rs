@@ -1,6 +1,11 @@
 //! The structs and enums making up the driver API
 
+use crate::{
+    config::ConfigRegisters,
+    registers::{AccConfig0, ReadReg, TapConfig1, register_info},
+};
 use bitflags::bitflags;
+use core::fmt;
 /// Error types
 #[derive(Debug)]
 pub enum BMA400Error<InterfaceError> {
@@ -8,10 +13,52 @@ pub enum BMA400Error<InterfaceError> {
     IOError(InterfaceError),
     /// Incorrect configuration
     ConfigBuildError(ConfigError),
-    /// Invalid Chip ID read at initialization
+    /// A [DeviceConfig](crate::config::DeviceConfig) breaks one or more rules, all of which
+    /// are listed
+    InvalidConfig(ConfigErrors),
+    /// The device did not reach the expected state before the timeout elapsed or within the
+    /// allowed number of status reads
+    Timeout,
+    /// Invalid Chip ID read at initialization or after a reset
     ChipIdReadFailed,
+    /// The device did not report a reset after a soft reset command
+    ResetFailed,
+    /// The device was reset since it was last checked, e.g. by a brown-out, see
+    /// [ResetDetection]
+    ///
+    /// The stored configuration has already been written back to the device. The requested
+    /// data was not returned. FIFO data and the step count were lost in the reset
+    DeviceWasReset,
     /// Self-Test Failure
     SelfTestFailedError,
+    /// A register did not hold the expected value when read back after a write
+    VerifyFailed {
+        /// Address of the register
+        register: u8,
+        /// The value written
+        expected: u8,
+        /// The value read back
+        actual: u8,
+    },
+    /// A configuration write failed part way through and the registers already written
+    /// could not all be restored to their previous values
+    ///
+    /// The driver keeps the previous configuration. Once communication is restored, use
+    /// `resync_config()` to read back the actual register values
+    RollbackFailed {
+        /// The error returned while restoring the registers
+        error: InterfaceError,
+        /// The registers that were not restored and are now in an unknown state
+        unknown: RegisterSet,
+    },
+    /// An interrupt pin returned an error while waiting for an interrupt
+    #[cfg(feature = "embedded-hal-async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "embedded-hal-async")))]
+    PinError(embedded_hal::digital::ErrorKind),
+    /// No interrupt is mapped to any of the pins being waited on
+    #[cfg(feature = "embedded-hal-async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "embedded-hal-async")))]
+    NoInterruptPin,
 }
 
 impl<InterfaceError> From<ConfigError> for BMA400Error<InterfaceError> {
@@ -21,7 +68,7 @@ impl<InterfaceError> From<ConfigError> for BMA400Error<InterfaceError> {
 }
 
 /// Errors building Config
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ConfigError {
     /// Interrupt data source ODR must be 100Hz
     Filt1InterruptInvalidODR,
@@ -29,6 +76,334 @@ pub enum ConfigError {
     TapIntEnabledInvalidODR,
     /// FIFO Read attempted with read circuit disabled
     FifoReadWhilePwrDisable,
+    /// The FIFO cannot use [DataSource::AccFilt2Lp] (strict mode only)
+    FifoInvalidSrc,
+    /// The FIFO watermark threshold is above 1024 bytes (strict mode only)
+    FifoWatermarkOutOfRange,
+    /// Generic interrupts cannot use [DataSource::AccFilt2Lp] (strict mode only)
+    GenIntInvalidSrc,
+    /// The activity change interrupt cannot use [DataSource::AccFilt2Lp] (strict mode only)
+    ActChgInvalidSrc,
+    /// The orientation change interrupt cannot use [DataSource::AccFilt1] (strict mode only)
+    OrientChgInvalidSrc,
+    /// A reference acceleration is outside \[-2048, 2047\] (strict mode only)
+    RefAccelOutOfRange,
+    /// The wake-up interrupt number of samples is outside \[1, 8\] (strict mode only)
+    WkupIntNumSamplesOutOfRange,
+    /// The auto low power timeout is above 4095 (strict mode only)
+    AutoLpTimeoutOutOfRange,
+    /// The auto wake-up period is above 4095 (strict mode only)
+    AutoWakeupPeriodOutOfRange,
+    /// Auto low power is triggered by Generic Interrupt 1, which is disabled (strict mode only)
+    AutoLpGen1IntDisabled,
+    /// The auto low power timeout is reset by Generic Interrupt 2, which is disabled (strict
+    /// mode only)
+    AutoLpGen2IntDisabled,
+    /// Auto wake-up is triggered by the wake-up interrupt, which has no axis enabled (strict
+    /// mode only)
+    AutoWakeupIntDisabled,
+    /// A threshold given in milli-g is too large for the register (at the current scale for the
+    /// wake-up interrupt)
+    ThresholdOutOfRange,
+    /// A duration given in milliseconds is too long for the register at the ODR used
+    DurationOutOfRange,
+    /// Auto low power or auto wake-up is enabled, so the device can change its power mode on
+    /// its own (typestate transitions only)
+    AutoPowerModeEnabled,
+}
+
+impl ConfigError {
+    const ALL: [ConfigError; 18] = [
+        ConfigError::Filt1InterruptInvalidODR,
+        ConfigError::TapIntEnabledInvalidODR,
+        ConfigError::FifoReadWhilePwrDisable,
+        ConfigError::FifoInvalidSrc,
+        ConfigError::FifoWatermarkOutOfRange,
+        ConfigError::GenIntInvalidSrc,
+        ConfigError::ActChgInvalidSrc,
+        ConfigError::OrientChgInvalidSrc,
+        ConfigError::RefAccelOutOfRange,
+        ConfigError::WkupIntNumSamplesOutOfRange,
+        ConfigError::AutoLpTimeoutOutOfRange,
+        ConfigError::AutoWakeupPeriodOutOfRange,
+        ConfigError::AutoLpGen1IntDisabled,
+        ConfigError::AutoLpGen2IntDisabled,
+        ConfigError::AutoWakeupIntDisabled,
+        ConfigError::ThresholdOutOfRange,
+        ConfigError::DurationOutOfRange,
+        ConfigError::AutoPowerModeEnabled,
+    ];
+}
+
+/// A set of [ConfigError]s
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConfigErrors {
+    bits: u32,
+}
+
+impl ConfigErrors {
+    pub(crate) const fn new() -> Self {
+        Self { bits: 0 }
+    }
+    pub(crate) fn insert(&mut self, error: ConfigError) {
+        self.bits |= 1 << error as u32;
+    }
+    /// Adds `error` to the set if `present`, otherwise removes it, for use in const setters
+    pub(crate) const fn with(self, error: ConfigError, present: bool) -> Self {
+        let bit = 1 << error as u32;
+        Self {
+            bits: if present {
+                self.bits | bit
+            } else {
+                self.bits & !bit
+            },
+        }
+    }
+    pub(crate) fn union(&mut self, other: ConfigErrors) {
+        self.bits |= other.bits;
+    }
+    pub(crate) fn difference(&mut self, other: ConfigErrors) {
+        self.bits &= !other.bits;
+    }
+    /// Returns `true` if `error` is in the set
+    pub fn contains(&self, error: ConfigError) -> bool {
+        self.bits & (1 << error as u32) != 0
+    }
+    /// Returns `true` if the set contains no errors
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+    /// Returns the number of errors in the set
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+    /// Returns an iterator over the errors in the set
+    pub fn iter(&self) -> impl Iterator<Item = ConfigError> + '_ {
+        ConfigError::ALL
+            .into_iter()
+            .filter(|error| self.contains(*error))
+    }
+}
+
+/// When the driver checks the reset detection flag for an unexpected reset of the device,
+/// e.g. by a brown-out
+///
+/// When a reset is detected, the stored configuration is written back to the device and
+/// the call returns [`BMA400Error::DeviceWasReset`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResetDetection {
+    /// Never check
+    #[default]
+    Disabled,
+    /// Check before every `n`th register read
+    EveryNthRead(u16),
+    /// Check whenever the interrupt status is read
+    OnInterruptStatus,
+}
+
+/// A set of configuration registers (0x19 - 0x58), identified by their address
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RegisterSet {
+    bits: u64,
+}
+
+impl RegisterSet {
+    const FIRST: u8 = 0x19;
+    const LAST: u8 = 0x58;
+
+    pub(crate) fn insert(&mut self, addr: u8) {
+        if (Self::FIRST..=Self::LAST).contains(&addr) {
+            self.bits |= 1 << (addr - Self::FIRST);
+        }
+    }
+    /// Returns `true` if the register at `addr` is in the set
+    pub fn contains(&self, addr: u8) -> bool {
+        (Self::FIRST..=Self::LAST).contains(&addr) && self.bits & (1 << (addr - Self::FIRST)) != 0
+    }
+    /// Returns `true` if the set contains no registers
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+    /// Returns the number of registers in the set
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+    /// Returns an iterator over the register addresses in the set, in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        (Self::FIRST..=Self::LAST).filter(|addr| self.contains(*addr))
+    }
+}
+
+/// Number of registers held in a [RegisterSnapshot] (0x00 - 0x7D)
+pub const REGISTER_SPACE_LEN: usize = 0x7E;
+
+/// The value of every register, read using `dump_registers()`
+///
+/// The [Debug] output names each register and decodes the bits of the configuration
+/// registers. Reserved registers and the FIFO data register are left out
+#[derive(Clone, PartialEq, Eq)]
+pub struct RegisterSnapshot {
+    bytes: [u8; REGISTER_SPACE_LEN],
+}
+
+impl RegisterSnapshot {
+    pub(crate) fn new(bytes: [u8; REGISTER_SPACE_LEN]) -> Self {
+        Self { bytes }
+    }
+    /// Returns the value of the register at `addr`
+    pub fn get(&self, addr: u8) -> Option<u8> {
+        self.bytes.get(addr as usize).copied()
+    }
+    /// Returns the register values, indexed by address
+    pub fn as_bytes(&self) -> &[u8; REGISTER_SPACE_LEN] {
+        &self.bytes
+    }
+    /// Returns the registers with defined bits that differ in `other`. `self` is on the
+    /// left of each [RegisterDiff]
+    pub fn diff(&self, other: &RegisterSnapshot) -> RegisterDiffs {
+        RegisterDiffs {
+            left: self.bytes,
+            right: other.bytes,
+            next: 0,
+            end: REGISTER_SPACE_LEN as u8,
+        }
+    }
+    /// Compares the configuration registers with the values in `expected`, which are on
+    /// the left of each [RegisterDiff]
+    pub(crate) fn diff_config(&self, expected: &ConfigRegisters) -> RegisterDiffs {
+        let mut left = self.bytes;
+        for addr in AccConfig0::ADDR..=TapConfig1::ADDR {
+            left[addr as usize] = expected.get(addr);
+        }
+        RegisterDiffs {
+            left,
+            right: self.bytes,
+            next: AccConfig0::ADDR,
+            end: TapConfig1::ADDR + 1,
+        }
+    }
+}
+
+impl fmt::Debug for RegisterSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut snapshot = f.debug_struct("RegisterSnapshot");
+        for (addr, value) in self.bytes.iter().enumerate() {
+            if let Some(info) = register_info(addr as u8) {
+                snapshot.field(info.name, &Decoded(info.fmt, *value));
+            }
+        }
+        snapshot.finish()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for RegisterSnapshot {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "RegisterSnapshot {{");
+        for (addr, value) in self.bytes.iter().enumerate() {
+            if let Some(info) = register_info(addr as u8) {
+                defmt::write!(f, " {=str}: {=u8:#04x}", info.name, *value);
+            }
+        }
+        defmt::write!(f, " }}");
+    }
+}
+
+// Formats a register value using the decoder from its RegisterInfo
+struct Decoded(fn(u8, &mut fmt::Formatter<'_>) -> fmt::Result, u8);
+
+impl fmt::Debug for Decoded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (self.0)(self.1, f)
+    }
+}
+
+/// A register holding different values on either side of a comparison, see
+/// [`RegisterSnapshot::diff()`]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct RegisterDiff {
+    /// Address of the register
+    pub addr: u8,
+    /// Name of the register
+    pub name: &'static str,
+    /// Value on the left hand side
+    pub left: u8,
+    /// Value on the right hand side
+    pub right: u8,
+    mask: u8,
+}
+
+impl RegisterDiff {
+    /// Returns the defined bits of the register that differ
+    pub fn changed_bits(&self) -> u8 {
+        (self.left ^ self.right) & self.mask
+    }
+}
+
+impl fmt::Debug for RegisterDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let decode = register_info(self.addr).map_or(
+            (|value, f| write!(f, "{:#04X}", value)) as fn(u8, &mut fmt::Formatter<'_>) -> _,
+            |info| info.fmt,
+        );
+        f.debug_struct("RegisterDiff")
+            .field(
+                "register",
+                &format_args!("{} ({:#04X})", self.name, self.addr),
+            )
+            .field("left", &Decoded(decode, self.left))
+            .field("right", &Decoded(decode, self.right))
+            .field("changed", &format_args!("{:#010b}", self.changed_bits()))
+            .finish()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for RegisterDiff {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "{=str} ({=u8:#04x}): {=u8:#010b} -> {=u8:#010b}",
+            self.name,
+            self.addr,
+            self.left,
+            self.right
+        );
+    }
+}
+
+/// Iterator over the registers holding different values on either side of a comparison
+#[derive(Clone)]
+pub struct RegisterDiffs {
+    left: [u8; REGISTER_SPACE_LEN],
+    right: [u8; REGISTER_SPACE_LEN],
+    next: u8,
+    end: u8,
+}
+
+impl Iterator for RegisterDiffs {
+    type Item = RegisterDiff;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.next < self.end {
+            let addr = self.next;
+            self.next += 1;
+            let Some(info) = register_info(addr) else {
+                continue;
+            };
+            let (left, right) = (self.left[addr as usize], self.right[addr as usize]);
+            if (left ^ right) & info.mask != 0 {
+                return Some(RegisterDiff {
+                    addr,
+                    name: info.name,
+                    left,
+                    right,
+                    mask: info.mask,
+                });
+            }
+        }
+        None
+    }
 }
 
 /// A sensor Status reading
@@ -63,6 +438,7 @@ impl Status {
 }
 
 /// The Step Interrupt Status
+#[derive(Debug)]
 pub enum StepIntStatus {
     /// No Step Detected
     None,
@@ -82,6 +458,7 @@ pub enum StepIntStatus {
 /// - Generic Interrupt 1 - [`gen1_stat()`](IntStatus0::gen1_stat)
 /// - Orientation Changed - [`orientch_stat()`](IntStatus0::orientch_stat)
 /// - Wakeup Activity Interrupt - [`wkup_stat()`](IntStatus0::wkup_stat)
+#[derive(Debug, Clone, Copy)]
 pub struct IntStatus0 {
     bits: u8,
 }
@@ -131,6 +508,7 @@ impl IntStatus0 {
 /// - Double Tap Interrupt - [`d_tap_stat()`](IntStatus1::d_tap_stat)
 /// - Single Tap Interrupt - [`s_tap_stat()`](IntStatus1::s_tap_stat)
 /// - Step Interrupt - [`step_int_stat()`](IntStatus1::step_int_stat)
+#[derive(Debug, Clone, Copy)]
 pub struct IntStatus1 {
     bits: u8,
 }
@@ -168,6 +546,7 @@ impl IntStatus1 {
 /// - Activity Change Z - [`actch_z_stat()`](IntStatus2::actch_z_stat)
 /// - Activity Change Y - [`actch_y_stat()`](IntStatus2::actch_y_stat)
 /// - Activity Change X - [`actch_x_stat()`](IntStatus2::actch_x_stat)
+#[derive(Debug, Clone, Copy)]
 pub struct IntStatus2 {
     bits: u8,
 }
@@ -195,6 +574,176 @@ impl IntStatus2 {
     }
 }
 
+bitflags! {
+    /// The triggered interrupts from all three interrupt status registers, see
+    /// [`get_interrupts()`](crate::BMA400::get_interrupts)
+    ///
+    /// Use [`events()`](Interrupts::events) to go through them as [InterruptEvent]s
+    pub struct Interrupts: u32 {
+        /// Wake-Up Interrupt
+        const WAKEUP = 1 << 0;
+        /// Orientation Change Interrupt
+        const ORIENTATION_CHANGED = 1 << 1;
+        /// Generic Interrupt 1
+        const GEN1 = 1 << 2;
+        /// Generic Interrupt 2
+        const GEN2 = 1 << 3;
+        /// The Interrupt Engine could not complete calculation of all enabled interrupts in time
+        const ENGINE_OVERRUN = 1 << 4;
+        /// FIFO Full Interrupt
+        const FIFO_FULL = 1 << 5;
+        /// FIFO Watermark Interrupt
+        const FIFO_WATERMARK = 1 << 6;
+        /// Data Ready Interrupt
+        const DATA_READY = 1 << 7;
+        /// Step Interrupt, one step detected
+        const STEP = 1 << 8;
+        /// Step Interrupt, more than one step detected
+        const STEPS = 1 << 9;
+        /// Single Tap Interrupt
+        const SINGLE_TAP = 1 << 10;
+        /// Double Tap Interrupt
+        const DOUBLE_TAP = 1 << 11;
+        /// Activity Change Interrupt along the x-axis
+        const ACTIVITY_CHANGE_X = 1 << 16;
+        /// Activity Change Interrupt along the y-axis
+        const ACTIVITY_CHANGE_Y = 1 << 17;
+        /// Activity Change Interrupt along the z-axis
+        const ACTIVITY_CHANGE_Z = 1 << 18;
+    }
+}
+
+impl Interrupts {
+    // INT_STAT0 - INT_STAT2 (0x0E - 0x10)
+    pub(crate) fn from_status_bytes(bytes: [u8; 3]) -> Self {
+        // The engine overrun bit is repeated in each register
+        let overrun = (bytes[0] | bytes[1] | bytes[2]) & 0b0001_0000;
+        let mut steps = bytes[1] & 0b0000_0011;
+        if steps == 0b11 {
+            steps = 0b10;
+        }
+        Interrupts::from_bits_truncate(
+            u32::from(bytes[0] | overrun)
+                | u32::from(bytes[1] & 0b0000_1100 | steps) << 8
+                | u32::from(bytes[2] & 0b0000_0111) << 16,
+        )
+    }
+    /// Returns the number of steps detected by the Step Interrupt: 0, 1 or 2 for more than one
+    pub fn steps(&self) -> u8 {
+        if self.contains(Self::STEPS) {
+            2
+        } else if self.contains(Self::STEP) {
+            1
+        } else {
+            0
+        }
+    }
+    /// Returns an iterator over the triggered interrupts as [InterruptEvent]s
+    pub fn events(&self) -> InterruptEvents {
+        InterruptEvents {
+            interrupts: *self,
+            index: 0,
+        }
+    }
+}
+
+impl IntoIterator for Interrupts {
+    type Item = InterruptEvent;
+    type IntoIter = InterruptEvents;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.events()
+    }
+}
+
+/// A triggered interrupt, see [`Interrupts::events()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterruptEvent {
+    /// New data is ready to be read
+    DataReady,
+    /// The FIFO fill level is above the watermark
+    FifoWatermark,
+    /// The FIFO is full
+    FifoFull,
+    /// Generic Interrupt 1
+    Gen1,
+    /// Generic Interrupt 2
+    Gen2,
+    /// The orientation changed
+    OrientationChanged,
+    /// Wake-Up Interrupt
+    Wakeup,
+    /// A single tap was detected
+    SingleTap,
+    /// A double tap was detected
+    DoubleTap,
+    /// Steps were detected: 1, or 2 for more than one
+    Step(u8),
+    /// Activity changed along the axes marked `true`
+    ActivityChange {
+        /// x-axis
+        x: bool,
+        /// y-axis
+        y: bool,
+        /// z-axis
+        z: bool,
+    },
+    /// The Interrupt Engine could not complete calculation of all enabled interrupts in time
+    EngineOverrun,
+}
+
+/// An iterator over the [InterruptEvent]s in [Interrupts]
+#[derive(Debug, Clone)]
+pub struct InterruptEvents {
+    interrupts: Interrupts,
+    index: u8,
+}
+
+impl Iterator for InterruptEvents {
+    type Item = InterruptEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let interrupts = self.interrupts;
+        while self.index < 12 {
+            self.index += 1;
+            let event = match self.index {
+                1 if interrupts.contains(Interrupts::DATA_READY) => InterruptEvent::DataReady,
+                2 if interrupts.contains(Interrupts::FIFO_WATERMARK) => {
+                    InterruptEvent::FifoWatermark
+                }
+                3 if interrupts.contains(Interrupts::FIFO_FULL) => InterruptEvent::FifoFull,
+                4 if interrupts.contains(Interrupts::GEN1) => InterruptEvent::Gen1,
+                5 if interrupts.contains(Interrupts::GEN2) => InterruptEvent::Gen2,
+                6 if interrupts.contains(Interrupts::ORIENTATION_CHANGED) => {
+                    InterruptEvent::OrientationChanged
+                }
+                7 if interrupts.contains(Interrupts::WAKEUP) => InterruptEvent::Wakeup,
+                8 if interrupts.contains(Interrupts::SINGLE_TAP) => InterruptEvent::SingleTap,
+                9 if interrupts.contains(Interrupts::DOUBLE_TAP) => InterruptEvent::DoubleTap,
+                10 if interrupts.steps() > 0 => InterruptEvent::Step(interrupts.steps()),
+                11 if interrupts.intersects(
+                    Interrupts::ACTIVITY_CHANGE_X
+                        | Interrupts::ACTIVITY_CHANGE_Y
+                        | Interrupts::ACTIVITY_CHANGE_Z,
+                ) =>
+                {
+                    InterruptEvent::ActivityChange {
+                        x: interrupts.contains(Interrupts::ACTIVITY_CHANGE_X),
+                        y: interrupts.contains(Interrupts::ACTIVITY_CHANGE_Y),
+                        z: interrupts.contains(Interrupts::ACTIVITY_CHANGE_Z),
+                    }
+                }
+                12 if interrupts.contains(Interrupts::ENGINE_OVERRUN) => {
+                    InterruptEvent::EngineOverrun
+                }
+                _ => continue,
+            };
+            return Some(event);
+        }
+        None
+    }
+}
+
 /// A 3-axis acceleration measurement with 3 fields
 ///
 /// x: x-axis data,
@@ -236,6 +785,35 @@ impl Measurement {
             Self::to_i16(bytes[4], bytes[5]) << shift,
         )
     }
+    /// Converts an unscaled measurement, e.g. from
+    /// [`get_unscaled_data()`](crate::BMA400::get_unscaled_data), taken at `scale` to milli-g
+    pub fn to_mg(&self, scale: Scale) -> Measurement {
+        Self::new(
+            scale.raw_to_mg(self.x),
+            scale.raw_to_mg(self.y),
+            scale.raw_to_mg(self.z),
+        )
+    }
+    /// Converts an unscaled measurement, e.g. from
+    /// [`get_unscaled_data()`](crate::BMA400::get_unscaled_data), taken at `scale` to g
+    #[cfg(feature = "float")]
+    pub fn to_g(&self, scale: Scale) -> Acceleration {
+        Acceleration {
+            x: scale.raw_to_g(self.x),
+            y: scale.raw_to_g(self.y),
+            z: scale.raw_to_g(self.z),
+        }
+    }
+    /// Converts an unscaled measurement, e.g. from
+    /// [`get_unscaled_data()`](crate::BMA400::get_unscaled_data), taken at `scale` to m/s²
+    #[cfg(feature = "float")]
+    pub fn to_mps2(&self, scale: Scale) -> Acceleration {
+        Acceleration {
+            x: scale.raw_to_mps2(self.x),
+            y: scale.raw_to_mps2(self.y),
+            z: scale.raw_to_mps2(self.z),
+        }
+    }
     fn to_i16(lsb: u8, msb: u8) -> i16 {
         let clear_rsvd_bits = msb & 0x0F;
         i16::from_le_bytes([
@@ -249,6 +827,76 @@ impl Measurement {
     }
 }
 
+/// A [Measurement] and the [SensorTime] read with it in a single burst, see
+/// [`get_data_with_time()`](crate::BMA400::get_data_with_time)
+#[derive(Debug)]
+pub struct TimedMeasurement {
+    /// The 3-axis reading adjusted for the selected [Scale]
+    pub measurement: Measurement,
+    /// The sensor clock when the reading was taken
+    pub time: SensorTime,
+}
+
+impl TimedMeasurement {
+    // Data registers 0x04 - 0x09 followed by the sensor time 0x0A - 0x0C
+    pub(crate) fn from_bytes(scale: Scale, bytes: &[u8]) -> Self {
+        TimedMeasurement {
+            measurement: Measurement::from_bytes_scaled(scale, &bytes[..6]),
+            time: SensorTime::from_le_bytes([bytes[6], bytes[7], bytes[8]]),
+        }
+    }
+}
+
+/// A [TimedMeasurement] and the event and interrupt status registers read with it in a single
+/// burst, see [`get_data_with_status()`](crate::BMA400::get_data_with_status)
+#[derive(Debug)]
+pub struct DataWithStatus {
+    /// The 3-axis reading and sensor time
+    pub data: TimedMeasurement,
+    /// `true` if a power on reset was detected, see
+    /// [`get_reset_status()`](crate::BMA400::get_reset_status)
+    pub reset: bool,
+    /// The INT_STAT0 register
+    pub int_status0: IntStatus0,
+    /// The INT_STAT1 register
+    pub int_status1: IntStatus1,
+    /// The INT_STAT2 register
+    pub int_status2: IntStatus2,
+}
+
+impl DataWithStatus {
+    // Data registers 0x04 - 0x0C followed by Event 0x0D and the interrupt status 0x0E - 0x10
+    pub(crate) fn from_bytes(scale: Scale, bytes: &[u8]) -> Self {
+        DataWithStatus {
+            data: TimedMeasurement::from_bytes(scale, &bytes[..9]),
+            reset: bytes[9] & 0x01 != 0,
+            int_status0: IntStatus0::new(bytes[10]),
+            int_status1: IntStatus1::new(bytes[11]),
+            int_status2: IntStatus2::new(bytes[12]),
+        }
+    }
+    /// Returns the three interrupt status registers combined as [Interrupts]
+    pub fn interrupts(&self) -> Interrupts {
+        Interrupts::from_status_bytes([
+            self.int_status0.bits,
+            self.int_status1.bits,
+            self.int_status2.bits,
+        ])
+    }
+}
+
+/// A 3-axis acceleration in floating point units, g or m/s² depending on the method returning it
+#[cfg(feature = "float")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Acceleration {
+    /// x-axis acceleration
+    pub x: f32,
+    /// y-axis acceleration
+    pub y: f32,
+    /// z-axis acceleration
+    pub z: f32,
+}
+
 /// The BMA400's Hardware Interrupt Pins, Int1 and Int2
 pub enum InterruptPins {
     /// The interrupt is mapped to neither pin
@@ -280,6 +928,7 @@ pub enum PinOutputConfig {
 /// The Measurement scale of the accelerometer
 ///
 /// 2g/4g/8g/16g
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Scale {
     /// -2g to 2g
     Range2G = 0x00,
@@ -291,12 +940,45 @@ pub enum Scale {
     Range16G = 0x03,
 }
 
+impl Scale {
+    /// Converts a raw 12-bit reading taken at this scale to milli-g, rounded to the nearest milli-g
+    ///
+    /// # Examples
+    /// ```
+    /// # use bma400::Scale;
+    /// assert_eq!(977, Scale::Range2G.raw_to_mg(1000));
+    /// assert_eq!(15992, Scale::Range16G.raw_to_mg(2047));
+    /// ```
+    pub const fn raw_to_mg(self, raw: i16) -> i16 {
+        // 4096 LSB over the full (±) range
+        ((raw as i32 * self.full_range_mg() as i32 + 2048) >> 12) as i16
+    }
+    /// Width of the full (±) range in milli-g
+    pub(crate) const fn full_range_mg(self) -> u32 {
+        4000 << self as u32
+    }
+    /// Converts a raw 12-bit reading taken at this scale to g
+    #[cfg(feature = "float")]
+    pub fn raw_to_g(self, raw: i16) -> f32 {
+        f32::from(raw) * f32::from(4u8 << self as u8) / 4096.0
+    }
+    /// Converts a raw 12-bit reading taken at this scale to m/s²
+    #[cfg(feature = "float")]
+    pub fn raw_to_mps2(self, raw: i16) -> f32 {
+        self.raw_to_g(raw) * STANDARD_GRAVITY
+    }
+}
+
+/// Standard acceleration due to gravity in m/s², used for conversions from g
+#[cfg(feature = "float")]
+pub const STANDARD_GRAVITY: f32 = 9.80665;
+
 /// Data Source Configuration
 ///
 /// Select one of three possible data sources to feed the data registers and the interrupt engine.
 ///
 /// The FIFO buffer can only use either [DataSource::AccFilt1] or [DataSource::AccFilt2]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DataSource {
     /// Selectable [OutputDataRate], choice of two low pass filter bandwidths
     ///
@@ -314,6 +996,7 @@ pub enum DataSource {
 }
 
 /// Bandwidth setting for the low pass filter for AccFilt1 data source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Filter1Bandwidth {
     /// 0.48 x [OutputDataRate] Hz
     High,
@@ -322,6 +1005,7 @@ pub enum Filter1Bandwidth {
 }
 
 /// Output Data Rate in Hz
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OutputDataRate {
     /// 12.5 Hz
     Hz12_5,
@@ -339,7 +1023,22 @@ pub enum OutputDataRate {
     Hz800,
 }
 
-#[derive(Debug)]
+impl OutputDataRate {
+    /// Returns the time between samples in sensor time ticks of 39.0625µs
+    pub const fn period_ticks(self) -> u32 {
+        match self {
+            OutputDataRate::Hz12_5 => 2048,
+            OutputDataRate::Hz25 => 1024,
+            OutputDataRate::Hz50 => 512,
+            OutputDataRate::Hz100 => 256,
+            OutputDataRate::Hz200 => 128,
+            OutputDataRate::Hz400 => 64,
+            OutputDataRate::Hz800 => 32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// Oversample Rate
 ///
 /// Higher values reduce data noise at the cost of power consumption
@@ -377,6 +1076,7 @@ pub enum OversampleRate {
 /// [`PowerMode::Normal`] highest power - All functionality available
 ///
 /// See [p.19 of the datasheet](https://www.bosch-sensortec.com/media/boschsensortec/downloads/datasheets/bst-bma400-ds000.pdf#page=19)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PowerMode {
     /// Sleep Mode: lowest power - no data output, no FIFO Read or Write
     Sleep,
@@ -466,15 +1166,14 @@ impl<'a> Frame<'a> {
         Some(self.data_at_offset(offset, header.resolution_is_12bit()))
     }
     /// If the [FrameType] is [`FrameType::Time`], returns a result containing the sensor time, None otherwise
-    pub fn time(&self) -> Option<u32> {
+    pub fn time(&self) -> Option<SensorTime> {
         if !matches!(self.frame_type(), FrameType::Time) {
             return None;
         }
-        Some(u32::from_le_bytes([
+        Some(SensorTime::from_le_bytes([
             self.slice[1],
             self.slice[2],
             self.slice[3],
-            0,
         ]))
     }
     /// If the [FrameType] is [`FrameType::Control`], returns a result containing whether a FIFO data source change was indicated
@@ -502,6 +1201,33 @@ impl<'a> Frame<'a> {
             None
         }
     }
+    /// If the [Frame] has x-axis data returns the x-axis measurement taken at `scale` in milli-g, None otherwise
+    pub fn x_mg(&self, scale: Scale) -> Option<i16> {
+        self.x().map(|x| scale.raw_to_mg(x))
+    }
+    /// If the [Frame] has y-axis data returns the y-axis measurement taken at `scale` in milli-g, None otherwise
+    pub fn y_mg(&self, scale: Scale) -> Option<i16> {
+        self.y().map(|y| scale.raw_to_mg(y))
+    }
+    /// If the [Frame] has z-axis data returns the z-axis measurement taken at `scale` in milli-g, None otherwise
+    pub fn z_mg(&self, scale: Scale) -> Option<i16> {
+        self.z().map(|z| scale.raw_to_mg(z))
+    }
+    /// If the [Frame] has x-axis data returns the x-axis measurement taken at `scale` in g, None otherwise
+    #[cfg(feature = "float")]
+    pub fn x_g(&self, scale: Scale) -> Option<f32> {
+        self.x().map(|x| scale.raw_to_g(x))
+    }
+    /// If the [Frame] has y-axis data returns the y-axis measurement taken at `scale` in g, None otherwise
+    #[cfg(feature = "float")]
+    pub fn y_g(&self, scale: Scale) -> Option<f32> {
+        self.y().map(|y| scale.raw_to_g(y))
+    }
+    /// If the [Frame] has z-axis data returns the z-axis measurement taken at `scale` in g, None otherwise
+    #[cfg(feature = "float")]
+    pub fn z_g(&self, scale: Scale) -> Option<f32> {
+        self.z().map(|z| scale.raw_to_g(z))
+    }
     fn data_at_offset(&self, offset: usize, resolution_is_12bit: bool) -> i16 {
         let (lsb, msb);
         if resolution_is_12bit {
@@ -526,7 +1252,7 @@ pub enum FrameType {
 }
 
 /// An iterator over the buffer provided to [`read_fifo_frames()`](crate::BMA400::read_fifo_frames)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FifoFrames<'a> {
     index: usize,
     bytes: &'a [u8],
@@ -562,6 +1288,808 @@ impl<'a> Iterator for FifoFrames<'a> {
     }
 }
 
+/// A reading of the 24-bit sensor clock
+///
+/// The clock counts ticks of 39.0625µs and rolls over to zero every 655.36s (about 11
+/// minutes). Readings are compared and subtracted with the rollover taken into account,
+/// assuming they are less than half a rollover period (327.68s) apart. Use a
+/// [SensorTimeExtender] to keep track of time over longer periods
+///
+/// # Examples
+/// ```
+/// use bma400::SensorTime;
+///
+/// let before = SensorTime::from_ticks(0xFFFF00);
+/// let after = SensorTime::from_ticks(0x000100);
+/// // The clock rolled over in between
+/// assert!(after.is_after(before));
+/// assert_eq!(0x200, after - before);
+/// assert_eq!(20_000, after.micros_since(before));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SensorTime(u32);
+
+impl SensorTime {
+    /// Number of ticks before the clock rolls over
+    pub const ROLLOVER_TICKS: u32 = 1 << 24;
+
+    /// Create a reading from a tick count. Only the lowest 24 bits are kept
+    pub const fn from_ticks(ticks: u32) -> Self {
+        SensorTime(ticks & (Self::ROLLOVER_TICKS - 1))
+    }
+    pub(crate) const fn from_le_bytes(bytes: [u8; 3]) -> Self {
+        SensorTime(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]))
+    }
+    /// Returns the reading in ticks of 39.0625µs
+    pub const fn ticks(self) -> u32 {
+        self.0
+    }
+    /// Returns the reading in µs, rolling over every 655.36s
+    pub const fn as_micros(self) -> u32 {
+        Self::ticks_to_micros(self.0 as u64) as u32
+    }
+    /// Converts a number of ticks to µs, rounding down
+    pub const fn ticks_to_micros(ticks: u64) -> u64 {
+        // 1 tick = 625 / 16 µs
+        ticks * 625 / 16
+    }
+    /// Returns the reading `ticks` later, rolling over if needed
+    pub const fn wrapping_add_ticks(self, ticks: u32) -> Self {
+        Self::from_ticks(self.0.wrapping_add(ticks))
+    }
+    /// Returns the reading `ticks` earlier, rolling over if needed
+    pub const fn wrapping_sub_ticks(self, ticks: u32) -> Self {
+        Self::from_ticks(self.0.wrapping_sub(ticks))
+    }
+    /// Returns the number of ticks elapsed since `earlier`, assuming the clock rolled over
+    /// at most once in between
+    pub const fn ticks_since(self, earlier: SensorTime) -> u32 {
+        self.0.wrapping_sub(earlier.0) & (Self::ROLLOVER_TICKS - 1)
+    }
+    /// Returns the number of µs elapsed since `earlier`, assuming the clock rolled over at
+    /// most once in between
+    pub const fn micros_since(self, earlier: SensorTime) -> u32 {
+        Self::ticks_to_micros(self.ticks_since(earlier) as u64) as u32
+    }
+    /// Returns `true` if this reading was taken after `other`
+    pub const fn is_after(self, other: SensorTime) -> bool {
+        self.signed_ticks_since(other) > 0
+    }
+    /// Returns `true` if this reading was taken before `other`
+    pub const fn is_before(self, other: SensorTime) -> bool {
+        self.signed_ticks_since(other) < 0
+    }
+    // Shortest distance around the clock from `other`
+    const fn signed_ticks_since(self, other: SensorTime) -> i32 {
+        // Move the 24-bit difference to the top of an i32 and back to sign-extend it
+        ((self.0.wrapping_sub(other.0) << 8) as i32) >> 8
+    }
+}
+
+impl core::ops::Sub for SensorTime {
+    /// Number of ticks from `rhs` to `self`, negative if `rhs` is later
+    type Output = i32;
+
+    fn sub(self, rhs: SensorTime) -> i32 {
+        self.signed_ticks_since(rhs)
+    }
+}
+
+/// Extends successive [SensorTime] readings to a monotonic 64-bit tick count
+///
+/// Counts the number of times the 24-bit clock has rolled over. Readings must be passed in the
+/// order they were taken and at least every 655.36s, otherwise a rollover is missed
+///
+/// # Examples
+/// ```
+/// use bma400::{SensorTime, SensorTimeExtender};
+///
+/// let mut extender = SensorTimeExtender::new();
+/// assert_eq!(0xFFFF00, extender.extend(SensorTime::from_ticks(0xFFFF00)));
+/// // The clock rolled over
+/// assert_eq!(0x1000100, extender.extend(SensorTime::from_ticks(0x000100)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SensorTimeExtender {
+    last: Option<SensorTime>,
+    rollovers: u64,
+}
+
+impl SensorTimeExtender {
+    /// Create a new extender, counting from zero at the first reading
+    pub const fn new() -> Self {
+        SensorTimeExtender {
+            last: None,
+            rollovers: 0,
+        }
+    }
+    /// Returns the number of ticks since the clock was last at zero before the first reading
+    pub fn extend(&mut self, time: SensorTime) -> u64 {
+        if let Some(last) = self.last
+            && time.ticks() < last.ticks()
+        {
+            self.rollovers += 1;
+        }
+        self.last = Some(time);
+        self.rollovers * SensorTime::ROLLOVER_TICKS as u64 + time.ticks() as u64
+    }
+    /// Same as [`extend()`](SensorTimeExtender::extend), in µs
+    pub fn extend_micros(&mut self, time: SensorTime) -> u64 {
+        SensorTime::ticks_to_micros(self.extend(time))
+    }
+    /// Start counting again, e.g. after the device was reset
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// Number of (host time, sensor time) pairs used by [ClockEstimator]
+const CLOCK_SAMPLES: usize = 16;
+
+/// Fixed point fraction bits used for the clock rate
+const RATE_SHIFT: u32 = 32;
+
+/// Nominal length of a sensor clock tick in host µs (39.0625µs), with [RATE_SHIFT] fraction bits
+const NOMINAL_RATE: i128 = 625 << (RATE_SHIFT - 4);
+
+/// Estimates the offset and rate of the sensor clock relative to a host clock
+///
+/// The sensor clock can drift from its nominal 39.0625µs tick by several percent. Feed the
+/// estimator pairs of host timestamps in µs and [SensorTime]s read at the same moment, e.g.
+/// from [`get_sensor_clock()`](crate::BMA400::get_sensor_clock) or a FIFO time frame. It fits a
+/// straight line through the last 16 pairs and can then convert sensor times, such as those
+/// of FIFO samples, to host time. Uses integer arithmetic only
+///
+/// Each conversion comes with an uncertainty: the largest difference between a pair and the
+/// fitted line, growing in proportion to the distance when converting times outside the span
+/// of the pairs
+///
+/// Pairs must be added in order, at least every 655.36s so sensor clock rollovers are not
+/// missed. Converted sensor times must be within 327.68s of the last pair
+///
+/// # Examples
+/// ```
+/// use bma400::{ClockEstimator, SensorTime};
+///
+/// let mut estimator = ClockEstimator::new();
+/// // Ticks are 2% longer than nominal: 4000 ticks take 159.375ms of host time
+/// estimator.add_sample(1_000_000, SensorTime::from_ticks(0));
+/// estimator.add_sample(1_159_375, SensorTime::from_ticks(4000));
+/// estimator.add_sample(1_318_750, SensorTime::from_ticks(8000));
+/// // So the sensor clock runs slow
+/// assert_eq!(Some(-19_608), estimator.rate_ppm());
+///
+/// let estimate = estimator.to_host_micros(SensorTime::from_ticks(6000)).unwrap();
+/// assert_eq!(1_239_063, estimate.host_us);
+/// assert_eq!(0, estimate.uncertainty_us);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ClockEstimator {
+    // (host µs, extended sensor ticks), oldest first starting at `start`
+    samples: [(u64, u64); CLOCK_SAMPLES],
+    start: usize,
+    len: usize,
+    extender: SensorTimeExtender,
+    last: Option<(SensorTime, u64)>,
+    fit: Option<ClockFit>,
+}
+
+/// A line through the samples relative to the oldest one
+#[derive(Debug, Clone, Copy)]
+struct ClockFit {
+    host_origin: u64,
+    ticks_origin: u64,
+    ticks_span: u64,
+    // Host µs per tick, with RATE_SHIFT fraction bits
+    rate: i128,
+    // Host µs at the origin, with RATE_SHIFT fraction bits
+    offset: i128,
+    max_residual: u64,
+}
+
+impl ClockFit {
+    fn host_us(&self, ticks: i128) -> i128 {
+        let scaled = self.rate * ticks + self.offset;
+        // Round to the nearest µs
+        self.host_origin as i128 + ((scaled + (1 << (RATE_SHIFT - 1))) >> RATE_SHIFT)
+    }
+}
+
+/// A sensor time converted to host time by [ClockEstimator]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockEstimate {
+    /// Estimated host time in µs
+    pub host_us: u64,
+    /// The host time is expected to be within this many µs of the estimate
+    pub uncertainty_us: u64,
+}
+
+impl ClockEstimator {
+    /// Create an estimator with no samples
+    pub const fn new() -> Self {
+        ClockEstimator {
+            samples: [(0, 0); CLOCK_SAMPLES],
+            start: 0,
+            len: 0,
+            extender: SensorTimeExtender::new(),
+            last: None,
+            fit: None,
+        }
+    }
+
+    /// Adds a pair of host time in µs and the sensor time read at the same moment, replacing
+    /// the oldest pair once 16 are held
+    pub fn add_sample(&mut self, host_us: u64, sensor: SensorTime) {
+        let ticks = self.extender.extend(sensor);
+        self.last = Some((sensor, ticks));
+        if self.len == CLOCK_SAMPLES {
+            self.start = (self.start + 1) % CLOCK_SAMPLES;
+            self.len -= 1;
+        }
+        self.samples[(self.start + self.len) % CLOCK_SAMPLES] = (host_us, ticks);
+        self.len += 1;
+        self.fit = self.fit_samples();
+    }
+
+    /// Returns the deviation of the sensor clock rate from nominal in parts per million,
+    /// negative if it runs slow. None until two pairs with different sensor times were added
+    pub fn rate_ppm(&self) -> Option<i32> {
+        let fit = self.fit?;
+        let deviation = (NOMINAL_RATE - fit.rate) * 1_000_000;
+        // Round to the nearest ppm
+        Some(((deviation + fit.rate / 2).div_euclid(fit.rate)) as i32)
+    }
+
+    /// Converts `sensor` to host time. None until two pairs with different sensor times were
+    /// added
+    pub fn to_host_micros(&self, sensor: SensorTime) -> Option<ClockEstimate> {
+        let fit = self.fit?;
+        let (last, last_ticks) = self.last?;
+        let ticks = last_ticks as i128 + (sensor - last) as i128 - fit.ticks_origin as i128;
+        // Outside of the samples, the error grows with the distance from them
+        let outside = if ticks < 0 {
+            -ticks
+        } else {
+            (ticks - fit.ticks_span as i128).max(0)
+        };
+        let extrapolation = (fit.max_residual as i128 * outside) / fit.ticks_span as i128;
+        Some(ClockEstimate {
+            host_us: fit.host_us(ticks).max(0) as u64,
+            uncertainty_us: fit.max_residual + extrapolation as u64,
+        })
+    }
+
+    /// Discards all pairs, e.g. after the device was reset
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    fn samples(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        (0..self.len).map(|i| self.samples[(self.start + i) % CLOCK_SAMPLES])
+    }
+
+    // Least squares fit of host time against sensor ticks
+    fn fit_samples(&self) -> Option<ClockFit> {
+        let (host_origin, ticks_origin) = self.samples().next()?;
+        let relative = |(host, ticks): (u64, u64)| {
+            (
+                ticks as i128 - ticks_origin as i128,
+                host as i128 - host_origin as i128,
+            )
+        };
+        let n = self.len as i128;
+        let (mut sx, mut sy, mut sxx, mut sxy) = (0i128, 0i128, 0i128, 0i128);
+        let mut ticks_span = 0;
+        for (x, y) in self.samples().map(relative) {
+            sx += x;
+            sy += y;
+            sxx += x * x;
+            sxy += x * y;
+            ticks_span = ticks_span.max(x);
+        }
+        let denominator = n * sxx - sx * sx;
+        if denominator == 0 {
+            return None;
+        }
+        let rate = ((n * sxy - sx * sy) << RATE_SHIFT) / denominator;
+        let offset = ((sy << RATE_SHIFT) - rate * sx) / n;
+        let mut fit = ClockFit {
+            host_origin,
+            ticks_origin,
+            ticks_span: ticks_span as u64,
+            rate,
+            offset,
+            max_residual: 0,
+        };
+        fit.max_residual = self
+            .samples()
+            .map(|(host, ticks)| {
+                let predicted = fit.host_us(ticks as i128 - ticks_origin as i128);
+                (host as i128 - predicted).unsigned_abs() as u64
+            })
+            .max()
+            .unwrap_or(0);
+        Some(fit)
+    }
+}
+
+impl<'a> FifoFrames<'a> {
+    /// Assigns a sensor time to each data frame in the batch, working back from the trailing
+    /// [`FrameType::Time`] frame one `odr` sample period per data frame
+    ///
+    /// The time frame is only sent when the FIFO is configured with send_time_on_empty and
+    /// read past its end. It holds the sensor time of the last data frame. Returns None if the
+    /// batch has no time frame. Like the sensor clock, times wrap around every 655.36s
+    ///
+    /// The batch should have been measured entirely at `odr`. Data frames read in earlier
+    /// batches can be timed from this one's time frame, as long as no samples were skipped in
+    /// between
+    ///
+    /// # Examples
+    /// ```
+    /// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    /// # use bma400::{BMA400, OutputDataRate};
+    /// # let ADDR = 0b10100;
+    /// # let expected = vec![
+    /// #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+    /// #        Transaction::write_read(ADDR, vec![0x14], vec![
+    /// #            0x92, 0x00, 0x20, 0x92, 0x00, 0x20, 0xA0, 0x40, 0x00, 0x00, 0x80, 0x00,
+    /// #        ]),
+    /// #    ];
+    /// # let mut i2c = Mock::new(&expected);
+    /// # let mut bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+    /// let mut buffer = [0u8; 12];
+    /// let frames = bma400.read_fifo_frames(&mut buffer).unwrap();
+    /// let mut frames = frames.with_timestamps(OutputDataRate::Hz200).unwrap();
+    /// // Measured 128 ticks (5ms) apart, just before the clock rolled over
+    /// assert_eq!(0xFFFFC0, frames.next().unwrap().time.ticks());
+    /// assert_eq!(0x000040, frames.next().unwrap().time.ticks());
+    /// assert!(frames.next().is_none());
+    /// # i2c.done();
+    /// ```
+    pub fn with_timestamps(self, odr: OutputDataRate) -> Option<TimestampedFrames<'a>> {
+        let mut data_frames = 0u32;
+        let mut time = None;
+        for frame in self.clone() {
+            match frame.frame_type() {
+                FrameType::Data => data_frames += 1,
+                FrameType::Time => {
+                    time = frame.time();
+                    break;
+                }
+                FrameType::Control => {}
+            }
+        }
+        let period = odr.period_ticks();
+        // Time of the first data frame
+        let next = time?.wrapping_sub_ticks(data_frames.saturating_sub(1).wrapping_mul(period));
+        Some(TimestampedFrames {
+            frames: self,
+            next,
+            period,
+        })
+    }
+}
+
+/// A FIFO data [Frame] and the sensor time it was measured at, see
+/// [`FifoFrames::with_timestamps()`]
+#[derive(Debug, PartialEq)]
+pub struct TimestampedFrame<'a> {
+    /// Sensor time the frame was measured at
+    pub time: SensorTime,
+    /// The data frame
+    pub frame: Frame<'a>,
+}
+
+/// An iterator over the data frames in a batch of [FifoFrames] and their sensor times
+#[derive(Debug)]
+pub struct TimestampedFrames<'a> {
+    frames: FifoFrames<'a>,
+    next: SensorTime,
+    period: u32,
+}
+
+impl<'a> Iterator for TimestampedFrames<'a> {
+    type Item = TimestampedFrame<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for frame in self.frames.by_ref() {
+            match frame.frame_type() {
+                FrameType::Data => {
+                    let time = self.next;
+                    self.next = self.next.wrapping_add_ticks(self.period);
+                    return Some(TimestampedFrame { time, frame });
+                }
+                FrameType::Time => return None,
+                FrameType::Control => {}
+            }
+        }
+        None
+    }
+}
+
+/// Longest FIFO frame: a header followed by 12-bit data for all 3 axes
+const MAX_FRAME_LEN: usize = 7;
+
+/// A stateful FIFO decoder for reading the FIFO in chunks
+///
+/// [`FifoFrames`] stops at a frame cut off by the end of the buffer. [`FifoDecoder`] instead
+/// keeps the partial frame and completes it with the start of the next chunk, so no frames are
+/// lost at chunk boundaries. Use it with [`read_fifo_chunk()`](crate::BMA400::read_fifo_chunk)
+/// or [`decode()`](FifoDecoder::decode) bytes read from the FIFO elsewhere
+#[derive(Debug, Clone, Default)]
+pub struct FifoDecoder {
+    // Start of a frame cut off at the end of the previous chunk
+    partial: [u8; MAX_FRAME_LEN],
+    partial_len: usize,
+    // A partial frame completed by the current chunk
+    joined: [u8; MAX_FRAME_LEN],
+    dropped: usize,
+    empty: bool,
+}
+
+impl FifoDecoder {
+    /// Create a new decoder with no partial frame
+    pub const fn new() -> Self {
+        FifoDecoder {
+            partial: [0u8; MAX_FRAME_LEN],
+            partial_len: 0,
+            joined: [0u8; MAX_FRAME_LEN],
+            dropped: 0,
+            empty: false,
+        }
+    }
+
+    /// Decodes the next `chunk` of bytes read from the FIFO, returning an iterator over the
+    /// complete [Frame]s. A frame left partial at the end of the previous chunk is returned first
+    ///
+    /// Decoding stops at the empty frame returned when reading past the end of the FIFO, see
+    /// [`empty_seen()`](FifoDecoder::empty_seen). A frame cut off at the end of `chunk` is kept
+    /// for the next call
+    pub fn decode<'a>(&'a mut self, chunk: &'a [u8]) -> DecodedFrames<'a> {
+        let was_empty = self.empty;
+        self.empty = false;
+        let mut start = 0;
+        let mut joined_len = 0;
+        if self.partial_len > 0 {
+            let frame_len = Self::frame_len(self.partial[0]);
+            start = (frame_len - self.partial_len).min(chunk.len());
+            self.partial[self.partial_len..self.partial_len + start]
+                .copy_from_slice(&chunk[..start]);
+            self.partial_len += start;
+            if self.partial_len == frame_len {
+                if Self::is_empty_frame(self.partial[0]) {
+                    self.empty = true;
+                } else {
+                    self.joined[..frame_len].copy_from_slice(&self.partial[..frame_len]);
+                    joined_len = frame_len;
+                }
+                self.partial_len = 0;
+            }
+        }
+        // Find the end of the complete frames and keep anything after it
+        let mut end = start;
+        while !self.empty && end < chunk.len() {
+            // Reading on past an empty frame header returns its 0x00 payload byte
+            if was_empty && end == 0 && chunk[end] == 0x00 {
+                self.empty = true;
+                break;
+            }
+            // The frame length is unknown, so the rest of the chunk can't be decoded
+            if Self::is_invalid_header(chunk[end]) {
+                self.dropped += chunk.len() - end;
+                break;
+            }
+            if Self::is_empty_frame(chunk[end]) {
+                self.empty = true;
+                break;
+            }
+            let frame_len = Self::frame_len(chunk[end]);
+            if end + frame_len > chunk.len() {
+                self.partial_len = chunk.len() - end;
+                self.partial[..self.partial_len].copy_from_slice(&chunk[end..]);
+                break;
+            }
+            end += frame_len;
+        }
+        DecodedFrames {
+            joined: (joined_len > 0).then(|| Frame {
+                slice: &self.joined[..joined_len],
+            }),
+            frames: FifoFrames::new(&chunk[start..end]),
+        }
+    }
+
+    /// Returns `true` if the last chunk decoded reached the empty frame returned when reading
+    /// past the end of the FIFO, i.e. the FIFO has been drained
+    pub fn empty_seen(&self) -> bool {
+        self.empty
+    }
+
+    /// Returns the number of bytes held from a frame cut off at the end of the last chunk
+    pub fn pending_bytes(&self) -> usize {
+        self.partial_len
+    }
+
+    /// Returns the number of bytes discarded without being decoded: the rest of a chunk after a
+    /// header with an invalid frame mode, and incomplete frames discarded by
+    /// [`reset()`](FifoDecoder::reset)
+    pub fn dropped_bytes(&self) -> usize {
+        self.dropped
+    }
+
+    /// Discards any partial frame, e.g. after the FIFO was flushed. Its bytes are added to
+    /// [`dropped_bytes()`](FifoDecoder::dropped_bytes)
+    pub fn reset(&mut self) {
+        self.dropped += self.partial_len;
+        self.partial_len = 0;
+        self.empty = false;
+    }
+
+    fn frame_len(header: u8) -> usize {
+        Header::from_bits_truncate(header).num_payload_bytes() + 1
+    }
+
+    fn is_empty_frame(header: u8) -> bool {
+        let header = Header::from_bits_truncate(header);
+        matches!(header.frame_type(), FrameType::Data) && !header.has_data()
+    }
+
+    // Only 0b10 (data / time) and 0b01 (control) are valid frame modes
+    fn is_invalid_header(header: u8) -> bool {
+        let modes = Header::FH_MODE1 | Header::FH_MODE0;
+        let mode = Header::from_bits_truncate(header) & modes;
+        mode.is_empty() || mode == modes
+    }
+}
+
+/// An iterator over the [Frame]s decoded from a chunk by [`FifoDecoder::decode()`]
+#[derive(Debug)]
+pub struct DecodedFrames<'a> {
+    joined: Option<Frame<'a>>,
+    frames: FifoFrames<'a>,
+}
+
+impl<'a> Iterator for DecodedFrames<'a> {
+    type Item = Frame<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.joined.take().or_else(|| self.frames.next())
+    }
+}
+
+/// The settings a FIFO data frame was measured with
+///
+/// The device signals a change to any of these with a control frame, see
+/// [`FifoSampleDecoder`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FifoSettings {
+    /// Measurement range
+    pub scale: Scale,
+    /// Output data rate of AccFilt1
+    pub odr: OutputDataRate,
+    /// Oversample rate in normal mode
+    pub osr: OversampleRate,
+    /// Low pass filter bandwidth of AccFilt1
+    pub filt1_bw: Filter1Bandwidth,
+    /// Data source feeding the FIFO
+    pub src: DataSource,
+}
+
+impl Default for FifoSettings {
+    /// The settings after power on or a soft reset
+    fn default() -> Self {
+        FifoSettings {
+            scale: Scale::Range4G,
+            odr: OutputDataRate::Hz200,
+            osr: OversampleRate::OSR0,
+            filt1_bw: Filter1Bandwidth::High,
+            src: DataSource::AccFilt1,
+        }
+    }
+}
+
+/// A FIFO data frame converted to milli-g using the [FifoSettings] it was measured with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FifoSample {
+    /// x-axis acceleration in milli-g, None if the x-axis is not written to the FIFO
+    pub x: Option<i16>,
+    /// y-axis acceleration in milli-g, None if the y-axis is not written to the FIFO
+    pub y: Option<i16>,
+    /// z-axis acceleration in milli-g, None if the z-axis is not written to the FIFO
+    pub z: Option<i16>,
+    /// The settings in effect when the sample was written to the FIFO
+    pub settings: FifoSettings,
+}
+
+/// Number of settings changes that can be waiting for their control frame
+const SETTINGS_QUEUE_LEN: usize = 8;
+
+/// Settings changes in the order their control frames were written to the FIFO
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SettingsQueue {
+    entries: [FifoSettings; SETTINGS_QUEUE_LEN],
+    start: usize,
+    len: usize,
+    // Changes pushed while the queue was full, only the most recent is kept
+    lost: usize,
+    latest: FifoSettings,
+}
+
+impl SettingsQueue {
+    /// Adds `settings` to the back of the queue. Once it is full, only the most recent change
+    /// is kept and the control frames of the others will have no known settings
+    pub fn push(&mut self, settings: FifoSettings) {
+        if self.len == SETTINGS_QUEUE_LEN || self.lost > 0 {
+            self.lost += 1;
+            self.latest = settings;
+            return;
+        }
+        self.entries[(self.start + self.len) % SETTINGS_QUEUE_LEN] = settings;
+        self.len += 1;
+    }
+    /// Returns the settings to move to at the next control frame, None if they are unknown
+    pub fn pop(&mut self) -> Option<FifoSettings> {
+        if self.len == 0 {
+            if self.lost == 0 {
+                return None;
+            }
+            self.lost -= 1;
+            return (self.lost == 0).then_some(self.latest);
+        }
+        let settings = self.entries[self.start];
+        self.start = (self.start + 1) % SETTINGS_QUEUE_LEN;
+        self.len -= 1;
+        Some(settings)
+    }
+    /// Returns the most recent change
+    pub fn last_mut(&mut self) -> Option<&mut FifoSettings> {
+        if self.lost > 0 {
+            return Some(&mut self.latest);
+        }
+        if self.len == 0 {
+            return None;
+        }
+        Some(&mut self.entries[(self.start + self.len - 1) % SETTINGS_QUEUE_LEN])
+    }
+    /// Moves all changes in `other` to the back of the queue
+    pub fn append(&mut self, other: &mut SettingsQueue) {
+        while other.len > 0 {
+            if let Some(settings) = other.pop() {
+                self.push(settings);
+            }
+        }
+        if other.lost > 0 {
+            self.lost += other.lost;
+            self.latest = other.latest;
+            other.lost = 0;
+        }
+    }
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.lost = 0;
+    }
+}
+
+/// A FIFO decoder that converts data frames to milli-g, following changes to the
+/// [FifoSettings] signalled by control frames
+///
+/// Changing the [Scale] while data is waiting in the FIFO would otherwise cause the older
+/// frames to be converted with the new [Scale]. The decoder starts from the settings the
+/// oldest data in the FIFO was measured with and moves on to the next known settings at each
+/// control frame, so every [FifoSample] is converted and tagged with the settings that
+/// produced it. Get one from the device using
+/// [`fifo_sample_decoder()`](crate::BMA400::fifo_sample_decoder) and read with
+/// [`read_fifo_samples()`](crate::BMA400::read_fifo_samples), which keep it up to date with
+/// the configuration changes made through the driver
+///
+/// Control frames are applied as the returned [FifoSamples] are iterated, so each iterator
+/// should be consumed fully. Up to 8 changes can be tracked between reads. If more are made,
+/// only the most recent one is kept and the decoder moves to it at the control frame of the
+/// last change. If the configuration is changed elsewhere, a control frame may arrive with no
+/// known settings to move to. In both cases the current settings are kept at each control
+/// frame with no known settings and the frame is counted in
+/// [`unmatched_changes()`](FifoSampleDecoder::unmatched_changes)
+#[derive(Debug, Clone, Default)]
+pub struct FifoSampleDecoder {
+    frames: FifoDecoder,
+    settings: FifoSettings,
+    changes: SettingsQueue,
+    unmatched: usize,
+}
+
+impl FifoSampleDecoder {
+    /// Create a new decoder for FIFO data measured with `settings`
+    pub fn new(settings: FifoSettings) -> Self {
+        FifoSampleDecoder {
+            frames: FifoDecoder::new(),
+            settings,
+            changes: SettingsQueue::default(),
+            unmatched: 0,
+        }
+    }
+
+    /// Returns the settings the next data frame is expected to have been measured with
+    pub fn settings(&self) -> FifoSettings {
+        self.settings
+    }
+
+    /// Queues the settings to move to at the next control frame not already accounted for.
+    /// Only needed when decoding data read without [`read_fifo_samples()`](crate::BMA400::read_fifo_samples)
+    pub fn push_change(&mut self, settings: FifoSettings) {
+        self.changes.push(settings);
+    }
+
+    /// Decodes the next `chunk` of bytes read from the FIFO, returning an iterator over the
+    /// [FifoSample]s it completes. Partial frames are handled as in [`FifoDecoder::decode()`]
+    pub fn decode<'a>(&'a mut self, chunk: &'a [u8]) -> FifoSamples<'a> {
+        FifoSamples {
+            frames: self.frames.decode(chunk),
+            settings: &mut self.settings,
+            changes: &mut self.changes,
+            unmatched: &mut self.unmatched,
+        }
+    }
+
+    /// Returns `true` if the last chunk decoded reached the end of the FIFO, see
+    /// [`FifoDecoder::empty_seen()`]
+    pub fn empty_seen(&self) -> bool {
+        self.frames.empty_seen()
+    }
+
+    /// Returns the number of control frames seen with no queued settings to move to
+    pub fn unmatched_changes(&self) -> usize {
+        self.unmatched
+    }
+
+    /// Discards any partial frame and queued changes, continuing with `settings`, e.g. after
+    /// the FIFO was flushed
+    pub fn reset(&mut self, settings: FifoSettings) {
+        self.frames.reset();
+        self.changes.clear();
+        self.settings = settings;
+    }
+
+    pub(crate) fn append_changes(&mut self, changes: &mut SettingsQueue) {
+        self.changes.append(changes);
+    }
+}
+
+/// An iterator over the [FifoSample]s decoded from a chunk by [`FifoSampleDecoder::decode()`]
+#[derive(Debug)]
+pub struct FifoSamples<'a> {
+    frames: DecodedFrames<'a>,
+    settings: &'a mut FifoSettings,
+    changes: &'a mut SettingsQueue,
+    unmatched: &'a mut usize,
+}
+
+impl<'a> Iterator for FifoSamples<'a> {
+    type Item = FifoSample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for frame in self.frames.by_ref() {
+            match frame.frame_type() {
+                FrameType::Data => {
+                    let scale = self.settings.scale;
+                    return Some(FifoSample {
+                        x: frame.x_mg(scale),
+                        y: frame.y_mg(scale),
+                        z: frame.z_mg(scale),
+                        settings: *self.settings,
+                    });
+                }
+                FrameType::Control => match self.changes.pop() {
+                    Some(settings) => *self.settings = settings,
+                    None => *self.unmatched += 1,
+                },
+                FrameType::Time => {}
+            }
+        }
+        None
+    }
+}
+
 bitflags! {
     struct Header: u8 {
         const FH_MODE1  = 0b1000_0000;
@@ -0,0 +1,253 @@
+//! Power mode and output data rate checked at compile time
+//!
+//! [`BMA400`] takes a second type parameter for the power mode and ODR the device is in. It
+//! defaults to [`Unchecked`], where the whole API is available and the rules between settings
+//! are checked when a configuration is written.
+//!
+//! `into_sleep()`, `into_low_power()` and `into_normal()` write the power mode (and ODR) and
+//! return the device as [`BMA400<T, Sleep>`](Sleep), [`BMA400<T, LowPower>`](LowPower) or
+//! [`BMA400<T, Normal<R>>`](Normal). If the write fails, the device is handed back in its
+//! previous state along with the error. In these states:
+//! - The power mode and ODR are only changed by another transition, so `config_accel()`,
+//!   `config_auto_lp()`, `config_autowkup()`, `apply()`, `apply_config()`,
+//!   `perform_self_test()` and `soft_reset()` are only available on an [`Unchecked`] device.
+//!   The transitions fail with [`ConfigError::AutoPowerModeEnabled`] while auto low power or
+//!   auto wake-up is enabled, as the device would then change its power mode on its own
+//! - The FIFO can only be read in low power and normal mode, see [`ReadsFifo`]
+//! - Tap detection can only be configured in normal mode at 200Hz, see [`DetectsTaps`]
+//!
+//! Other rules, e.g. the data source of an interrupt requiring a particular ODR, are still
+//! checked when writing. `into_unchecked()` returns to the full API without writing anything
+//!
+//! # Examples
+//! ```
+//! # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+//! use bma400::{
+//!     BMA400, TapSensitivity,
+//!     typestate::{Hz200, Normal},
+//! };
+//! # let ADDR = 0b10100;
+//! # let expected = vec![
+//! #        Transaction::write_read(ADDR, vec![0x00], vec![0x90]),
+//! #        Transaction::write(ADDR, vec![0x19, 0x02]),
+//! #        Transaction::write_read(ADDR, vec![0x03], vec![0x14]),
+//! #        Transaction::write(ADDR, vec![0x57, 0x03]),
+//! #        Transaction::write(ADDR, vec![0x19, 0x00]),
+//! #        Transaction::write_read(ADDR, vec![0x03], vec![0x10]),
+//! #    ];
+//! # let mut i2c = Mock::new(&expected);
+//! let bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+//! let mut bma400: BMA400<_, Normal<Hz200>> = bma400.into_normal().map_err(|(_, e)| e).unwrap();
+//! bma400
+//!     .config_tap()
+//!     .with_sensitivity(TapSensitivity::SENS3)
+//!     .write()
+//!     .unwrap();
+//! let bma400 = bma400.into_sleep().map_err(|(_, e)| e).unwrap();
+//! # i2c.done();
+//! ```
+//! Tap detection is not available at other data rates
+//! ```compile_fail
+//! # use embedded_hal_mock::eh1::i2c::Mock;
+//! # use bma400::{BMA400, typestate::{Hz100, Normal}};
+//! # let mut i2c = Mock::new(&[]);
+//! let bma400 = BMA400::new_i2c(&mut i2c).unwrap();
+//! let mut bma400: BMA400<_, Normal<Hz100>> = bma400.into_normal().map_err(|(_, e)| e).unwrap();
+//! bma400.config_tap();
+//! ```
+use core::marker::PhantomData;
+
+#[cfg(feature = "embedded-hal-async")]
+use crate::AsyncInterface;
+use crate::{
+    BMA400, BMA400Error, ConfigError, OutputDataRate, PowerMode, config::AccConfigBuilder,
+};
+
+mod sealed {
+    pub trait Sealed {}
+}
+use sealed::Sealed;
+
+/// The power mode and ODR are not tracked, the whole API is available (default)
+pub struct Unchecked;
+
+/// Sleep mode
+pub struct Sleep;
+
+/// Low power mode, measuring at a fixed 25Hz
+pub struct LowPower;
+
+/// Normal mode, measuring at the output data rate `R`
+pub struct Normal<R>(PhantomData<R>);
+
+/// An output data rate of the accelerometer in normal mode
+pub trait Rate: Sealed {
+    /// The matching runtime value
+    const ODR: OutputDataRate;
+}
+
+macro_rules! rates {
+    ($($rate:ident: $doc:literal),+) => {
+        $(
+            #[doc = $doc]
+            pub struct $rate;
+            impl Sealed for $rate {}
+            impl Rate for $rate {
+                const ODR: OutputDataRate = OutputDataRate::$rate;
+            }
+        )+
+    };
+}
+
+rates!(
+    Hz12_5: "12.5 Hz",
+    Hz25: "25 Hz",
+    Hz50: "50 Hz",
+    Hz100: "100 Hz",
+    Hz200: "200 Hz",
+    Hz400: "400 Hz",
+    Hz800: "800 Hz"
+);
+
+impl Sealed for Unchecked {}
+impl Sealed for Sleep {}
+impl Sealed for LowPower {}
+impl<R: Rate> Sealed for Normal<R> {}
+
+/// States in which the FIFO can be read, using e.g.
+/// [`read_fifo_frames()`](BMA400::read_fifo_frames)
+pub trait ReadsFifo: Sealed {}
+impl ReadsFifo for Unchecked {}
+impl ReadsFifo for LowPower {}
+impl<R: Rate> ReadsFifo for Normal<R> {}
+
+/// States in which tap detection can be configured using
+/// [`config_tap()`](BMA400::config_tap)
+pub trait DetectsTaps: Sealed {}
+impl DetectsTaps for Unchecked {}
+impl DetectsTaps for Normal<Hz200> {}
+
+impl<T, S> BMA400<T, S> {
+    /// Stop tracking the power mode and ODR, making the whole API available again
+    pub fn into_unchecked(self) -> BMA400<T> {
+        self.into_state()
+    }
+}
+
+// The device is handed back when a transition fails, so the error holds all of it
+#[allow(clippy::result_large_err)]
+impl<T, S, InterfaceError> BMA400<T, S>
+where
+    T: crate::blocking::ReadFromRegister<Error = BMA400Error<InterfaceError>>
+        + crate::blocking::WriteToRegister<Error = BMA400Error<InterfaceError>>,
+{
+    /// Put the device in sleep mode
+    pub fn into_sleep(mut self) -> Result<BMA400<T, Sleep>, (Self, BMA400Error<InterfaceError>)> {
+        if self.config.auto_power_enabled() {
+            return Err((self, ConfigError::AutoPowerModeEnabled.into()));
+        }
+        match AccConfigBuilder::new(&mut self)
+            .with_power_mode(PowerMode::Sleep)
+            .write()
+        {
+            Ok(()) => Ok(self.into_state()),
+            Err(e) => Err((self, e)),
+        }
+    }
+
+    /// Put the device in low power mode
+    pub fn into_low_power(
+        mut self,
+    ) -> Result<BMA400<T, LowPower>, (Self, BMA400Error<InterfaceError>)> {
+        if self.config.auto_power_enabled() {
+            return Err((self, ConfigError::AutoPowerModeEnabled.into()));
+        }
+        match AccConfigBuilder::new(&mut self)
+            .with_power_mode(PowerMode::LowPower)
+            .write()
+        {
+            Ok(()) => Ok(self.into_state()),
+            Err(e) => Err((self, e)),
+        }
+    }
+
+    /// Put the device in normal mode with the output data rate `R`
+    ///
+    /// Fails with a [`ConfigError`] if an enabled interrupt requires a different ODR
+    pub fn into_normal<R: Rate>(
+        mut self,
+    ) -> Result<BMA400<T, Normal<R>>, (Self, BMA400Error<InterfaceError>)> {
+        if self.config.auto_power_enabled() {
+            return Err((self, ConfigError::AutoPowerModeEnabled.into()));
+        }
+        match AccConfigBuilder::new(&mut self)
+            .with_power_mode(PowerMode::Normal)
+            .with_odr(R::ODR)
+            .write()
+        {
+            Ok(()) => Ok(self.into_state()),
+            Err(e) => Err((self, e)),
+        }
+    }
+}
+
+#[cfg(feature = "embedded-hal-async")]
+impl<T, S, InterfaceError> BMA400<AsyncInterface<T>, S>
+where
+    AsyncInterface<T>: crate::asynch::ReadFromRegister<Error = BMA400Error<InterfaceError>>
+        + crate::asynch::WriteToRegister<Error = BMA400Error<InterfaceError>>,
+{
+    /// Put the device in sleep mode
+    pub async fn into_sleep(
+        mut self,
+    ) -> Result<BMA400<AsyncInterface<T>, Sleep>, (Self, BMA400Error<InterfaceError>)> {
+        if self.config.auto_power_enabled() {
+            return Err((self, ConfigError::AutoPowerModeEnabled.into()));
+        }
+        match AccConfigBuilder::new(&mut self)
+            .with_power_mode(PowerMode::Sleep)
+            .write()
+            .await
+        {
+            Ok(()) => Ok(self.into_state()),
+            Err(e) => Err((self, e)),
+        }
+    }
+
+    /// Put the device in low power mode
+    pub async fn into_low_power(
+        mut self,
+    ) -> Result<BMA400<AsyncInterface<T>, LowPower>, (Self, BMA400Error<InterfaceError>)> {
+        if self.config.auto_power_enabled() {
+            return Err((self, ConfigError::AutoPowerModeEnabled.into()));
+        }
+        match AccConfigBuilder::new(&mut self)
+            .with_power_mode(PowerMode::LowPower)
+            .write()
+            .await
+        {
+            Ok(()) => Ok(self.into_state()),
+            Err(e) => Err((self, e)),
+        }
+    }
+
+    /// Put the device in normal mode with the output data rate `R`
+    ///
+    /// Fails with a [`ConfigError`] if an enabled interrupt requires a different ODR
+    pub async fn into_normal<R: Rate>(
+        mut self,
+    ) -> Result<BMA400<AsyncInterface<T>, Normal<R>>, (Self, BMA400Error<InterfaceError>)> {
+        if self.config.auto_power_enabled() {
+            return Err((self, ConfigError::AutoPowerModeEnabled.into()));
+        }
+        match AccConfigBuilder::new(&mut self)
+            .with_power_mode(PowerMode::Normal)
+            .with_odr(R::ODR)
+            .write()
+            .await
+        {
+            Ok(()) => Ok(self.into_state()),
+            Err(e) => Err((self, e)),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use bma400::{BMA400, RegisterInterface, types::*};
+
+// Register file standing in for a device behind a custom transport
+struct Registers {
+    regs: [u8; 0x80],
+    writes: Vec<(u8, Vec<u8>)>,
+    fail: bool,
+}
+
+#[derive(Debug, PartialEq)]
+struct TransportError;
+
+impl RegisterInterface for Registers {
+    type Error = TransportError;
+
+    fn read_registers(&mut self, start: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        if self.fail {
+            return Err(TransportError);
+        }
+        let start = start as usize;
+        buffer.copy_from_slice(&self.regs[start..start + buffer.len()]);
+        Ok(())
+    }
+
+    fn write_registers(&mut self, start: u8, data: &[u8]) -> Result<(), Self::Error> {
+        if self.fail {
+            return Err(TransportError);
+        }
+        let addr = start as usize;
+        self.regs[addr..addr + data.len()].copy_from_slice(data);
+        self.writes.push((start, data.to_vec()));
+        Ok(())
+    }
+}
+
+fn registers() -> Registers {
+    let mut regs = [0u8; 0x80];
+    regs[0x00] = 0x90;
+    Registers {
+        regs,
+        writes: Vec::new(),
+        fail: false,
+    }
+}
+
+#[test]
+fn init_bad_chip_id() {
+    let mut regs = registers();
+    regs.regs[0x00] = 0x89;
+    let result = BMA400::new_with_interface(regs);
+    assert!(matches!(result, Err(BMA400Error::ChipIdReadFailed)));
+}
+
+#[test]
+fn init_transport_error() {
+    let mut regs = registers();
+    regs.fail = true;
+    let result = BMA400::new_with_interface(regs);
+    assert!(matches!(result, Err(BMA400Error::IOError(TransportError))));
+}
+
+#[test]
+fn destroy() {
+    let device = BMA400::new_with_interface(registers()).unwrap();
+    let regs = device.destroy().destroy();
+    BMA400::new_with_interface(regs).unwrap();
+}
+
+#[test]
+fn get_data() {
+    let mut regs = registers();
+    // Status reports normal mode once the power mode is written
+    regs.regs[0x03] = 0x14;
+    regs.regs[0x04..0x0A].copy_from_slice(&[0x1E, 0x00, 0x10, 0x00, 0xDC, 0x03]);
+    let mut device = BMA400::new_with_interface(regs).unwrap();
+    device
+        .config_accel()
+        .with_power_mode(PowerMode::Normal)
+        .with_scale(Scale::Range2G)
+        .write()
+        .unwrap();
+    let m = device.get_data().unwrap();
+    assert_eq!((m.x, m.y, m.z), (30, 16, 988));
+    // Adjacent registers are written together
+    let regs = device.destroy().destroy();
+    assert_eq!(regs.writes, vec![(0x19, vec![0x02, 0x09])]);
+}
+
+#[test]
+fn adopt_config() {
+    let mut regs = registers();
+    // Range = 2G, ODR = 200Hz
+    regs.regs[0x1A] = 0x09;
+    regs.regs[0x04..0x0A].copy_from_slice(&[0x1E, 0x00, 0x10, 0x00, 0xDC, 0x03]);
+    let mut device = BMA400::new_with_interface(regs).unwrap();
+    device.resync_config().unwrap();
+    let m = device.get_data().unwrap();
+    assert_eq!((m.x, m.y, m.z), (30, 16, 988));
+}
@@ -1,5 +1,6 @@
 #![allow(clippy::vec_init_then_push)]
 use bma400::{BMA400, I2CInterface, types::*};
+use embedded_hal::i2c::ErrorKind;
 use embedded_hal_mock::eh1::{
     delay::{CheckedDelay, Transaction as DelayTransaction},
     i2c::{Mock, Transaction},
@@ -204,7 +205,7 @@ fn get_unscaled_data() {
     cleanup(device);
 }
 
-fn get_scaled_data(scale: Scale) -> (i16, i16, i16) {
+fn read_at_scale<T>(scale: Scale, read: impl FnOnce(&mut BMA400<I2CInterface<Mock>>) -> T) -> T {
     let byte = match scale {
         Scale::Range2G => 0x09,
         Scale::Range4G => 0x49,
@@ -234,9 +235,16 @@ fn get_scaled_data(scale: Scale) -> (i16, i16, i16) {
     }
     let mut device = new(&expected);
     device.config_accel().with_scale(scale).write().unwrap();
-    let m = device.get_data().unwrap();
+    let result = read(&mut device);
     cleanup(device);
-    (m.x, m.y, m.z)
+    result
+}
+
+fn get_scaled_data(scale: Scale) -> (i16, i16, i16) {
+    read_at_scale(scale, |device| {
+        let m = device.get_data().unwrap();
+        (m.x, m.y, m.z)
+    })
 }
 
 #[test]
@@ -247,6 +255,79 @@ fn get_data() {
     assert_eq!((-16376, -8, 16376), get_scaled_data(Scale::Range16G));
 }
 
+fn get_data_mg_at(scale: Scale) -> (i16, i16, i16) {
+    read_at_scale(scale, |device| {
+        let m = device.get_data_mg().unwrap();
+        (m.x, m.y, m.z)
+    })
+}
+
+#[test]
+fn get_data_with_time() {
+    let mut expected = Vec::new();
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x00],
+        vec![0x90],
+    ));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x04],
+        vec![0x01, 0x08, 0xFF, 0x0F, 0xFF, 0x07, 0xF8, 0xFF, 0xFF],
+    ));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x04],
+        vec![
+            0x01, 0x08, 0xFF, 0x0F, 0xFF, 0x07, 0x08, 0x00, 0x00, 0x01, 0x10, 0x1F, 0x07,
+        ],
+    ));
+    let mut device = new(&expected);
+    let data = device.get_data_with_time().unwrap();
+    let m = data.measurement;
+    // Scaled for the default 4g range
+    assert_eq!((m.x, m.y, m.z), (-4094, -2, 4094));
+    assert_eq!(data.time.ticks(), 0xFFFFF8);
+
+    let status = device.get_data_with_status().unwrap();
+    let m = status.data.measurement;
+    assert_eq!((m.x, m.y, m.z), (-4094, -2, 4094));
+    assert_eq!(status.data.time.ticks(), 0x000008);
+    assert!(status.data.time.is_after(data.time));
+    assert!(status.reset);
+    assert!(status.int_status0.ieng_overrun_stat());
+    assert!(!status.int_status0.drdy_stat());
+    assert!(status.int_status1.d_tap_stat());
+    assert!(status.int_status1.s_tap_stat());
+    assert!(matches!(
+        status.int_status1.step_int_stat(),
+        StepIntStatus::ManyStepDetect
+    ));
+    assert!(status.int_status2.actch_x_stat());
+    assert!(status.int_status2.actch_y_stat());
+    assert!(status.int_status2.actch_z_stat());
+    cleanup(device);
+}
+
+#[test]
+fn get_data_mg() {
+    assert_eq!((-1999, -1, 1999), get_data_mg_at(Scale::Range2G));
+    assert_eq!((-3998, -2, 3998), get_data_mg_at(Scale::Range4G));
+    assert_eq!((-7996, -4, 7996), get_data_mg_at(Scale::Range8G));
+    assert_eq!((-15992, -8, 15992), get_data_mg_at(Scale::Range16G));
+}
+
+#[test]
+fn get_data_g() {
+    let a = read_at_scale(Scale::Range16G, |device| device.get_data_g().unwrap());
+    assert_eq!(
+        (a.x, a.y, a.z),
+        (-2047.0 / 128.0, -1.0 / 128.0, 2047.0 / 128.0)
+    );
+    let a = read_at_scale(Scale::Range2G, |device| device.get_data_mps2().unwrap());
+    assert_eq!(a.z, 2047.0 / 1024.0 * STANDARD_GRAVITY);
+}
+
 #[test]
 fn get_sensor_clock() {
     let mut expected = Vec::new();
@@ -262,7 +343,43 @@ fn get_sensor_clock() {
     ));
     let mut device = new(&expected);
     let t = device.get_sensor_clock().unwrap();
-    assert_eq!(t, 0xFFFFF8);
+    assert_eq!(t.ticks(), 0xFFFFF8);
+    cleanup(device);
+}
+
+#[test]
+fn extend_sensor_clock() {
+    let mut expected = Vec::new();
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x00],
+        vec![0x90],
+    ));
+    for reading in [[0x00, 0xFF, 0x7F], [0x00, 0xFF, 0xFF], [0x08, 0x00, 0x00]] {
+        expected.push(Transaction::write_read(
+            DEFAULT_ADDR,
+            vec![0x0A],
+            reading.to_vec(),
+        ));
+    }
+    let mut device = new(&expected);
+    let mut extender = SensorTimeExtender::new();
+    let first = device.get_sensor_clock().unwrap();
+    assert_eq!(extender.extend(first), 0x7FFF00);
+    let second = device.get_sensor_clock().unwrap();
+    assert_eq!(extender.extend(second), 0xFFFF00);
+    // The clock rolled over
+    let third = device.get_sensor_clock().unwrap();
+    assert_eq!(extender.extend(third), 0x1000008);
+    assert!(third.is_after(second));
+    assert!(second.is_before(third));
+    assert_eq!(third - second, 0x108);
+    assert_eq!(second - third, -0x108);
+    assert_eq!(third.ticks_since(second), 0x108);
+    // 264 ticks = 10.3125ms
+    assert_eq!(third.micros_since(second), 10_312);
+    // More than half a rollover apart, so the shorter way around is assumed
+    assert!(first.is_after(third));
     cleanup(device);
 }
 
@@ -612,6 +729,83 @@ fn get_int_status2() {
     cleanup(device);
 }
 
+#[test]
+fn get_interrupts() {
+    let mut expected = Vec::new();
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x00],
+        vec![0x90],
+    ));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x0E],
+        vec![0xEF, 0x0F, 0x07],
+    ));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x0E],
+        vec![0x00, 0x00, 0x12],
+    ));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x0E],
+        vec![0x00, 0x00, 0x00],
+    ));
+    let mut device = new(&expected);
+
+    // Everything except the engine overrun
+    let interrupts = device.get_interrupts().unwrap();
+    assert!(interrupts.contains(Interrupts::DATA_READY | Interrupts::WAKEUP));
+    assert!(!interrupts.contains(Interrupts::ENGINE_OVERRUN));
+    assert_eq!(interrupts.steps(), 2);
+    let events: Vec<InterruptEvent> = interrupts.into_iter().collect();
+    assert_eq!(
+        events,
+        [
+            InterruptEvent::DataReady,
+            InterruptEvent::FifoWatermark,
+            InterruptEvent::FifoFull,
+            InterruptEvent::Gen1,
+            InterruptEvent::Gen2,
+            InterruptEvent::OrientationChanged,
+            InterruptEvent::Wakeup,
+            InterruptEvent::SingleTap,
+            InterruptEvent::DoubleTap,
+            InterruptEvent::Step(2),
+            InterruptEvent::ActivityChange {
+                x: true,
+                y: true,
+                z: true,
+            },
+        ]
+    );
+
+    // The engine overrun is reported once, whichever register it is read from
+    let interrupts = device.get_interrupts().unwrap();
+    assert_eq!(
+        interrupts,
+        Interrupts::ENGINE_OVERRUN | Interrupts::ACTIVITY_CHANGE_Y
+    );
+    let events: Vec<InterruptEvent> = interrupts.events().collect();
+    assert_eq!(
+        events,
+        [
+            InterruptEvent::ActivityChange {
+                x: false,
+                y: true,
+                z: false,
+            },
+            InterruptEvent::EngineOverrun,
+        ]
+    );
+
+    let interrupts = device.get_interrupts().unwrap();
+    assert!(interrupts.is_empty());
+    assert_eq!(interrupts.events().next(), None);
+    cleanup(device);
+}
+
 #[test]
 fn get_fifo_len() {
     let mut expected = Vec::new();
@@ -667,7 +861,7 @@ fn read_fifo_frames() {
                 assert_eq!(frame.z(), Some(2047));
             }
             FrameType::Time => {
-                assert_eq!(frame.time(), Some(0xFFFFF8));
+                assert_eq!(frame.time(), Some(SensorTime::from_ticks(0xFFFFF8)));
             }
             FrameType::Control => {
                 assert!(frame.fifo_src_chg().unwrap());
@@ -681,6 +875,254 @@ fn read_fifo_frames() {
     cleanup(device);
 }
 
+#[test]
+fn read_fifo_chunk() {
+    let mut expected = Vec::new();
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x00],
+        vec![0x90],
+    ));
+    // Control frame, data frame, time frame then empty frames split across 5 byte reads
+    let fifo = [
+        0x48, 0x6E, 0x9E, 0x01, 0x80, 0x0F, 0xFF, 0x0F, 0x7F, 0xA0, 0xF8, 0xFF, 0xFF, 0x80, 0x00,
+    ];
+    for chunk in fifo.chunks(5) {
+        expected.push(Transaction::write_read(
+            DEFAULT_ADDR,
+            vec![0x14],
+            chunk.to_vec(),
+        ));
+    }
+    // Frame header at the end of the chunk
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x14],
+        vec![0x8A, 0x10, 0xF0, 0x9E],
+    ));
+    let mut device = new(&expected);
+    let mut decoder = FifoDecoder::new();
+    let mut buffer = [0u8; 5];
+
+    let mut frames = device.read_fifo_chunk(&mut decoder, &mut buffer).unwrap();
+    assert!(matches!(
+        frames.next().unwrap().frame_type(),
+        FrameType::Control
+    ));
+    assert!(frames.next().is_none());
+    assert_eq!(decoder.pending_bytes(), 3);
+
+    let mut frames = device.read_fifo_chunk(&mut decoder, &mut buffer).unwrap();
+    let frame = frames.next().unwrap();
+    assert_eq!(frame.x(), Some(-2047));
+    assert_eq!(frame.y(), Some(-1));
+    assert_eq!(frame.z(), Some(2047));
+    assert!(frames.next().is_none());
+    assert_eq!(decoder.pending_bytes(), 1);
+
+    let mut frames = device.read_fifo_chunk(&mut decoder, &mut buffer).unwrap();
+    assert_eq!(
+        frames.next().unwrap().time(),
+        Some(SensorTime::from_ticks(0xFFFFF8))
+    );
+    assert!(frames.next().is_none());
+    assert!(decoder.empty_seen());
+    assert_eq!(decoder.pending_bytes(), 0);
+
+    // A new read after the FIFO was drained
+    let mut frames = device
+        .read_fifo_chunk(&mut decoder, &mut buffer[..4])
+        .unwrap();
+    let frame = frames.next().unwrap();
+    assert_eq!(frame.x(), Some(256));
+    assert_eq!(frame.z(), Some(-256));
+    assert!(frames.next().is_none());
+    assert!(!decoder.empty_seen());
+    assert_eq!(decoder.pending_bytes(), 1);
+
+    // The FIFO was flushed, the partial frame will never be completed
+    decoder.reset();
+    assert_eq!(decoder.pending_bytes(), 0);
+    assert_eq!(decoder.dropped_bytes(), 1);
+    cleanup(device);
+}
+
+#[test]
+fn fifo_decoder_invalid_header() {
+    let mut decoder = FifoDecoder::new();
+    // x-axis frame, then a header with frame mode 0b00
+    let chunk = [0x92, 0x00, 0x01, 0x00, 0xAA, 0xBB];
+    let mut frames = decoder.decode(&chunk);
+    assert_eq!(frames.next().unwrap().x(), Some(16));
+    assert!(frames.next().is_none());
+    assert!(!decoder.empty_seen());
+    assert_eq!(decoder.pending_bytes(), 0);
+    assert_eq!(decoder.dropped_bytes(), 3);
+
+    // Frame mode 0b11
+    let mut frames = decoder.decode(&[0xC2, 0x00]);
+    assert!(frames.next().is_none());
+    assert_eq!(decoder.dropped_bytes(), 5);
+}
+
+#[test]
+fn read_timestamped_fifo_frames() {
+    let mut expected = Vec::new();
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x00],
+        vec![0x90],
+    ));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1A, 0x4B]));
+    // Data frame, control frame, data frame, time frame at 0x000010
+    let fifo = vec![
+        0x92, 0x00, 0x20, 0x48, 0x08, 0x92, 0x00, 0x20, 0xA0, 0x10, 0x00, 0x00, 0x80, 0x00,
+    ];
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x14],
+        fifo.clone(),
+    ));
+    // Read stops before the time frame
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x14],
+        fifo[..8].to_vec(),
+    ));
+    let mut device = new(&expected);
+    device
+        .config_accel()
+        .with_odr(OutputDataRate::Hz800)
+        .write()
+        .unwrap();
+
+    let mut buffer = [0u8; 14];
+    let mut frames = device
+        .read_timestamped_fifo_frames(&mut buffer)
+        .unwrap()
+        .unwrap();
+    // 32 ticks apart, the first before the 24-bit clock rolled over
+    let frame = frames.next().unwrap();
+    assert_eq!(frame.time.ticks(), 0xFFFFF0);
+    assert_eq!(frame.time.as_micros(), 655_359_375);
+    assert_eq!(frame.frame.x(), Some(512));
+    let frame = frames.next().unwrap();
+    assert_eq!(frame.time.ticks(), 0x000010);
+    assert_eq!(frame.time.as_micros(), 625);
+    assert!(frames.next().is_none());
+
+    let mut buffer = [0u8; 8];
+    assert!(
+        device
+            .read_timestamped_fifo_frames(&mut buffer)
+            .unwrap()
+            .is_none()
+    );
+    cleanup(device);
+}
+
+#[test]
+fn read_fifo_samples() {
+    let mut expected = Vec::new();
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x00],
+        vec![0x90],
+    ));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x26, 0x20]));
+    // ODR and scale written in one burst with the filter bandwidth
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x19, 0x80, 0x08]));
+    // x-axis only: 512 at 4g, control frames for each register, 512 at 2g, an unexpected
+    // control frame, then a time frame and the end of the FIFO
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x14],
+        vec![
+            0x92, 0x00, 0x20, 0x48, 0x04, 0x48, 0x08, 0x92, 0x00, 0x20, 0x48, 0x08, 0xA0, 0x00,
+            0x00, 0x00, 0x80, 0x00,
+        ],
+    ));
+    // Flushing forgets the settings history
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1A, 0xC8]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x7E, 0xB0]));
+    let mut device = new(&expected);
+    device
+        .config_fifo()
+        .with_axes(true, false, false)
+        .write()
+        .unwrap();
+    let mut decoder = device.fifo_sample_decoder();
+    device
+        .config_accel()
+        .with_filt1_bw(Filter1Bandwidth::Low)
+        .with_odr(OutputDataRate::Hz100)
+        .with_scale(Scale::Range2G)
+        .write()
+        .unwrap();
+    let mut buffer = [0u8; 18];
+    let mut samples = device.read_fifo_samples(&mut decoder, &mut buffer).unwrap();
+    let sample = samples.next().unwrap();
+    assert_eq!(sample.x, Some(1000));
+    assert_eq!((sample.y, sample.z), (None, None));
+    assert_eq!(sample.settings, FifoSettings::default());
+    let sample = samples.next().unwrap();
+    assert_eq!(sample.x, Some(500));
+    assert_eq!(
+        sample.settings,
+        FifoSettings {
+            scale: Scale::Range2G,
+            odr: OutputDataRate::Hz100,
+            filt1_bw: Filter1Bandwidth::Low,
+            ..FifoSettings::default()
+        }
+    );
+    assert_eq!(samples.next(), None);
+    assert!(decoder.empty_seen());
+    assert_eq!(decoder.unmatched_changes(), 1);
+
+    device
+        .config_accel()
+        .with_scale(Scale::Range16G)
+        .write()
+        .unwrap();
+    device.flush_fifo().unwrap();
+    let decoder = device.fifo_sample_decoder();
+    assert_eq!(decoder.settings().scale, Scale::Range16G);
+    cleanup(device);
+}
+
+#[test]
+fn fifo_frame_conversions() {
+    let mut expected = Vec::new();
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x00],
+        vec![0x90],
+    ));
+    // 12 bit x, y, z frame then 8 bit x, z frame
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x14],
+        vec![0x9E, 0x01, 0x80, 0x0F, 0xFF, 0x0F, 0x7F, 0x8A, 0x40, 0xC0],
+    ));
+    let mut device = new(&expected);
+    let mut buffer = [0u8; 10];
+    let mut frames = device.read_fifo_frames(&mut buffer).unwrap();
+    let frame = frames.next().unwrap();
+    assert_eq!(frame.x_mg(Scale::Range16G), Some(-15992));
+    assert_eq!(frame.y_mg(Scale::Range16G), Some(-8));
+    assert_eq!(frame.z_mg(Scale::Range16G), Some(15992));
+    assert_eq!(frame.x_g(Scale::Range2G), Some(-2047.0 / 1024.0));
+    let frame = frames.next().unwrap();
+    assert_eq!(frame.x_mg(Scale::Range4G), Some(2000));
+    assert_eq!(frame.y_mg(Scale::Range4G), None);
+    assert_eq!(frame.z_mg(Scale::Range4G), Some(-2000));
+    assert_eq!(frame.y_g(Scale::Range4G), None);
+    assert_eq!(frame.z_g(Scale::Range4G), Some(-2.0));
+    assert!(frames.next().is_none());
+    cleanup(device);
+}
+
 #[test]
 fn flush_fifo() {
     let mut expected = Vec::new();
@@ -802,13 +1244,26 @@ fn config_accel() {
         vec![0x90],
     ));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x19, 0xE2]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1A, 0xFB]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1B, 0x08]));
+    expected.push(Transaction::write(
+        DEFAULT_ADDR,
+        vec![0x19, 0xE2, 0xFB, 0x08],
+    ));
+    // Device reports the new power mode
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x03],
+        vec![0x14],
+    ));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x19, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1A, 0x05]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1B, 0x00]));
+    expected.push(Transaction::write(
+        DEFAULT_ADDR,
+        vec![0x19, 0x00, 0x05, 0x00],
+    ));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x03],
+        vec![0x10],
+    ));
 
     let mut device = new(&expected);
 
@@ -853,11 +1308,9 @@ fn config_interrupts() {
     expected.push(Transaction::write(DEFAULT_ADDR, vec![0x3F, 0x10]));
     expected.push(Transaction::write(DEFAULT_ADDR, vec![0x4A, 0x10]));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1F, 0xEE]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x20, 0x9D]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1F, 0xEE, 0x9D]));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1F, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x20, 0x00]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1F, 0x00, 0x00]));
 
     let mut device = new(&expected);
 
@@ -923,18 +1376,18 @@ fn config_int_pins() {
         vec![0x90],
     ));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x21, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x22, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x23, 0xDD]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x24, 0x66]));
+    expected.push(Transaction::write(
+        DEFAULT_ADDR,
+        vec![0x21, 0xFF, 0xFF, 0xDD, 0x66],
+    ));
 
     expected.push(Transaction::write(DEFAULT_ADDR, vec![0x21, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x23, 0xD0]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x24, 0x60]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x23, 0xD0, 0x60]));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x22, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x23, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x24, 0x00]));
+    expected.push(Transaction::write(
+        DEFAULT_ADDR,
+        vec![0x22, 0x00, 0x00, 0x00],
+    ));
 
     let mut device = new(&expected);
 
@@ -1004,15 +1457,15 @@ fn config_fifo() {
         vec![0x90],
     ));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x26, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x27, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x28, 0x03]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x29, 0x01]));
+    expected.push(Transaction::write(
+        DEFAULT_ADDR,
+        vec![0x26, 0xFF, 0xFF, 0x03, 0x01],
+    ));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x26, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x27, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x28, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x29, 0x00]));
+    expected.push(Transaction::write(
+        DEFAULT_ADDR,
+        vec![0x26, 0x00, 0x00, 0x00, 0x00],
+    ));
 
     let mut device = new(&expected);
 
@@ -1055,11 +1508,9 @@ fn config_auto_lp() {
         vec![0x90],
     ));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x2A, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x2B, 0xFB]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x2A, 0xFF, 0xFB]));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x2A, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x2B, 0x00]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x2A, 0x00, 0x00]));
 
     let mut device = new(&expected);
 
@@ -1094,11 +1545,9 @@ fn config_autowkup() {
         vec![0x90],
     ));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x2C, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x2D, 0xF6]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x2C, 0xFF, 0xF6]));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x2C, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x2D, 0x00]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x2C, 0x00, 0x00]));
 
     let mut device = new(&expected);
 
@@ -1131,19 +1580,18 @@ fn config_wkup_int() {
         vec![0x90],
     ));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x30, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x31, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x32, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x33, 0xFF]));
+    expected.push(Transaction::write(
+        DEFAULT_ADDR,
+        vec![0x30, 0xFF, 0xFF, 0xFF, 0xFF],
+    ));
     // Enable the interrupt last
     expected.push(Transaction::write(DEFAULT_ADDR, vec![0x2F, 0xFE]));
 
     // Disable the interrupt first, keeping other settings intact
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x2F, 0x1E]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x30, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x31, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x32, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x33, 0x00]));
+    expected.push(Transaction::write(
+        DEFAULT_ADDR,
+        vec![0x2F, 0x1E, 0x00, 0x00, 0x00, 0x00],
+    ));
     // Re-enable / write IntConfig0 changes last
     expected.push(Transaction::write(DEFAULT_ADDR, vec![0x2F, 0x00]));
 
@@ -1182,25 +1630,17 @@ fn config_orientchg_int() {
         vec![0x90],
     ));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x35, 0xF8]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x36, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x38, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x39, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x3A, 0x0F]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x3B, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x3C, 0x0F]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x3D, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x3E, 0x0F]));
-
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x35, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x36, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x38, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x39, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x3A, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x3B, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x3C, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x3D, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x3E, 0x00]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x35, 0xF8, 0xFF]));
+    expected.push(Transaction::write(
+        DEFAULT_ADDR,
+        vec![0x38, 0xFF, 0xFF, 0x0F, 0xFF, 0x0F, 0xFF, 0x0F],
+    ));
+
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x35, 0x00, 0x00]));
+    expected.push(Transaction::write(
+        DEFAULT_ADDR,
+        vec![0x38, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    ));
 
     let mut device = new(&expected);
 
@@ -1239,29 +1679,19 @@ fn config_gen1_int() {
         vec![0x90],
     ));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x3F, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x40, 0x03]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x41, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x42, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x43, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x44, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x45, 0x0F]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x46, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x47, 0x0F]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x48, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x49, 0x0F]));
-
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x3F, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x40, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x41, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x42, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x43, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x44, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x45, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x46, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x47, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x48, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x49, 0x00]));
+    expected.push(Transaction::write(
+        DEFAULT_ADDR,
+        vec![
+            0x3F, 0xFF, 0x03, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 0xFF, 0x0F, 0xFF, 0x0F,
+        ],
+    ));
+
+    expected.push(Transaction::write(
+        DEFAULT_ADDR,
+        vec![
+            0x3F, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ],
+    ));
 
     let mut device = new(&expected);
 
@@ -1306,29 +1736,19 @@ fn config_gen2_int() {
         vec![0x90],
     ));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x4A, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x4B, 0x03]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x4C, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x4D, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x4E, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x4F, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x50, 0x0F]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x51, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x52, 0x0F]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x53, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x54, 0x0F]));
-
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x4A, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x4B, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x4C, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x4D, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x4E, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x4F, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x50, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x51, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x52, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x53, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x54, 0x00]));
+    expected.push(Transaction::write(
+        DEFAULT_ADDR,
+        vec![
+            0x4A, 0xFF, 0x03, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 0xFF, 0x0F, 0xFF, 0x0F,
+        ],
+    ));
+
+    expected.push(Transaction::write(
+        DEFAULT_ADDR,
+        vec![
+            0x4A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ],
+    ));
 
     let mut device = new(&expected);
 
@@ -1373,11 +1793,9 @@ fn config_actchg_int() {
         vec![0x90],
     ));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x55, 0xFF]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x56, 0xF4]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x55, 0xFF, 0xF4]));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x55, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x56, 0x00]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x55, 0x00, 0x00]));
 
     let mut device = new(&expected);
 
@@ -1412,11 +1830,9 @@ fn config_tap() {
         vec![0x90],
     ));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x57, 0x17]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x58, 0x3F]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x57, 0x17, 0x3F]));
 
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x57, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x58, 0x00]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x57, 0x00, 0x00]));
 
     let mut device = new(&expected);
 
@@ -1444,17 +1860,54 @@ fn config_tap() {
     cleanup(device);
 }
 
-fn self_test_setup(expected: &mut Vec<Transaction>) {
-    // Disable Interrupts
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1F, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x20, 0x00]));
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x2D, 0xF4]));
+#[test]
+fn apply_config() {
+    use bma400::config::{AccConfig, IntConfig, IntPinConfig};
+    let mut expected = Vec::new();
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x00],
+        vec![0x90],
+    ));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x20, 0x04]));
 
-    // Disable FIFO
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x26, 0x1F]));
+    // Disable interrupts
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x20, 0x00]));
+    // ODR
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1A, 0x48]));
+    // Pin mapping
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x21, 0x04]));
+    // Enable interrupts
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1F, 0x04]));
 
-    // Set PowerMode
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x19, 0xE2]));
+    let mut device = new(&expected);
+    device
+        .config_interrupts()
+        .with_s_tap_int(true)
+        .write()
+        .unwrap();
+    let config = device
+        .device_config()
+        .with_accel(AccConfig::new().with_odr(OutputDataRate::Hz100))
+        .with_int_pins(IntPinConfig::new().with_gen1(InterruptPins::Int1))
+        .with_interrupts(IntConfig::new().with_gen1_int(true));
+    device.apply_config(&config).unwrap();
+    // No changes
+    device.apply_config(&config).unwrap();
+    cleanup(device);
+}
+
+fn self_test_setup(expected: &mut Vec<Transaction>) {
+    // Disable Interrupts
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1F, 0x00]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x20, 0x00]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x2D, 0xF4]));
+
+    // Disable FIFO
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x26, 0x1F]));
+
+    // Set PowerMode
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x19, 0xE2]));
 
     // Set Range = 4G, OSR = OSR3, ODR = 100Hz
     expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1A, 0x78]));
@@ -1582,16 +2035,11 @@ fn perform_self_test() {
     expected.push(Transaction::write(DEFAULT_ADDR, vec![0x4A, 0x10]));
 
     // Set all non-power mode settings in AccConfig0
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x19, 0xE0]));
-
     // Set Range = 2G, OSR = OSR0, ODR = 200Hz
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1A, 0x09]));
-
-    // Set IntConfig0
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1F, 0xEE]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x19, 0xE0, 0x09]));
 
-    // Set IntConfig1
-    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x20, 0x9D]));
+    // Set IntConfig0, IntConfig1
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1F, 0xEE, 0x9D]));
 
     // Set Wakeup Int, Settings
     expected.push(Transaction::write(DEFAULT_ADDR, vec![0x2D, 0xF6]));
@@ -1707,6 +2155,17 @@ fn soft_reset() {
         vec![0x90],
     ));
 
+    // Command decoder busy, then ready
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x03],
+        vec![0x00],
+    ));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x03],
+        vec![0x10],
+    ));
     expected.push(Transaction::write(DEFAULT_ADDR, vec![0x7E, 0xB6]));
     expected.push(Transaction::write_read(
         DEFAULT_ADDR,
@@ -1718,3 +2177,490 @@ fn soft_reset() {
     device.soft_reset().unwrap();
     cleanup(device);
 }
+
+#[test]
+fn wait_for_status() {
+    let mut expected = Vec::new();
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x00],
+        vec![0x90],
+    ));
+    // Data ready on the second poll
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x03],
+        vec![0x10],
+    ));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x03],
+        vec![0x90],
+    ));
+    // Command decoder never ready
+    for _ in 0..3 {
+        expected.push(Transaction::write_read(
+            DEFAULT_ADDR,
+            vec![0x03],
+            vec![0x00],
+        ));
+    }
+    // Power mode change
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x19, 0x01]));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x03],
+        vec![0x10],
+    ));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x03],
+        vec![0x12],
+    ));
+    let expected_delay = [
+        DelayTransaction::delay_us(100),
+        DelayTransaction::delay_us(100),
+        DelayTransaction::delay_us(50),
+        DelayTransaction::delay_us(100),
+    ];
+    let mut timer = CheckedDelay::new(&expected_delay);
+    let mut device = new(&expected);
+
+    device.wait_for_data_ready(&mut timer, 1000).unwrap();
+    assert!(matches!(
+        device.wait_for_cmd_ready(&mut timer, 150),
+        Err(BMA400Error::Timeout)
+    ));
+    device
+        .config_accel()
+        .with_power_mode(PowerMode::LowPower)
+        .write_and_wait(&mut timer)
+        .unwrap();
+    // Already in low power mode, nothing is written or polled
+    device
+        .config_accel()
+        .with_power_mode(PowerMode::LowPower)
+        .write_and_wait(&mut timer)
+        .unwrap();
+    timer.done();
+    cleanup(device);
+}
+
+#[test]
+fn status_polls_without_timer() {
+    let mut expected = Vec::new();
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x00],
+        vec![0x90],
+    ));
+    // Command decoder stays busy for all 10 reads
+    for _ in 0..10 {
+        expected.push(Transaction::write_read(
+            DEFAULT_ADDR,
+            vec![0x03],
+            vec![0x00],
+        ));
+    }
+    // Power mode never changes in 100 reads
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x19, 0x02]));
+    for _ in 0..100 {
+        expected.push(Transaction::write_read(
+            DEFAULT_ADDR,
+            vec![0x03],
+            vec![0x10],
+        ));
+    }
+    let mut device = new(&expected);
+
+    assert!(matches!(device.soft_reset(), Err(BMA400Error::Timeout)));
+    assert!(matches!(
+        device
+            .config_accel()
+            .with_power_mode(PowerMode::Normal)
+            .write(),
+        Err(BMA400Error::Timeout)
+    ));
+    cleanup(device);
+}
+
+fn configured_registers() -> Vec<u8> {
+    let mut registers = vec![0u8; 64];
+    // AccConfig0: Normal Mode
+    registers[0x00] = 0x02;
+    // AccConfig1: Range4G, ODR 200Hz
+    registers[0x01] = 0x49;
+    // IntConfig0: Data Ready Interrupt Enabled
+    registers[0x06] = 0x80;
+    // Int12IOCtrl
+    registers[0x0B] = 0x22;
+    // TapConfig1
+    registers[0x3F] = 0x06;
+    registers
+}
+
+#[test]
+fn new_adopt() {
+    let mut expected = Vec::new();
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x00],
+        vec![0x90],
+    ));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x19],
+        configured_registers(),
+    ));
+    // Only the power mode differs from the adopted configuration
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x19, 0x00]));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x03],
+        vec![0x10],
+    ));
+    let mut device = BMA400::new_i2c_adopt(Mock::new(&expected)).unwrap();
+    device
+        .config_accel()
+        .with_power_mode(PowerMode::Sleep)
+        .with_scale(Scale::Range4G)
+        .write()
+        .unwrap();
+    device
+        .config_interrupts()
+        .with_dta_rdy_int(true)
+        .write()
+        .unwrap();
+    cleanup(device);
+}
+
+#[test]
+fn new_alt_adopt() {
+    let mut expected = Vec::new();
+    expected.push(Transaction::write_read(0b10101, vec![0x00], vec![0x90]));
+    expected.push(Transaction::write_read(
+        0b10101,
+        vec![0x19],
+        configured_registers(),
+    ));
+    let mut i2c = Mock::new(&expected);
+    let result = BMA400::new_i2c_alt_adopt(&mut i2c);
+    assert!(result.is_ok());
+    i2c.done();
+}
+
+#[test]
+fn resync_config() {
+    let mut expected = Vec::new();
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x00],
+        vec![0x90],
+    ));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x19],
+        configured_registers(),
+    ));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1F, 0x00]));
+    let mut device = new(&expected);
+    device.resync_config().unwrap();
+    // The shadow config now matches the device, so only the change is written
+    device
+        .config_accel()
+        .with_power_mode(PowerMode::Normal)
+        .write()
+        .unwrap();
+    device
+        .config_interrupts()
+        .with_dta_rdy_int(false)
+        .write()
+        .unwrap();
+    cleanup(device);
+}
+
+#[test]
+fn verify_writes() {
+    let mut expected = Vec::new();
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x00],
+        vec![0x90],
+    ));
+    // Write, read back, check ErrReg
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x19, 0x02]));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x19],
+        vec![0x02],
+    ));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x02],
+        vec![0x00],
+    ));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x03],
+        vec![0x14],
+    ));
+    // Reserved bits are ignored
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1B, 0x08]));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x1B],
+        vec![0x0B],
+    ));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x02],
+        vec![0x00],
+    ));
+    // Value not accepted
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x19, 0x00]));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x19],
+        vec![0x02],
+    ));
+    // Previous value restored
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x19, 0x02]));
+    // Command error
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1A, 0x48]));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x1A],
+        vec![0x48],
+    ));
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x02],
+        vec![0x02],
+    ));
+    // Previous value restored
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1A, 0x49]));
+    // Disabled for a single builder
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1A, 0x47]));
+
+    let mut device = new(&expected);
+    device.set_verify_writes(true);
+    device
+        .config_accel()
+        .with_power_mode(PowerMode::Normal)
+        .write()
+        .unwrap();
+    device
+        .config_accel()
+        .with_reg_dta_src(DataSource::AccFilt2Lp)
+        .write()
+        .unwrap();
+    let result = device
+        .config_accel()
+        .with_power_mode(PowerMode::Sleep)
+        .write();
+    assert!(matches!(
+        result,
+        Err(BMA400Error::VerifyFailed {
+            register: 0x19,
+            expected: 0x00,
+            actual: 0x02
+        })
+    ));
+    let result = device
+        .config_accel()
+        .with_odr(OutputDataRate::Hz100)
+        .write();
+    assert!(matches!(
+        result,
+        Err(BMA400Error::VerifyFailed {
+            register: 0x02,
+            expected: 0x00,
+            actual: 0x02
+        })
+    ));
+    device
+        .config_accel()
+        .with_odr(OutputDataRate::Hz50)
+        .with_verify_writes(false)
+        .write()
+        .unwrap();
+    cleanup(device);
+}
+
+#[test]
+fn config_write_rollback() {
+    let mut expected = Vec::new();
+    expected.push(Transaction::write_read(
+        DEFAULT_ADDR,
+        vec![0x00],
+        vec![0x90],
+    ));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1A, 0x48]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1F, 0x04]));
+
+    // Disable the interrupt, then writing the config fails
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1F, 0x00]));
+    expected.push(
+        Transaction::write(DEFAULT_ADDR, vec![0x41, 0x10, 0x01]).with_error(ErrorKind::Other),
+    );
+    // Restore the config, then the interrupt
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x41, 0x00]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x42, 0x00]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1F, 0x04]));
+
+    // Nothing was applied, so the same changes are written again
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1F, 0x00]));
+    expected.push(
+        Transaction::write(DEFAULT_ADDR, vec![0x41, 0x10, 0x01]).with_error(ErrorKind::Other),
+    );
+    // Restoring one of the registers fails
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x41, 0x00]));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x42, 0x00]).with_error(ErrorKind::Other));
+    expected.push(Transaction::write(DEFAULT_ADDR, vec![0x1F, 0x04]));
+
+    let mut device = new(&expected);
+    device
+        .config_accel()
+        .with_odr(OutputDataRate::Hz100)
+        .write()
+        .unwrap();
+    device
+        .config_interrupts()
+        .with_gen1_int(true)
+        .write()
+        .unwrap();
+
+    let result = device
+        .config_gen1_int()
+        .with_threshold(0x10)
+        .with_duration(0x0100)
+        .write();
+    assert!(matches!(
+        result,
+        Err(BMA400Error::IOError(ErrorKind::Other))
+    ));
+
+    let result = device
+        .config_gen1_int()
+        .with_threshold(0x10)
+        .with_duration(0x0100)
+        .write();
+    if let Err(BMA400Error::RollbackFailed { error, unknown }) = result {
+        assert!(matches!(error, ErrorKind::Other));
+        assert_eq!(unknown.len(), 1);
+        assert!(unknown.contains(0x42));
+    } else {
+        panic!("expected RollbackFailed");
+    }
+    cleanup(device);
+}
+
+#[cfg(feature = "embedded-hal-async")]
+mod asynch {
+    use super::DEFAULT_ADDR;
+    use bma400::{BMA400, types::*};
+    use core::{
+        future::Future,
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+    use embedded_hal_mock::eh1::{
+        digital::{Edge, Mock as PinMock, State, Transaction as PinTransaction},
+        i2c::{Mock, Transaction},
+    };
+
+    // The mocks never return Pending
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn wait_for_event_latched() {
+        let mut expected = Vec::new();
+        expected.push(Transaction::write_read(
+            DEFAULT_ADDR,
+            vec![0x00],
+            vec![0x90],
+        ));
+        expected.push(Transaction::write(DEFAULT_ADDR, vec![0x23, 0x04]));
+        expected.push(Transaction::write(DEFAULT_ADDR, vec![0x20, 0x84]));
+        expected.push(Transaction::write_read(
+            DEFAULT_ADDR,
+            vec![0x0E],
+            vec![0x00, 0x04, 0x00],
+        ));
+        let pin_expected = [PinTransaction::wait_for_state(State::High)];
+        block_on(async {
+            let mut device = BMA400::new_i2c_async(Mock::new(&expected)).await.unwrap();
+            device
+                .config_int_pins()
+                .with_tap(InterruptPins::Int1)
+                .write()
+                .await
+                .unwrap();
+            device
+                .config_interrupts()
+                .with_latch_int(true)
+                .with_s_tap_int(true)
+                .write()
+                .await
+                .unwrap();
+            let mut listener = device.with_int1_pin(PinMock::new(&pin_expected));
+            let interrupts = listener.wait_for_event().await.unwrap();
+            assert_eq!(Interrupts::SINGLE_TAP, interrupts);
+            let (device, int1, int2) = listener.destroy();
+            assert!(int2.is_none());
+            int1.unwrap().done();
+            device.destroy().destroy().done();
+        });
+    }
+
+    #[test]
+    fn wait_for_event_edge() {
+        let mut expected = Vec::new();
+        expected.push(Transaction::write_read(
+            DEFAULT_ADDR,
+            vec![0x00],
+            vec![0x90],
+        ));
+        expected.push(Transaction::write(DEFAULT_ADDR, vec![0x22, 0x04]));
+        expected.push(Transaction::write(DEFAULT_ADDR, vec![0x24, 0x02]));
+        expected.push(Transaction::write_read(
+            DEFAULT_ADDR,
+            vec![0x0E],
+            vec![0x04, 0x00, 0x00],
+        ));
+        let pin_expected = [PinTransaction::wait_for_edge(Edge::Falling)];
+        block_on(async {
+            let mut device = BMA400::new_i2c_async(Mock::new(&expected)).await.unwrap();
+            device
+                .config_int_pins()
+                .with_gen1(InterruptPins::Int2)
+                .with_int2_cfg(PinOutputConfig::PushPull(PinOutputLevel::ActiveLow))
+                .write()
+                .await
+                .unwrap();
+            let mut int1 = PinMock::new(&[]);
+            let mut listener = device.with_int_pins(int1.clone(), PinMock::new(&pin_expected));
+            let events: Vec<_> = listener.wait_for_event().await.unwrap().events().collect();
+            assert_eq!(vec![InterruptEvent::Gen1], events);
+
+            // Nothing is mapped to a connected pin
+            let (device, _, int2) = listener.destroy();
+            let mut listener = device.with_int1_pin(int1.clone());
+            assert!(matches!(
+                listener.wait_for_event().await,
+                Err(BMA400Error::NoInterruptPin)
+            ));
+            int1.done();
+            int2.unwrap().done();
+            listener.destroy().0.destroy().destroy().done();
+        });
+    }
+}
@@ -0,0 +1,1066 @@
+use bma400::{
+    BMA400, I2CInterface,
+    sim::{EmulatedDevice, EngineEvent, SharedDevice, SimError},
+    types::*,
+};
+use core::cell::RefCell;
+
+fn new(sim: &RefCell<EmulatedDevice>) -> BMA400<I2CInterface<SharedDevice<'_>>> {
+    BMA400::new_i2c(SharedDevice::new(sim)).unwrap()
+}
+
+fn normal_mode(device: &mut BMA400<I2CInterface<SharedDevice<'_>>>) {
+    device
+        .config_accel()
+        .with_power_mode(PowerMode::Normal)
+        .write()
+        .unwrap();
+}
+
+#[test]
+fn reset_values() {
+    let sim = EmulatedDevice::new();
+    assert_eq!(sim.register(0x00), 0x90);
+    // AccConfig1: ODR = 200Hz, OSR = 0, Range = 4G
+    assert_eq!(sim.register(0x1A), 0x49);
+    // Reset detected
+    assert_eq!(sim.register(0x0D), 0x01);
+}
+
+#[test]
+fn wrong_address() {
+    let mut sim = EmulatedDevice::new();
+    let result = BMA400::new_i2c_alt(&mut sim);
+    assert!(matches!(
+        result,
+        Err(BMA400Error::IOError(SimError::NoAcknowledge))
+    ));
+    assert!(BMA400::new_i2c_alt(EmulatedDevice::new_alt()).is_ok());
+}
+
+#[test]
+fn spi() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = BMA400::new_spi(SharedDevice::new(&sim)).unwrap();
+    assert_eq!(device.get_id().unwrap(), 0x90);
+    device
+        .config_accel()
+        .with_power_mode(PowerMode::Normal)
+        .with_scale(Scale::Range2G)
+        .write()
+        .unwrap();
+    assert_eq!(sim.borrow().register(0x1A), 0x09);
+    // 4096 counts over 4000mg at 2g
+    sim.borrow_mut().push_sample(-30, 16, 988);
+    let m = device.get_data().unwrap();
+    assert_eq!((m.x, m.y, m.z), (-31, 16, 1012));
+    // Round-trips the driver's conversion
+    sim.borrow_mut().push_sample(-30, 16, 1000);
+    let m = device.get_data_mg().unwrap();
+    assert_eq!((m.x, m.y, m.z), (-30, 16, 1000));
+}
+
+#[test]
+fn get_data() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    // No samples are taken in sleep mode
+    sim.borrow_mut().push_sample(30, 16, 988);
+    assert!(!device.get_status().unwrap().drdy_stat());
+
+    normal_mode(&mut device);
+    sim.borrow_mut().push_sample(30, 16, 988);
+    assert!(device.get_status().unwrap().drdy_stat());
+    // 4g range has a resolution of 1.95mg
+    let m = device.get_unscaled_data().unwrap();
+    assert_eq!((m.x, m.y, m.z), (15, 8, 506));
+    let m = device.get_data().unwrap();
+    assert_eq!((m.x, m.y, m.z), (30, 16, 1012));
+    // Reading the data clears data ready
+    assert!(!device.get_status().unwrap().drdy_stat());
+
+    // Saturates at the range limit
+    sim.borrow_mut().push_sample(i16::MAX, i16::MIN, 0);
+    let m = device.get_data().unwrap();
+    assert_eq!((m.x, m.y, m.z), (4094, -4096, 0));
+}
+
+#[test]
+fn sensor_clock() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    normal_mode(&mut device);
+    // 128 ticks of 39.0625µs per sample at 200Hz
+    sim.borrow_mut().push_sample(0, 0, 0);
+    sim.borrow_mut().push_sample(0, 0, 0);
+    assert_eq!(device.get_sensor_clock().unwrap().ticks(), 256);
+}
+
+#[test]
+fn data_with_time_and_status() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    device
+        .config_interrupts()
+        .with_dta_rdy_int(true)
+        .write()
+        .unwrap();
+    normal_mode(&mut device);
+    sim.borrow_mut().push_sample(1000, 0, -1000);
+    let data = device.get_data_with_time().unwrap();
+    assert_eq!((data.measurement.x, data.measurement.z), (1024, -1024));
+    assert_eq!(data.time.ticks(), 128);
+
+    sim.borrow_mut().power_on_reset();
+    device.resync_config().unwrap();
+    device
+        .config_interrupts()
+        .with_dta_rdy_int(true)
+        .write()
+        .unwrap();
+    normal_mode(&mut device);
+    sim.borrow_mut().push_sample(0, 500, 0);
+    let status = device.get_data_with_status().unwrap();
+    assert_eq!(status.data.measurement.y, 512);
+    assert_eq!(status.data.time.ticks(), 128);
+    assert!(status.reset);
+    assert!(status.int_status0.drdy_stat());
+    assert!(!status.int_status1.s_tap_stat());
+    // Cleared on read
+    let status = device.get_data_with_status().unwrap();
+    assert!(!status.reset);
+    assert!(!status.int_status0.drdy_stat());
+}
+
+#[test]
+fn fifo() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    normal_mode(&mut device);
+    device
+        .config_fifo()
+        .with_axes(true, true, true)
+        .with_send_time_on_empty(true)
+        .write()
+        .unwrap();
+    sim.borrow_mut().push_sample(30, 16, 988);
+    sim.borrow_mut().push_sample(-30, -16, -988);
+    assert_eq!(device.get_fifo_len().unwrap(), 14);
+
+    let mut buffer = [0u8; 20];
+    let mut frames = device.read_fifo_frames(&mut buffer).unwrap();
+    let frame = frames.next().unwrap();
+    assert_eq!(
+        (frame.x(), frame.y(), frame.z()),
+        (Some(15), Some(8), Some(506))
+    );
+    let frame = frames.next().unwrap();
+    assert_eq!(
+        (frame.x(), frame.y(), frame.z()),
+        (Some(-15), Some(-8), Some(-506))
+    );
+    // Reading past the end returns the sensor time
+    let frame = frames.next().unwrap();
+    assert!(matches!(frame.frame_type(), FrameType::Time));
+    assert_eq!(frame.time(), Some(SensorTime::from_ticks(256)));
+    assert!(frames.next().is_none());
+    assert_eq!(device.get_fifo_len().unwrap(), 0);
+}
+
+#[test]
+fn fifo_chunks() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    normal_mode(&mut device);
+    device
+        .config_fifo()
+        .with_axes(true, true, true)
+        .write()
+        .unwrap();
+    for i in 0..20 {
+        sim.borrow_mut().push_sample(2 * i, -2 * i, 0);
+    }
+    // 7 byte frames read 5 bytes at a time
+    let mut decoder = FifoDecoder::new();
+    let mut buffer = [0u8; 5];
+    let mut count = 0;
+    while !decoder.empty_seen() {
+        for frame in device.read_fifo_chunk(&mut decoder, &mut buffer).unwrap() {
+            assert_eq!(frame.x(), Some(count));
+            assert_eq!(frame.y(), Some(-count));
+            count += 1;
+        }
+    }
+    assert_eq!(count, 20);
+    assert_eq!(decoder.dropped_bytes(), 0);
+}
+
+#[test]
+fn fifo_samples_follow_settings() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    normal_mode(&mut device);
+    device
+        .config_fifo()
+        .with_axes(true, true, true)
+        .write()
+        .unwrap();
+    let mut decoder = device.fifo_sample_decoder();
+    // 1000mg round-trips at every scale
+    sim.borrow_mut().push_sample(1000, 0, -1000);
+    device
+        .config_accel()
+        .with_scale(Scale::Range16G)
+        .with_filt1_bw(Filter1Bandwidth::Low)
+        .write()
+        .unwrap();
+    sim.borrow_mut().push_sample(1000, 0, -1000);
+    device
+        .config_fifo()
+        .with_src(DataSource::AccFilt2)
+        .write()
+        .unwrap();
+    sim.borrow_mut().push_sample(1000, 0, -1000);
+
+    let mut buffer = [0u8; 32];
+    let samples: Vec<FifoSample> = device
+        .read_fifo_samples(&mut decoder, &mut buffer)
+        .unwrap()
+        .collect();
+    assert!(decoder.empty_seen());
+    assert_eq!(decoder.unmatched_changes(), 0);
+    assert_eq!(samples.len(), 3);
+    for sample in &samples {
+        assert_eq!(sample.x, Some(1000));
+        assert_eq!(sample.y, Some(0));
+        assert_eq!(sample.z, Some(-1000));
+    }
+    assert_eq!(samples[0].settings, FifoSettings::default());
+    let settings = FifoSettings {
+        scale: Scale::Range16G,
+        filt1_bw: Filter1Bandwidth::Low,
+        ..FifoSettings::default()
+    };
+    assert_eq!(samples[1].settings, settings);
+    let settings = FifoSettings {
+        src: DataSource::AccFilt2,
+        ..settings
+    };
+    assert_eq!(samples[2].settings, settings);
+    assert_eq!(decoder.settings(), settings);
+
+    // Changes made after the decoder was created are passed on by the next read
+    device
+        .config_accel()
+        .with_scale(Scale::Range2G)
+        .write()
+        .unwrap();
+    sim.borrow_mut().push_sample(1000, 0, 0);
+    let sample = device
+        .read_fifo_samples(&mut decoder, &mut buffer)
+        .unwrap()
+        .next()
+        .unwrap();
+    assert_eq!(sample.x, Some(1000));
+    assert_eq!(sample.settings.scale, Scale::Range2G);
+}
+
+#[test]
+fn fifo_samples_too_many_changes() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    normal_mode(&mut device);
+    device
+        .config_fifo()
+        .with_axes(true, true, true)
+        .write()
+        .unwrap();
+    let mut decoder = device.fifo_sample_decoder();
+    sim.borrow_mut().push_sample(1000, 0, 0);
+    // 10 different settings, only 8 can be tracked between reads
+    let scales = [
+        Scale::Range2G,
+        Scale::Range4G,
+        Scale::Range8G,
+        Scale::Range16G,
+    ];
+    let odrs = [
+        OutputDataRate::Hz25,
+        OutputDataRate::Hz50,
+        OutputDataRate::Hz100,
+    ];
+    let mut changes = Vec::new();
+    for i in 0..10 {
+        device
+            .config_accel()
+            .with_scale(scales[i % 4])
+            .with_odr(odrs[i / 4])
+            .write()
+            .unwrap();
+        sim.borrow_mut().push_sample(1000, 0, 0);
+        changes.push(FifoSettings {
+            scale: scales[i % 4],
+            odr: odrs[i / 4],
+            ..FifoSettings::default()
+        });
+    }
+
+    let mut buffer = [0u8; 128];
+    let samples: Vec<FifoSample> = device
+        .read_fifo_samples(&mut decoder, &mut buffer)
+        .unwrap()
+        .collect();
+    assert_eq!(samples.len(), 11);
+    for (sample, settings) in samples[1..9].iter().zip(&changes[..8]) {
+        assert_eq!(sample.settings, *settings);
+        assert_eq!(sample.x, Some(1000));
+    }
+    // The 9th control frame has no known settings, the 8th are kept
+    assert_eq!(samples[9].settings, changes[7]);
+    assert_eq!(decoder.unmatched_changes(), 1);
+    // The last control frame moves to the current settings
+    assert_eq!(samples[10].settings, changes[9]);
+    assert_eq!(samples[10].x, Some(1000));
+    assert_eq!(decoder.settings(), changes[9]);
+}
+
+#[test]
+fn fifo_timestamps() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    device
+        .config_accel()
+        .with_power_mode(PowerMode::Normal)
+        .with_odr(OutputDataRate::Hz100)
+        .write()
+        .unwrap();
+    device
+        .config_fifo()
+        .with_axes(true, true, true)
+        .with_send_time_on_empty(true)
+        .write()
+        .unwrap();
+    for _ in 0..10 {
+        sim.borrow_mut().push_sample(0, 0, 1000);
+    }
+    let mut buffer = [0u8; 80];
+    let frames = device
+        .read_timestamped_fifo_frames(&mut buffer)
+        .unwrap()
+        .unwrap();
+    let times: Vec<u32> = frames.map(|frame| frame.time.ticks()).collect();
+    // The clock advanced 256 ticks per sample at 100Hz
+    let expected: Vec<u32> = (1..=10).map(|i| i * 256).collect();
+    assert_eq!(times, expected);
+}
+
+#[test]
+fn clock_estimation() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    device
+        .config_accel()
+        .with_power_mode(PowerMode::Normal)
+        .with_odr(OutputDataRate::Hz100)
+        .write()
+        .unwrap();
+    device
+        .config_fifo()
+        .with_axes(true, true, true)
+        .with_send_time_on_empty(true)
+        .write()
+        .unwrap();
+    // Each 256 tick (10ms nominal) sample period takes 10.3ms of host time, the sensor
+    // clock is read with up to 20µs of latency
+    let host_start = 5_000_000u64;
+    let mut estimator = ClockEstimator::new();
+    for i in 0u64..40 {
+        sim.borrow_mut().push_sample(0, 0, 1000);
+        let latency = [0, 20, 5, 12][i as usize % 4];
+        let host_us = host_start + (i + 1) * 10_300 + latency;
+        estimator.add_sample(host_us, device.get_sensor_clock().unwrap());
+    }
+    // 10ms / 10.3ms - 1
+    let ppm = estimator.rate_ppm().unwrap();
+    assert!((-29_200..=-29_050).contains(&ppm), "{ppm}");
+
+    let mut buffer = [0u8; 1024];
+    let frames = device
+        .read_timestamped_fifo_frames(&mut buffer)
+        .unwrap()
+        .unwrap();
+    let mut uncertainties = Vec::new();
+    for (i, frame) in frames.enumerate() {
+        let estimate = estimator.to_host_micros(frame.time).unwrap();
+        let actual = host_start + (i as u64 + 1) * 10_300;
+        assert!(estimate.host_us.abs_diff(actual) <= estimate.uncertainty_us);
+        uncertainties.push(estimate.uncertainty_us);
+    }
+    assert_eq!(uncertainties.len(), 40);
+    // The fit only uses the last 16 pairs, the uncertainty grows further back
+    assert!(uncertainties[24..].iter().all(|&u| u <= 20));
+    assert!(uncertainties[0] > uncertainties[23]);
+    assert!(uncertainties[23] >= uncertainties[24]);
+}
+
+#[test]
+fn fifo_8bit_and_control_frames() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    normal_mode(&mut device);
+    device
+        .config_fifo()
+        .with_axes(true, false, true)
+        .with_8bit_mode(true)
+        .write()
+        .unwrap();
+    sim.borrow_mut().push_sample(64, 0, 512);
+    device
+        .config_accel()
+        .with_odr(OutputDataRate::Hz100)
+        .write()
+        .unwrap();
+    sim.borrow_mut().push_sample(64, 0, 512);
+
+    let mut buffer = [0u8; 8];
+    let mut frames = device.read_fifo_frames(&mut buffer).unwrap();
+    let frame = frames.next().unwrap();
+    assert_eq!(
+        (frame.x(), frame.y(), frame.z()),
+        (Some(32), None, Some(256))
+    );
+    let frame = frames.next().unwrap();
+    assert_eq!(frame.acc1_chg(), Some(true));
+    assert_eq!(frame.fifo_src_chg(), Some(false));
+    let frame = frames.next().unwrap();
+    assert_eq!(
+        (frame.x(), frame.y(), frame.z()),
+        (Some(32), None, Some(256))
+    );
+    assert!(frames.next().is_none());
+}
+
+#[test]
+fn fifo_full() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    normal_mode(&mut device);
+    device
+        .config_interrupts()
+        .with_ffull_int(true)
+        .with_latch_int(true)
+        .write()
+        .unwrap();
+    device
+        .config_fifo()
+        .with_axes(true, true, true)
+        .with_stop_on_full(true)
+        .write()
+        .unwrap();
+    // 7 byte frames
+    for i in 0..146 {
+        sim.borrow_mut().push_sample(i, 0, 0);
+    }
+    assert_eq!(device.get_fifo_len().unwrap(), 1022);
+    assert!(device.get_int_status0().unwrap().ffull_stat());
+    // Stopped on full, the oldest frame is kept
+    sim.borrow_mut().push_sample(1000, 0, 0);
+    let mut buffer = [0u8; 7];
+    let frame = device
+        .read_fifo_frames(&mut buffer)
+        .unwrap()
+        .next()
+        .unwrap();
+    assert_eq!(frame.x(), Some(0));
+
+    // Without stop on full, the oldest frames are overwritten
+    device
+        .config_fifo()
+        .with_stop_on_full(false)
+        .write()
+        .unwrap();
+    sim.borrow_mut().push_sample(1000, 0, 0);
+    sim.borrow_mut().push_sample(1000, 0, 0);
+    assert_eq!(device.get_fifo_len().unwrap(), 1022);
+    let frame = device
+        .read_fifo_frames(&mut buffer)
+        .unwrap()
+        .next()
+        .unwrap();
+    assert_eq!(frame.x(), Some(1));
+}
+
+#[test]
+fn flush_fifo() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    normal_mode(&mut device);
+    device
+        .config_fifo()
+        .with_axes(true, true, true)
+        .write()
+        .unwrap();
+    sim.borrow_mut().push_sample(0, 0, 0);
+    assert_eq!(device.get_fifo_len().unwrap(), 7);
+    device.flush_fifo().unwrap();
+    assert_eq!(device.get_fifo_len().unwrap(), 0);
+}
+
+#[test]
+fn watermark() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    normal_mode(&mut device);
+    device
+        .config_interrupts()
+        .with_fwm_int(true)
+        .write()
+        .unwrap();
+    device
+        .config_fifo()
+        .with_axes(true, true, true)
+        .with_watermark_thresh(14)
+        .write()
+        .unwrap();
+    sim.borrow_mut().push_sample(0, 0, 0);
+    assert!(!device.get_int_status0().unwrap().fwm_stat());
+    sim.borrow_mut().push_sample(0, 0, 0);
+    assert!(device.get_int_status0().unwrap().fwm_stat());
+}
+
+#[test]
+fn latched_interrupts() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    normal_mode(&mut device);
+    device
+        .config_interrupts()
+        .with_dta_rdy_int(true)
+        .with_s_tap_int(true)
+        .write()
+        .unwrap();
+
+    // Not latched, held until the next sample
+    sim.borrow_mut().trigger(EngineEvent::SingleTap);
+    assert!(device.get_status().unwrap().int_active());
+    sim.borrow_mut().push_sample(0, 0, 0);
+    assert!(!device.get_int_status1().unwrap().s_tap_stat());
+    assert!(device.get_int_status0().unwrap().drdy_stat());
+
+    // Latched, held until read
+    device
+        .config_interrupts()
+        .with_latch_int(true)
+        .write()
+        .unwrap();
+    sim.borrow_mut().trigger(EngineEvent::SingleTap);
+    sim.borrow_mut().push_sample(0, 0, 0);
+    assert!(device.get_int_status1().unwrap().s_tap_stat());
+    assert!(!device.get_int_status1().unwrap().s_tap_stat());
+
+    // Disabled interrupts are not raised
+    sim.borrow_mut().trigger(EngineEvent::DoubleTap);
+    assert!(!device.get_int_status1().unwrap().d_tap_stat());
+}
+
+#[test]
+fn interrupt_events() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    normal_mode(&mut device);
+    device
+        .config_interrupts()
+        .with_dta_rdy_int(true)
+        .with_d_tap_int(true)
+        .with_step_int(true)
+        .with_latch_int(true)
+        .write()
+        .unwrap();
+    sim.borrow_mut().push_sample(0, 0, 0);
+    sim.borrow_mut().trigger(EngineEvent::DoubleTap);
+    sim.borrow_mut().add_steps(1);
+    let events: Vec<InterruptEvent> = device.get_interrupts().unwrap().events().collect();
+    assert_eq!(
+        events,
+        [
+            InterruptEvent::DataReady,
+            InterruptEvent::DoubleTap,
+            InterruptEvent::Step(1),
+        ]
+    );
+    // Cleared on read
+    assert!(device.get_interrupts().unwrap().is_empty());
+}
+
+#[test]
+fn activity_change() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    // The activity change interrupt requires 100Hz on AccFilt1
+    device
+        .config_accel()
+        .with_odr(OutputDataRate::Hz100)
+        .write()
+        .unwrap();
+    device
+        .config_interrupts()
+        .with_actch_int(true)
+        .write()
+        .unwrap();
+    sim.borrow_mut().trigger(EngineEvent::ActivityChange {
+        x: true,
+        y: false,
+        z: true,
+    });
+    let status = device.get_int_status2().unwrap();
+    assert!(status.actch_x_stat());
+    assert!(!status.actch_y_stat());
+    assert!(status.actch_z_stat());
+}
+
+#[test]
+fn step_counter() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    sim.borrow_mut().add_steps(12);
+    sim.borrow_mut().set_activity(Activity::Walk);
+    assert_eq!(device.get_step_count().unwrap(), 12);
+    assert!(matches!(
+        device.get_step_activity().unwrap(),
+        Activity::Walk
+    ));
+    device.clear_step_count().unwrap();
+    assert_eq!(device.get_step_count().unwrap(), 0);
+}
+
+#[test]
+fn soft_reset() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    assert!(device.get_reset_status().unwrap());
+    assert!(!device.get_reset_status().unwrap());
+    normal_mode(&mut device);
+    sim.borrow_mut().add_steps(3);
+
+    // soft_reset() clears the reset flag itself
+    device.soft_reset().unwrap();
+    assert!(!device.get_reset_status().unwrap());
+    assert_eq!(sim.borrow().register(0x19), 0x00);
+    assert_eq!(sim.borrow().step_count(), 0);
+    assert!(matches!(
+        device.get_status().unwrap().power_mode(),
+        PowerMode::Sleep
+    ));
+}
+
+#[test]
+fn soft_reset_with_delay() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    let mut delay = embedded_hal_mock::eh1::delay::NoopDelay::new();
+    normal_mode(&mut device);
+    device
+        .config_int_pins()
+        .with_step(InterruptPins::Int1)
+        .write()
+        .unwrap();
+    device
+        .config_interrupts()
+        .with_step_int(true)
+        .write()
+        .unwrap();
+    device
+        .config_fifo()
+        .with_axes(true, true, true)
+        .write()
+        .unwrap();
+    let configured: Vec<u8> = (0x19..=0x58)
+        .map(|addr| sim.borrow().register(addr))
+        .collect();
+
+    // Restore the previous configuration
+    device.soft_reset_with_delay(&mut delay, true).unwrap();
+    assert!(!device.get_reset_status().unwrap());
+    let restored: Vec<u8> = (0x19..=0x58)
+        .map(|addr| sim.borrow().register(addr))
+        .collect();
+    assert_eq!(configured, restored);
+
+    // Return to defaults
+    device.soft_reset_with_delay(&mut delay, false).unwrap();
+    assert_eq!(sim.borrow().register(0x19), 0x00);
+    assert_eq!(sim.borrow().register(0x1F), 0x00);
+    assert!(matches!(
+        device.get_status().unwrap().power_mode(),
+        PowerMode::Sleep
+    ));
+}
+
+#[test]
+fn reset_detection() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    normal_mode(&mut device);
+    device
+        .config_int_pins()
+        .with_drdy(InterruptPins::Int1)
+        .write()
+        .unwrap();
+    device
+        .config_interrupts()
+        .with_dta_rdy_int(true)
+        .write()
+        .unwrap();
+    let configured: Vec<u8> = (0x19..=0x58)
+        .map(|addr| sim.borrow().register(addr))
+        .collect();
+    let restored = || -> Vec<u8> {
+        (0x19..=0x58)
+            .map(|addr| sim.borrow().register(addr))
+            .collect()
+    };
+
+    // Checked every 3rd read
+    assert!(device.get_reset_status().unwrap());
+    device.set_reset_detection(ResetDetection::EveryNthRead(3));
+    sim.borrow_mut().power_on_reset();
+    device.get_data().unwrap();
+    device.get_data().unwrap();
+    assert!(matches!(
+        device.get_data(),
+        Err(BMA400Error::DeviceWasReset)
+    ));
+    assert_eq!(configured, restored());
+    device.get_data().unwrap();
+
+    // Checked on interrupt status reads
+    device.set_reset_detection(ResetDetection::OnInterruptStatus);
+    sim.borrow_mut().power_on_reset();
+    device.get_data().unwrap();
+    assert!(matches!(
+        device.get_int_status0(),
+        Err(BMA400Error::DeviceWasReset)
+    ));
+    assert_eq!(configured, restored());
+    sim.borrow_mut().power_on_reset();
+    assert!(matches!(
+        device.get_data_with_status(),
+        Err(BMA400Error::DeviceWasReset)
+    ));
+    assert_eq!(configured, restored());
+    assert!(!device.get_data_with_status().unwrap().reset);
+
+    // A diagnostic dump sees the reset flag too
+    sim.borrow_mut().power_on_reset();
+    assert!(matches!(
+        device.dump_registers(),
+        Err(BMA400Error::DeviceWasReset)
+    ));
+    assert_eq!(configured, restored());
+    let snapshot = device.dump_registers().unwrap();
+    assert_eq!(0, device.diff_config(&snapshot).count());
+}
+
+#[test]
+fn invalid_command() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    assert!(!device.get_cmd_error().unwrap());
+    // Unknown command
+    embedded_hal::i2c::I2c::write(&mut *sim.borrow_mut(), 0b10100, &[0x7E, 0x00]).unwrap();
+    assert!(device.get_cmd_error().unwrap());
+    assert!(!device.get_cmd_error().unwrap());
+}
+
+#[test]
+fn dump_registers() {
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    let before = device.dump_registers().unwrap();
+    normal_mode(&mut device);
+    device
+        .config_accel()
+        .with_scale(Scale::Range16G)
+        .write()
+        .unwrap();
+    let after = device.dump_registers().unwrap();
+    assert_eq!(0x90, after.as_bytes()[0x00]);
+    assert_eq!(Some(0xC9), after.get(0x1A));
+    let diffs: Vec<_> = before.diff(&after).map(|diff| diff.name).collect();
+    // Power mode in the status register, reset flag cleared by the first dump
+    assert_eq!(vec!["Status", "Event", "AccConfig0", "AccConfig1"], diffs);
+    let debug = format!("{:?}", after);
+    assert!(debug.contains("ChipId: 0x90"));
+    assert!(debug.contains("AccConfig0: PW_CFG1,"));
+    assert_eq!(0, device.diff_config(&after).count());
+
+    // Changed behind the driver's back
+    embedded_hal::i2c::I2c::write(&mut *sim.borrow_mut(), 0b10100, &[0x1F, 0x80]).unwrap();
+    let snapshot = device.dump_registers().unwrap();
+    let diff = device.diff_config(&snapshot).next().unwrap();
+    assert_eq!((0x1F, 0x00, 0x80), (diff.addr, diff.left, diff.right));
+    assert_eq!(0x80, diff.changed_bits());
+}
+
+#[test]
+fn apply_profile() {
+    use bma400::config::{AccConfig, IntConfig, IntPinConfig, Profile, TapConfig};
+
+    const TAP: Profile = Profile::new()
+        .with_accel(
+            AccConfig::new()
+                .with_power_mode(PowerMode::Normal)
+                .with_odr(OutputDataRate::Hz200),
+        )
+        .with_tap(TapConfig::new().with_sensitivity(TapSensitivity::SENS0))
+        .with_int_pins(IntPinConfig::new().with_tap(InterruptPins::Int1))
+        .with_interrupts(IntConfig::new().with_s_tap_int(true));
+    const SLOW: Profile =
+        Profile::new().with_accel(AccConfig::new().with_odr(OutputDataRate::Hz25));
+
+    // The same profile can be shared by several devices
+    let sims = [
+        RefCell::new(EmulatedDevice::new()),
+        RefCell::new(EmulatedDevice::new()),
+    ];
+    for sim in &sims {
+        let mut device = new(sim);
+        device.apply(&TAP).unwrap();
+        let sim = sim.borrow();
+        assert_eq!(0x02, sim.register(0x19));
+        assert_eq!(0x04, sim.register(0x20));
+        assert_eq!(0x04, sim.register(0x23));
+        assert_eq!(0x00, sim.register(0x57));
+    }
+
+    // Same validation as the builders, nothing is written
+    let mut device = new(&sims[0]);
+    device.apply(&TAP).unwrap();
+    assert!(matches!(
+        device.apply(&SLOW),
+        Err(BMA400Error::ConfigBuildError(
+            ConfigError::TapIntEnabledInvalidODR
+        ))
+    ));
+    assert_eq!(0x49, sims[0].borrow().register(0x1A));
+
+    // Sections not in the profile are left as they are
+    device
+        .config_interrupts()
+        .with_s_tap_int(false)
+        .write()
+        .unwrap();
+    device.apply(&SLOW).unwrap();
+    let sim = sims[0].borrow();
+    assert_eq!(0x46, sim.register(0x1A));
+    assert_eq!(0x04, sim.register(0x23));
+}
+
+#[test]
+fn apply_profile_in_units() {
+    use bma400::config::{
+        ActChgConfig, AutoLpConfig, AutoWakeupConfig, Gen1IntConfig, OrientChgConfig, Profile,
+    };
+
+    // `Result::unwrap()` can't be called in a const
+    macro_rules! ok {
+        ($config:expr) => {
+            match $config {
+                Ok(config) => config,
+                Err(_) => panic!("out of range"),
+            }
+        };
+    }
+    const UNITS: Profile = Profile::new()
+        .with_orientchg_int(ok!(
+            ok!(OrientChgConfig::new().with_threshold_mg(248)).with_duration_ms(100)
+        ))
+        .with_actchg_int(ok!(ActChgConfig::new().with_threshold_mg(100)))
+        .with_gen1_int(ok!(Gen1IntConfig::new().with_threshold_mg(2043)))
+        .with_auto_lp(ok!(AutoLpConfig::new().with_timeout_ms(1000)))
+        .with_autowkup(ok!(AutoWakeupConfig::new().with_period_ms(10_238)));
+
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    device.apply(&UNITS).unwrap();
+    let sim = sim.borrow();
+    assert_eq!(31, sim.register(0x36));
+    assert_eq!(10, sim.register(0x38));
+    assert_eq!(13, sim.register(0x55));
+    assert_eq!(255, sim.register(0x41));
+    // 400 * 2.5ms
+    assert_eq!(0x19, sim.register(0x2A));
+    assert_eq!(0xFF, sim.register(0x2C));
+    assert!(matches!(
+        OrientChgConfig::new().with_duration_ms(2555),
+        Err(ConfigError::DurationOutOfRange)
+    ));
+}
+
+#[test]
+fn apply_config() {
+    use bma400::config::{AccConfig, DeviceConfig, Gen1IntConfig, IntConfig, IntPinConfig};
+
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    device
+        .config_interrupts()
+        .with_s_tap_int(true)
+        .write()
+        .unwrap();
+
+    // Every broken rule is reported, nothing is written
+    let invalid = DeviceConfig::new()
+        .with_accel(AccConfig::new().with_odr(OutputDataRate::Hz50))
+        .with_gen1_int(Gen1IntConfig::new().with_src(DataSource::AccFilt1))
+        .with_interrupts(IntConfig::new().with_gen1_int(true).with_s_tap_int(true));
+    let Err(BMA400Error::InvalidConfig(errors)) = device.apply_config(&invalid) else {
+        panic!("expected InvalidConfig");
+    };
+    assert_eq!(2, errors.len());
+    assert!(errors.contains(ConfigError::Filt1InterruptInvalidODR));
+    assert!(errors.contains(ConfigError::TapIntEnabledInvalidODR));
+    assert_eq!(0x04, sim.borrow().register(0x20));
+
+    // Switching from taps to a generic interrupt on AccFilt1 needs the ODR and the
+    // interrupts changed together, which no single builder allows
+    let config = device
+        .device_config()
+        .with_accel(
+            AccConfig::new()
+                .with_power_mode(PowerMode::Normal)
+                .with_odr(OutputDataRate::Hz100),
+        )
+        .with_gen1_int(
+            Gen1IntConfig::new()
+                .with_src(DataSource::AccFilt1)
+                .with_threshold(0x10),
+        )
+        .with_int_pins(IntPinConfig::new().with_gen1(InterruptPins::Int1))
+        .with_interrupts(IntConfig::new().with_gen1_int(true));
+    assert!(
+        device
+            .config_accel()
+            .with_odr(OutputDataRate::Hz100)
+            .write()
+            .is_err()
+    );
+    device.apply_config(&config).unwrap();
+    {
+        let sim = sim.borrow();
+        assert_eq!(0x02, sim.register(0x19));
+        assert_eq!(0x48, sim.register(0x1A));
+        assert_eq!(0x04, sim.register(0x1F));
+        assert_eq!(0x00, sim.register(0x20));
+        assert_eq!(0x04, sim.register(0x21));
+        assert_eq!(0x10, sim.register(0x41));
+    }
+    let snapshot = device.dump_registers().unwrap();
+    assert_eq!(0, device.diff_config(&snapshot).count());
+}
+
+#[test]
+fn apply_config_strict() {
+    use bma400::config::{AutoWakeupConfig, DeviceConfig, FifoConfig};
+
+    let sim = RefCell::new(EmulatedDevice::new());
+    let mut device = new(&sim);
+    let config = DeviceConfig::new()
+        .with_fifo(FifoConfig::new().with_watermark_thresh(2000))
+        .with_autowkup(AutoWakeupConfig::new().with_activity_int(true));
+    assert!(config.validate().is_ok());
+
+    // Strict mode reports the clamped watermark and the auto wake-up relying on the
+    // disabled wake-up interrupt, nothing is written
+    device.set_strict_config(true);
+    let Err(BMA400Error::InvalidConfig(errors)) = device.apply_config(&config) else {
+        panic!("expected InvalidConfig");
+    };
+    assert_eq!(Err(errors), config.validate_strict());
+    assert_eq!(2, errors.len());
+    assert!(errors.contains(ConfigError::FifoWatermarkOutOfRange));
+    assert!(errors.contains(ConfigError::AutoWakeupIntDisabled));
+    assert_eq!(0x00, sim.borrow().register(0x28));
+
+    // Outside strict mode the watermark is clamped to 1024 (0x400)
+    device.set_strict_config(false);
+    device.apply_config(&config).unwrap();
+    assert_eq!(0x00, sim.borrow().register(0x27));
+    assert_eq!(0x04, sim.borrow().register(0x28));
+
+    // The stored configuration holds no record of the adjusted watermark
+    device.set_strict_config(true);
+    device
+        .config_wkup_int()
+        .with_axes(true, true, true)
+        .write()
+        .unwrap();
+}
+
+#[test]
+fn typestate_transitions() {
+    use bma400::typestate::{Hz100, Hz200, Normal};
+
+    let sim = RefCell::new(EmulatedDevice::new());
+    let device = new(&sim);
+    let mut device: BMA400<_, Normal<Hz200>> = device.into_normal().map_err(|(_, e)| e).unwrap();
+    // AccConfig0: Normal mode, AccConfig1: ODR unchanged at 200Hz
+    assert_eq!(0x02, sim.borrow().register(0x19));
+    assert_eq!(0x49, sim.borrow().register(0x1A));
+    device
+        .config_interrupts()
+        .with_s_tap_int(true)
+        .write()
+        .unwrap();
+    device
+        .config_fifo()
+        .with_axes(true, true, true)
+        .write()
+        .unwrap();
+    sim.borrow_mut().push_sample(30, 16, 988);
+    let mut buffer = [0u8; 7];
+    let mut frames = device.read_fifo_frames(&mut buffer).unwrap();
+    assert_eq!(frames.next().unwrap().x(), Some(15));
+
+    // Taps need 200Hz, the device is handed back unchanged
+    let Err((device, error)) = device.into_normal::<Hz100>() else {
+        panic!("expected the transition to fail");
+    };
+    assert!(matches!(
+        error,
+        BMA400Error::ConfigBuildError(ConfigError::TapIntEnabledInvalidODR)
+    ));
+    assert_eq!(0x49, sim.borrow().register(0x1A));
+
+    let device = device.into_sleep().map_err(|(_, e)| e).unwrap();
+    assert_eq!(0x00, sim.borrow().register(0x19));
+    let mut device = device.into_unchecked();
+    assert_eq!(PowerMode::Sleep, device.get_status().unwrap().power_mode());
+
+    // The device would leave normal mode on its own
+    device
+        .config_auto_lp()
+        .with_timeout(400)
+        .with_auto_lp_trigger(AutoLPTimeoutTrigger::TimeoutEnabledNoReset)
+        .write()
+        .unwrap();
+    let Err((mut device, error)) = device.into_normal::<Hz200>() else {
+        panic!("expected the transition to fail");
+    };
+    assert!(matches!(
+        error,
+        BMA400Error::ConfigBuildError(ConfigError::AutoPowerModeEnabled)
+    ));
+    assert_eq!(0x00, sim.borrow().register(0x19));
+    device
+        .config_auto_lp()
+        .with_auto_lp_trigger(AutoLPTimeoutTrigger::TimeoutDisabled)
+        .write()
+        .unwrap();
+    device
+        .config_autowkup()
+        .with_periodic_wakeup(true)
+        .write()
+        .unwrap();
+    assert!(device.into_low_power().is_err());
+}
@@ -199,7 +199,7 @@ fn get_sensor_clock() {
     expected_io.push_read(0x8A, vec![0xF8, 0xFF, 0xFF]);
     let mut device = new(&expected_io);
     let t = device.get_sensor_clock().unwrap();
-    assert_eq!(t, 0xFFFFF8);
+    assert_eq!(t.ticks(), 0xFFFFF8);
     cleanup(device);
 }
 
@@ -501,7 +501,7 @@ fn read_fifo_frames() {
                 assert_eq!(frame.z(), Some(2047));
             }
             FrameType::Time => {
-                assert_eq!(frame.time(), Some(0xFFFFF8));
+                assert_eq!(frame.time(), Some(SensorTime::from_ticks(0xFFFFF8)));
             }
             FrameType::Control => {
                 assert!(frame.fifo_src_chg().unwrap());
@@ -591,12 +591,11 @@ fn config_accel() {
     let mut expected_io = Transactions(Vec::new());
     init(&mut expected_io);
 
-    expected_io.push_write(0x19, &mut vec![0xE2]);
-    expected_io.push_write(0x1A, &mut vec![0xFB]);
-    expected_io.push_write(0x1B, &mut vec![0x08]);
-    expected_io.push_write(0x19, &mut vec![0x00]);
-    expected_io.push_write(0x1A, &mut vec![0x05]);
-    expected_io.push_write(0x1B, &mut vec![0x00]);
+    expected_io.push_write(0x19, &mut vec![0xE2, 0xFB, 0x08]);
+    // Device reports the new power mode
+    expected_io.push_read(0x83, vec![0x14]);
+    expected_io.push_write(0x19, &mut vec![0x00, 0x05, 0x00]);
+    expected_io.push_read(0x83, vec![0x10]);
 
     let mut device = new(&expected_io);
 
@@ -636,10 +635,8 @@ fn config_interrupts() {
     expected_io.push_write(0x56, &mut vec![0x10]);
     expected_io.push_write(0x3F, &mut vec![0x10]);
     expected_io.push_write(0x4A, &mut vec![0x10]);
-    expected_io.push_write(0x1F, &mut vec![0xEE]);
-    expected_io.push_write(0x20, &mut vec![0x9D]);
-    expected_io.push_write(0x1F, &mut vec![0x00]);
-    expected_io.push_write(0x20, &mut vec![0x00]);
+    expected_io.push_write(0x1F, &mut vec![0xEE, 0x9D]);
+    expected_io.push_write(0x1F, &mut vec![0x00, 0x00]);
 
     let mut device = new(&expected_io);
 
@@ -701,16 +698,10 @@ fn config_int_pins() {
     let mut expected_io = Transactions(Vec::new());
     init(&mut expected_io);
 
-    expected_io.push_write(0x21, &mut vec![0xFF]);
-    expected_io.push_write(0x22, &mut vec![0xFF]);
-    expected_io.push_write(0x23, &mut vec![0xDD]);
-    expected_io.push_write(0x24, &mut vec![0x66]);
+    expected_io.push_write(0x21, &mut vec![0xFF, 0xFF, 0xDD, 0x66]);
     expected_io.push_write(0x21, &mut vec![0x00]);
-    expected_io.push_write(0x23, &mut vec![0xD0]);
-    expected_io.push_write(0x24, &mut vec![0x60]);
-    expected_io.push_write(0x22, &mut vec![0x00]);
-    expected_io.push_write(0x23, &mut vec![0x00]);
-    expected_io.push_write(0x24, &mut vec![0x00]);
+    expected_io.push_write(0x23, &mut vec![0xD0, 0x60]);
+    expected_io.push_write(0x22, &mut vec![0x00, 0x00, 0x00]);
 
     let mut device = new(&expected_io);
 
@@ -776,14 +767,8 @@ fn config_fifo() {
     let mut expected_io = Transactions(Vec::new());
     init(&mut expected_io);
 
-    expected_io.push_write(0x26, &mut vec![0xFF]);
-    expected_io.push_write(0x27, &mut vec![0xFF]);
-    expected_io.push_write(0x28, &mut vec![0x03]);
-    expected_io.push_write(0x29, &mut vec![0x01]);
-    expected_io.push_write(0x26, &mut vec![0x00]);
-    expected_io.push_write(0x27, &mut vec![0x00]);
-    expected_io.push_write(0x28, &mut vec![0x00]);
-    expected_io.push_write(0x29, &mut vec![0x00]);
+    expected_io.push_write(0x26, &mut vec![0xFF, 0xFF, 0x03, 0x01]);
+    expected_io.push_write(0x26, &mut vec![0x00, 0x00, 0x00, 0x00]);
 
     let mut device = new(&expected_io);
 
@@ -822,10 +807,8 @@ fn config_auto_lp() {
     let mut expected_io = Transactions(Vec::new());
     init(&mut expected_io);
 
-    expected_io.push_write(0x2A, &mut vec![0xFF]);
-    expected_io.push_write(0x2B, &mut vec![0xFB]);
-    expected_io.push_write(0x2A, &mut vec![0x00]);
-    expected_io.push_write(0x2B, &mut vec![0x00]);
+    expected_io.push_write(0x2A, &mut vec![0xFF, 0xFB]);
+    expected_io.push_write(0x2A, &mut vec![0x00, 0x00]);
 
     let mut device = new(&expected_io);
 
@@ -856,10 +839,8 @@ fn config_autowkup() {
     let mut expected_io = Transactions(Vec::new());
     init(&mut expected_io);
 
-    expected_io.push_write(0x2C, &mut vec![0xFF]);
-    expected_io.push_write(0x2D, &mut vec![0xF6]);
-    expected_io.push_write(0x2C, &mut vec![0x00]);
-    expected_io.push_write(0x2D, &mut vec![0x00]);
+    expected_io.push_write(0x2C, &mut vec![0xFF, 0xF6]);
+    expected_io.push_write(0x2C, &mut vec![0x00, 0x00]);
 
     let mut device = new(&expected_io);
 
@@ -888,20 +869,13 @@ fn config_wkup_int() {
     let mut expected_io = Transactions(Vec::new());
     init(&mut expected_io);
 
-    expected_io.push_write(0x30, &mut vec![0xFF]);
-    expected_io.push_write(0x31, &mut vec![0xFF]);
-    expected_io.push_write(0x32, &mut vec![0xFF]);
-    expected_io.push_write(0x33, &mut vec![0xFF]);
+    expected_io.push_write(0x30, &mut vec![0xFF, 0xFF, 0xFF, 0xFF]);
 
     // Enable the interrupt last
     expected_io.push_write(0x2F, &mut vec![0xFE]);
 
     // Disable the interrupt first, keeping other settings intact
-    expected_io.push_write(0x2F, &mut vec![0x1E]);
-    expected_io.push_write(0x30, &mut vec![0x00]);
-    expected_io.push_write(0x31, &mut vec![0x00]);
-    expected_io.push_write(0x32, &mut vec![0x00]);
-    expected_io.push_write(0x33, &mut vec![0x00]);
+    expected_io.push_write(0x2F, &mut vec![0x1E, 0x00, 0x00, 0x00, 0x00]);
 
     // Re-enable / write IntConfig0 changes last
     expected_io.push_write(0x2F, &mut vec![0x00]);
@@ -942,25 +916,11 @@ fn config_orientchg_int() {
         expected_io.push_write(*addr, &mut expected.into());
     };
 
-    write(vec![0x35, 0xF8]);
-    write(vec![0x36, 0xFF]);
-    write(vec![0x38, 0xFF]);
-    write(vec![0x39, 0xFF]);
-    write(vec![0x3A, 0x0F]);
-    write(vec![0x3B, 0xFF]);
-    write(vec![0x3C, 0x0F]);
-    write(vec![0x3D, 0xFF]);
-    write(vec![0x3E, 0x0F]);
-
-    write(vec![0x35, 0x00]);
-    write(vec![0x36, 0x00]);
-    write(vec![0x38, 0x00]);
-    write(vec![0x39, 0x00]);
-    write(vec![0x3A, 0x00]);
-    write(vec![0x3B, 0x00]);
-    write(vec![0x3C, 0x00]);
-    write(vec![0x3D, 0x00]);
-    write(vec![0x3E, 0x00]);
+    write(vec![0x35, 0xF8, 0xFF]);
+    write(vec![0x38, 0xFF, 0xFF, 0x0F, 0xFF, 0x0F, 0xFF, 0x0F]);
+
+    write(vec![0x35, 0x00, 0x00]);
+    write(vec![0x38, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
 
     let mut device = new(&expected_io);
 
@@ -1000,29 +960,13 @@ fn config_gen1_int() {
         expected_io.push_write(*addr, &mut expected.into());
     };
 
-    write(vec![0x3F, 0xFF]);
-    write(vec![0x40, 0x03]);
-    write(vec![0x41, 0xFF]);
-    write(vec![0x42, 0xFF]);
-    write(vec![0x43, 0xFF]);
-    write(vec![0x44, 0xFF]);
-    write(vec![0x45, 0x0F]);
-    write(vec![0x46, 0xFF]);
-    write(vec![0x47, 0x0F]);
-    write(vec![0x48, 0xFF]);
-    write(vec![0x49, 0x0F]);
-
-    write(vec![0x3F, 0x00]);
-    write(vec![0x40, 0x00]);
-    write(vec![0x41, 0x00]);
-    write(vec![0x42, 0x00]);
-    write(vec![0x43, 0x00]);
-    write(vec![0x44, 0x00]);
-    write(vec![0x45, 0x00]);
-    write(vec![0x46, 0x00]);
-    write(vec![0x47, 0x00]);
-    write(vec![0x48, 0x00]);
-    write(vec![0x49, 0x00]);
+    write(vec![
+        0x3F, 0xFF, 0x03, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 0xFF, 0x0F, 0xFF, 0x0F,
+    ]);
+
+    write(vec![
+        0x3F, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ]);
 
     let mut device = new(&expected_io);
 
@@ -1068,29 +1012,13 @@ fn config_gen2_int() {
         expected_io.push_write(*addr, &mut expected.into());
     };
 
-    write(vec![0x4A, 0xFF]);
-    write(vec![0x4B, 0x03]);
-    write(vec![0x4C, 0xFF]);
-    write(vec![0x4D, 0xFF]);
-    write(vec![0x4E, 0xFF]);
-    write(vec![0x4F, 0xFF]);
-    write(vec![0x50, 0x0F]);
-    write(vec![0x51, 0xFF]);
-    write(vec![0x52, 0x0F]);
-    write(vec![0x53, 0xFF]);
-    write(vec![0x54, 0x0F]);
-
-    write(vec![0x4A, 0x00]);
-    write(vec![0x4B, 0x00]);
-    write(vec![0x4C, 0x00]);
-    write(vec![0x4D, 0x00]);
-    write(vec![0x4E, 0x00]);
-    write(vec![0x4F, 0x00]);
-    write(vec![0x50, 0x00]);
-    write(vec![0x51, 0x00]);
-    write(vec![0x52, 0x00]);
-    write(vec![0x53, 0x00]);
-    write(vec![0x54, 0x00]);
+    write(vec![
+        0x4A, 0xFF, 0x03, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 0xFF, 0x0F, 0xFF, 0x0F,
+    ]);
+
+    write(vec![
+        0x4A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ]);
 
     let mut device = new(&expected_io);
 
@@ -1131,10 +1059,8 @@ fn config_actchg_int() {
     let mut expected_io = Transactions(Vec::new());
     init(&mut expected_io);
 
-    expected_io.push_write(0x55, &mut vec![0xFF]);
-    expected_io.push_write(0x56, &mut vec![0xF4]);
-    expected_io.push_write(0x55, &mut vec![0x00]);
-    expected_io.push_write(0x56, &mut vec![0x00]);
+    expected_io.push_write(0x55, &mut vec![0xFF, 0xF4]);
+    expected_io.push_write(0x55, &mut vec![0x00, 0x00]);
 
     let mut device = new(&expected_io);
 
@@ -1165,10 +1091,8 @@ fn config_tap() {
     let mut expected_io = Transactions(Vec::new());
     init(&mut expected_io);
 
-    expected_io.push_write(0x57, &mut vec![0x17]);
-    expected_io.push_write(0x58, &mut vec![0x3F]);
-    expected_io.push_write(0x57, &mut vec![0x00]);
-    expected_io.push_write(0x58, &mut vec![0x00]);
+    expected_io.push_write(0x57, &mut vec![0x17, 0x3F]);
+    expected_io.push_write(0x57, &mut vec![0x00, 0x00]);
 
     let mut device = new(&expected_io);
 
@@ -1328,16 +1252,11 @@ fn perform_self_test() {
     expected_io.push_write(0x4A, &mut vec![0x10]);
 
     // Set non-power mode settings in AccConfig0
-    expected_io.push_write(0x19, &mut vec![0xE0]);
-
     // Set Range = 2G, OSR = OSR0, ODR = 200Hz
-    expected_io.push_write(0x1A, &mut vec![0x09]);
-
-    // Set IntConfig0
-    expected_io.push_write(0x1F, &mut vec![0xEE]);
+    expected_io.push_write(0x19, &mut vec![0xE0, 0x09]);
 
-    // Set IntConfig1
-    expected_io.push_write(0x20, &mut vec![0x9D]);
+    // Set IntConfig0, IntConfig1
+    expected_io.push_write(0x1F, &mut vec![0xEE, 0x9D]);
 
     // Set Wakeup Int, Settings
     expected_io.push_write(0x2D, &mut vec![0xF6]);
@@ -1449,6 +1368,7 @@ fn soft_reset() {
     let mut expected_io = Transactions(Vec::new());
     init(&mut expected_io);
 
+    expected_io.push_read(0x83, vec![0x10]);
     expected_io.push_write(0x7E, &mut vec![0xB6]);
     expected_io.push_read(0x8D, vec![0x01]);
 
@@ -1456,3 +1376,162 @@ fn soft_reset() {
     device.soft_reset().unwrap();
     cleanup(device);
 }
+
+#[test]
+fn soft_reset_with_delay() {
+    let mut expected_io = Transactions(Vec::new());
+    init(&mut expected_io);
+
+    expected_io.push_read(0x83, vec![0x10]);
+    expected_io.push_write(0x7E, &mut vec![0xB6]);
+    // Dummy read to return to SPI mode
+    expected_io.push_read(0x80, vec![0x00]);
+    expected_io.push_read(0x80, vec![0x90]);
+    expected_io.push_read(0x8D, vec![0x01]);
+    // Reset not reported
+    expected_io.push_read(0x83, vec![0x10]);
+    expected_io.push_write(0x7E, &mut vec![0xB6]);
+    expected_io.push_read(0x80, vec![0x00]);
+    expected_io.push_read(0x80, vec![0x90]);
+    expected_io.push_read(0x8D, vec![0x00]);
+
+    let expected_delay = [DelayTransaction::delay_ms(5), DelayTransaction::delay_ms(5)];
+    let mut timer = CheckedDelay::new(&expected_delay);
+    let mut device = new(&expected_io);
+    device.soft_reset_with_delay(&mut timer, false).unwrap();
+    assert!(matches!(
+        device.soft_reset_with_delay(&mut timer, false),
+        Err(BMA400Error::ResetFailed)
+    ));
+    timer.done();
+    cleanup(device);
+}
+
+fn configured_registers() -> Vec<u8> {
+    let mut registers = vec![0u8; 64];
+    // AccConfig0: Normal Mode
+    registers[0x00] = 0x02;
+    // AccConfig1: Range4G, ODR 200Hz
+    registers[0x01] = 0x49;
+    // IntConfig0: Data Ready Interrupt Enabled
+    registers[0x06] = 0x80;
+    // Int12IOCtrl
+    registers[0x0B] = 0x22;
+    // TapConfig1
+    registers[0x3F] = 0x06;
+    registers
+}
+
+#[test]
+fn new_adopt() {
+    let mut expected_io = Transactions(Vec::new());
+    init(&mut expected_io);
+    expected_io.push_read(0x99, configured_registers());
+    // Only the power mode differs from the adopted configuration
+    expected_io.push_write(0x19, &mut vec![0x00]);
+    expected_io.push_read(0x83, vec![0x10]);
+    let spi = MockSPI::new(&expected_io.0);
+    let mut device = BMA400::new_spi_adopt(spi).unwrap();
+    device
+        .config_accel()
+        .with_power_mode(PowerMode::Sleep)
+        .with_scale(Scale::Range4G)
+        .write()
+        .unwrap();
+    device
+        .config_interrupts()
+        .with_dta_rdy_int(true)
+        .write()
+        .unwrap();
+    cleanup(device);
+}
+
+#[test]
+fn new_3wire_adopt() {
+    let mut expected_io = Transactions(Vec::new());
+    init(&mut expected_io);
+    expected_io.push_write(0x7C, &mut vec![0x01]);
+    expected_io.push_read(0x99, configured_registers());
+    let mut spi = MockSPI::new(&expected_io.0);
+    let result = BMA400::new_spi_3wire_adopt(&mut spi);
+    assert!(result.is_ok());
+    spi.done();
+}
+
+#[test]
+fn resync_config() {
+    let mut expected_io = Transactions(Vec::new());
+    init(&mut expected_io);
+    expected_io.push_read(0x99, configured_registers());
+    expected_io.push_write(0x1F, &mut vec![0x00]);
+    let mut device = new(&expected_io);
+    device.resync_config().unwrap();
+    // The shadow config now matches the device, so only the change is written
+    device
+        .config_accel()
+        .with_power_mode(PowerMode::Normal)
+        .write()
+        .unwrap();
+    device
+        .config_interrupts()
+        .with_dta_rdy_int(false)
+        .write()
+        .unwrap();
+    cleanup(device);
+}
+
+#[test]
+fn verify_writes() {
+    let mut expected_io = Transactions(Vec::new());
+    init(&mut expected_io);
+    // Write, read back, check ErrReg
+    expected_io.push_write(0x19, &mut vec![0x02]);
+    expected_io.push_read(0x99, vec![0x02]);
+    expected_io.push_read(0x82, vec![0x00]);
+    expected_io.push_read(0x83, vec![0x14]);
+    // Value not accepted
+    expected_io.push_write(0x19, &mut vec![0x00]);
+    expected_io.push_read(0x99, vec![0x02]);
+    // Previous value restored
+    expected_io.push_write(0x19, &mut vec![0x02]);
+    // Enabled for a single builder
+    expected_io.push_write(0x1A, &mut vec![0x48]);
+    expected_io.push_read(0x9A, vec![0x48]);
+    expected_io.push_read(0x82, vec![0x02]);
+    expected_io.push_write(0x1A, &mut vec![0x49]);
+
+    let mut device = new(&expected_io);
+    device.set_verify_writes(true);
+    device
+        .config_accel()
+        .with_power_mode(PowerMode::Normal)
+        .write()
+        .unwrap();
+    let result = device
+        .config_accel()
+        .with_power_mode(PowerMode::Sleep)
+        .write();
+    assert!(matches!(
+        result,
+        Err(BMA400Error::VerifyFailed {
+            register: 0x19,
+            expected: 0x00,
+            actual: 0x02
+        })
+    ));
+    device.set_verify_writes(false);
+    let result = device
+        .config_accel()
+        .with_odr(OutputDataRate::Hz100)
+        .with_verify_writes(true)
+        .write();
+    assert!(matches!(
+        result,
+        Err(BMA400Error::VerifyFailed {
+            register: 0x02,
+            expected: 0x00,
+            actual: 0x02
+        })
+    ));
+    cleanup(device);
+}